@@ -0,0 +1,21 @@
+use actix_web::{web, HttpResponse, Responder};
+use ark_ff::PrimeField;
+use crate::models::response::Challenge;
+use crate::utils::challenge_store::{ChallengeStore, CHALLENGE_SWEEP_INTERVAL, CHALLENGE_TTL};
+
+/// Issues a single-use nonce that a prover must bind into the proof it submits for verification.
+pub async fn issue_challenge(challenges: web::Data<ChallengeStore>) -> impl Responder {
+    // Full until the next sweep frees expired challenges or verifications consume some
+    let Some(nonce) = challenges.issue() else {
+        return HttpResponse::ServiceUnavailable()
+            .insert_header(("Retry-After", CHALLENGE_SWEEP_INTERVAL.as_secs().to_string()))
+            .body("Too many outstanding challenges");
+    };
+
+    let challenge = Challenge {
+        nonce: nonce.into_bigint().to_string(),
+        expires_in_secs: CHALLENGE_TTL.as_secs(),
+    };
+
+    HttpResponse::Ok().json(challenge)
+}
@@ -0,0 +1,83 @@
+use actix_web::{web, HttpResponse, Responder};
+use ark_bn254::Bn254;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgeVerification};
+use zkp_core::circuits::citizenship_verification::witness_calculator::CitizenshipVerification;
+use zkp_core::circuits::college_credential_verification::witness_calculator::CollegeCredentialVerification;
+use zkp_core::circuits::date_of_birth_verification::witness_calculator::DateOfBirthVerification;
+use zkp_core::circuits::signed_age_verification::witness_calculator::SignedAgeVerification;
+use zkp_core::error::ZkpError;
+use zkp_core::inspect::{inspect, CircuitInfo, SerializedSize};
+use crate::utils::zkp_error_response;
+use crate::models::response::{CircuitDescription, CircuitsQuery, EncodedSize};
+
+// (num_bits, depth) -> descriptions. Inspecting a circuit synthesizes it and sizes its keys, so each shape is only
+// described once; shapes are validated first, which bounds the cache to the valid ones.
+type DescriptionCache = HashMap<(usize, usize), Arc<Vec<CircuitDescription>>>;
+
+/// Lists every circuit of `zkp_core` with its dimensions and key sizes, at the requested shapes.
+pub async fn list_circuits(query: web::Query<CircuitsQuery>) -> impl Responder {
+    match cached_descriptions(query.num_bits, query.depth) {
+        Ok(circuits) => HttpResponse::Ok().json(&*circuits),
+        Err(e) => zkp_error_response("Circuit inspection error", &e),
+    }
+}
+
+fn cached_descriptions(num_bits: usize, depth: usize) -> Result<Arc<Vec<CircuitDescription>>, ZkpError> {
+    static CACHE: OnceLock<Mutex<DescriptionCache>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(circuits) = cache.lock().expect("circuit description cache lock poisoned").get(&(num_bits, depth)) {
+        return Ok(circuits.clone());
+    }
+
+    // Described without the lock held, so a deep tree does not stall requests for other shapes
+    let circuits = Arc::new(describe_circuits(num_bits, depth)?);
+    cache.lock().expect("circuit description cache lock poisoned").insert((num_bits, depth), circuits.clone());
+    Ok(circuits)
+}
+
+fn describe_circuits(num_bits: usize, depth: usize) -> Result<Vec<CircuitDescription>, ZkpError> {
+    let mut circuits = Vec::new();
+    for upper_bounded in [false, true] {
+        let info = inspect::<Bn254, AgeVerification>(&AgeConfig { num_bits, upper_bounded })?;
+        circuits.push(CircuitDescription {
+            num_bits: Some(num_bits),
+            upper_bounded: Some(upper_bounded),
+            ..describe(Some("/age_verification"), info)
+        });
+    }
+
+    let info = inspect::<Bn254, CitizenshipVerification>(&depth)?;
+    circuits.push(CircuitDescription { depth: Some(depth), ..describe(Some("/citizenship"), info) });
+
+    let info = inspect::<Bn254, CollegeCredentialVerification>(&())?;
+    circuits.push(describe(Some("/college_degree"), info));
+
+    // Built into zkp_core but not served over HTTP yet
+    circuits.push(describe(None, inspect::<Bn254, DateOfBirthVerification>(&())?));
+    circuits.push(describe(None, inspect::<Bn254, SignedAgeVerification>(&())?));
+
+    Ok(circuits)
+}
+
+// Description of a circuit without a shape; callers fill in the parameters of the ones that have one
+fn describe(endpoint: Option<&str>, info: CircuitInfo) -> CircuitDescription {
+    let size = |size: SerializedSize| EncodedSize { compressed: size.compressed, uncompressed: size.uncompressed };
+    CircuitDescription {
+        name: info.name.to_string(),
+        version: info.version,
+        curve: info.curve.to_string(),
+        endpoint: endpoint.map(str::to_string),
+        num_bits: None,
+        upper_bounded: None,
+        depth: None,
+        fingerprint: info.fingerprint.to_string(),
+        num_constraints: info.num_constraints,
+        num_witness_variables: info.num_witness_variables,
+        num_public_inputs: info.num_public_inputs,
+        proving_key_size: size(info.proving_key_size),
+        verifying_key_size: size(info.verifying_key_size),
+        proof_size: size(info.proof_size),
+    }
+}
@@ -0,0 +1,102 @@
+use actix_web::{web, HttpResponse, Responder};
+use ark_bn254::Fr;
+use zkp_core::error::ZkpError;
+use zkp_core::proof_system;
+use zkp_core::circuits::citizenship_verification::witness_calculator::MerklePath;
+use crate::utils::{zkp_error_response, parse_field_element, parse_public_key, parse_signature};
+use crate::models::response::InputCheck;
+use crate::models::proof_generation::{
+    AgeInputCheckRequest,
+    CitizenshipInputCheckRequest,
+    CollegeCredentialInputCheckRequest,
+};
+
+// Dry runs: the inputs are synthesized into the circuit and every constraint is evaluated, but no keys are needed
+// and nothing is proven. Clients can find out why a proof would fail before uploading a proving key.
+
+/// Checks age inputs against the age circuit without proving.
+pub async fn check_age_inputs(req: web::Json<AgeInputCheckRequest>) -> impl Responder {
+    let nonce = match parse_optional_nonce(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
+    };
+
+    let result = proof_system::check_age_inputs(nonce, req.num_bits, req.user_age, req.min_age, req.max_age);
+    input_check_response("Age input check error", result)
+}
+
+/// Checks a leaf and Merkle path against the citizenship circuit without proving.
+pub async fn check_citizenship_inputs(req: web::Json<CitizenshipInputCheckRequest>) -> impl Responder {
+    let nonce = match parse_optional_nonce(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
+    };
+    let merkle_root = match parse_field_element(&req.merkle_root) {
+        Ok(root) => root,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Merkle root parsing error: {}", e)),
+    };
+    let leaf = match parse_field_element(&req.leaf) {
+        Ok(leaf) => leaf,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Leaf parsing error: {}", e)),
+    };
+    let siblings = match req.path.siblings.iter().map(|s| parse_field_element(s)).collect::<Result<Vec<_>, _>>() {
+        Ok(siblings) => siblings,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Merkle path parsing error: {}", e)),
+    };
+    let path = MerklePath {
+        siblings,
+        directions: req.path.directions.clone(),
+    };
+
+    let result = proof_system::check_citizenship_inputs(nonce, req.depth, merkle_root, leaf, path);
+    input_check_response("Citizenship input check error", result)
+}
+
+/// Checks a signed credential against the college credential circuit without proving.
+pub async fn check_college_credential_inputs(req: web::Json<CollegeCredentialInputCheckRequest>) -> impl Responder {
+    let nonce = match parse_optional_nonce(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
+    };
+    let university_public_key = match parse_public_key(&req.university_public_key.x, &req.university_public_key.y) {
+        Ok(key) => key,
+        Err(e) => return HttpResponse::BadRequest().body(format!("University public key parsing error: {}", e)),
+    };
+    let credential = match parse_field_element(&req.credential) {
+        Ok(credential) => credential,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Credential parsing error: {}", e)),
+    };
+    let holder_secret = match parse_field_element(&req.holder_secret) {
+        Ok(secret) => secret,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Holder secret parsing error: {}", e)),
+    };
+    let signature = match parse_signature(&req.signature.r.x, &req.signature.r.y, &req.signature.s) {
+        Ok(signature) => signature,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Signature parsing error: {}", e)),
+    };
+
+    let result = proof_system::check_college_credential_inputs(
+        nonce,
+        &university_public_key,
+        credential,
+        holder_secret,
+        &signature,
+    );
+    input_check_response("College credential input check error", result)
+}
+
+// The nonce does not affect satisfiability, so a check may be run before any challenge was issued
+fn parse_optional_nonce(nonce: &Option<String>) -> Result<Fr, Box<dyn std::error::Error>> {
+    nonce.as_deref().map_or(Ok(Fr::from(0u64)), parse_field_element)
+}
+
+// Unsatisfied inputs are a successful check with a negative answer; malformed ones are still rejected
+fn input_check_response(context: &str, result: Result<(), ZkpError>) -> HttpResponse {
+    match result {
+        Ok(()) => HttpResponse::Ok().json(InputCheck { satisfied: true, unsatisfied_constraint: None }),
+        Err(ZkpError::UnsatisfiedConstraint { constraint }) => {
+            HttpResponse::Ok().json(InputCheck { satisfied: false, unsatisfied_constraint: constraint })
+        }
+        Err(e) => zkp_error_response(context, &e),
+    }
+}
@@ -1,19 +1,19 @@
-use actix_web::{HttpResponse, Responder};
-use zkp_core::proof_system;
-use crate::utils::utils::{serialize_proving_key, serialize_verifying_key};
-use crate::models::response::Keys;
+use actix_web::{web, HttpResponse, Responder};
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgeVerification};
+use zkp_core::circuits::citizenship_verification::witness_calculator::CitizenshipVerification;
+use zkp_core::circuits::college_credential_verification::witness_calculator::CollegeCredentialVerification;
+use crate::utils::{zkp_error_response, setup, serialize_proving_key, serialize_verifying_key};
+use crate::models::response::{AgeKeysQuery, CitizenshipKeysQuery, CollegeKeysQuery, Keys};
 
-/// Generate keys using the age verification circuit setup
-pub async fn generate_age_keys() -> impl Responder {
-    let (pk, vk) = match proof_system::setup_age_verification_circuit() {
+/// Generate keys using the age verification circuit setup for the requested bit width, bound and proving system.
+pub async fn generate_age_keys(query: web::Query<AgeKeysQuery>) -> impl Responder {
+    let config = AgeConfig { num_bits: query.num_bits, upper_bounded: query.upper_bounded };
+    let (pk, vk) = match setup::<AgeVerification>(&config, query.proving_system) {
         Ok(keys) => keys,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .body(format!("Age circuit setup error: {:?}", e))
-        },
+        Err(e) => return zkp_error_response("Age circuit setup error", &e),
     };
 
-    let proving_key_str = match serialize_proving_key(&pk) {
+    let proving_key_str = match serialize_proving_key(&pk, query.compressed) {
         Ok(s) => s,
         Err(e) => {
             return HttpResponse::InternalServerError()
@@ -21,7 +21,7 @@ pub async fn generate_age_keys() -> impl Responder {
         },
     };
 
-    let verifying_key_str = match serialize_verifying_key(&vk) {
+    let verifying_key_str = match serialize_verifying_key(&vk, query.compressed) {
         Ok(s) => s,
         Err(e) => {
             return HttpResponse::InternalServerError()
@@ -37,17 +37,14 @@ pub async fn generate_age_keys() -> impl Responder {
     HttpResponse::Ok().json(keys)
 }
 
-/// Generate keys using the citizenship verification circuit setup.
-pub async fn generate_citizenship_keys() -> impl Responder {
-    let (pk, vk) = match proof_system::setup_citizenship_verification_circuit() {
+/// Generate keys using the citizenship verification circuit setup for the requested tree depth and proving system.
+pub async fn generate_citizenship_keys(query: web::Query<CitizenshipKeysQuery>) -> impl Responder {
+    let (pk, vk) = match setup::<CitizenshipVerification>(&query.depth, query.proving_system) {
         Ok(keys) => keys,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .body(format!("Citizenship circuit setup error: {:?}", e))
-        },
+        Err(e) => return zkp_error_response("Citizenship circuit setup error", &e),
     };
 
-    let proving_key_str = match serialize_proving_key(&pk) {
+    let proving_key_str = match serialize_proving_key(&pk, query.compressed) {
         Ok(s) => s,
         Err(e) => {
             return HttpResponse::InternalServerError()
@@ -55,7 +52,7 @@ pub async fn generate_citizenship_keys() -> impl Responder {
         },
     };
 
-    let verifying_key_str = match serialize_verifying_key(&vk) {
+    let verifying_key_str = match serialize_verifying_key(&vk, query.compressed) {
         Ok(s) => s,
         Err(e) => {
             return HttpResponse::InternalServerError()
@@ -71,17 +68,14 @@ pub async fn generate_citizenship_keys() -> impl Responder {
     HttpResponse::Ok().json(keys)
 }
 
-/// Generate keys using the college credential verification circuit setup.
-pub async fn generate_college_credential_keys() -> impl Responder {
-    let (pk, vk) = match proof_system::setup_credential_verification_circuit() {
+/// Generate keys using the college credential verification circuit setup for the requested proving system.
+pub async fn generate_college_credential_keys(query: web::Query<CollegeKeysQuery>) -> impl Responder {
+    let (pk, vk) = match setup::<CollegeCredentialVerification>(&(), query.proving_system) {
         Ok(keys) => keys,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .body(format!("College credential circuit setup error: {:?}", e))
-        },
+        Err(e) => return zkp_error_response("College credential circuit setup error", &e),
     };
 
-    let proving_key_str = match serialize_proving_key(&pk) {
+    let proving_key_str = match serialize_proving_key(&pk, query.compressed) {
         Ok(s) => s,
         Err(e) => {
             return HttpResponse::InternalServerError()
@@ -89,7 +83,7 @@ pub async fn generate_college_credential_keys() -> impl Responder {
         },
     };
 
-    let verifying_key_str = match serialize_verifying_key(&vk) {
+    let verifying_key_str = match serialize_verifying_key(&vk, query.compressed) {
         Ok(s) => s,
         Err(e) => {
             return HttpResponse::InternalServerError()
@@ -1,3 +1,6 @@
 pub mod proof_generator_controller;
 pub mod proof_verifier_controller;
-pub mod keys_generator_controller;
\ No newline at end of file
+pub mod keys_generator_controller;
+pub mod challenge_controller;
+pub mod input_checker_controller;
+pub mod circuits_controller;
@@ -1,7 +1,33 @@
 use actix_web::{web, HttpResponse, Responder};
-use ark_bn254::Fr;
-use zkp_core::proof_system;
-use crate::utils::utils::{serialize_proof, deserialize_proving_key};
+use zkp_core::attribute::AttributeCircuit;
+use zkp_core::circuits::age_verification::witness_calculator::{
+    AgeConfig,
+    AgePrivateInputs,
+    AgePublicInputs,
+    AgeVerification,
+};
+use zkp_core::circuits::citizenship_verification::witness_calculator::{
+    CitizenshipPrivateInputs,
+    CitizenshipPublicInputs,
+    CitizenshipVerification,
+    MerklePath,
+};
+use zkp_core::circuits::college_credential_verification::witness_calculator::{
+    CollegeCredentialPrivateInputs,
+    CollegeCredentialPublicInputs,
+    CollegeCredentialVerification,
+};
+use crate::utils::{
+    zkp_error_response,
+    prove,
+    check_proof_format,
+    serialize_proof_payload,
+    public_signals,
+    deserialize_proving_key,
+    parse_field_element,
+    parse_public_key,
+    parse_signature,
+};
 use crate::models::response::GeneratedProof;
 use crate::models::proof_generation::{
     AgeProofGenerationRequest,
@@ -14,23 +40,38 @@ pub async fn generate_age_proof(req: web::Json<AgeProofGenerationRequest>) -> im
     // Deserialize the provided proving key.
     let pk = match deserialize_proving_key(&req.proving_key) {
         Ok(key) => key,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Proving key deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Proving key deserialization error: {}", e)),
+    };
+    if let Err(e) = check_proof_format(&pk, req.proof_format) {
+        return HttpResponse::BadRequest().body(format!("Proof format error: {}", e));
+    }
+
+    // Convert the verifier's challenge into a field element.
+    let nonce = match parse_field_element(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
     };
 
     // Generate the proof using the provided keys.
-    let proof = match proof_system::prove_age(&pk, req.user_age, req.min_age) {
+    let config = AgeConfig { num_bits: req.num_bits, upper_bounded: req.max_age.is_some() };
+    let public = AgePublicInputs { nonce, min_age: req.min_age, max_age: req.max_age };
+    let private = AgePrivateInputs { user_age: req.user_age };
+    let proof = match prove::<AgeVerification>(&pk, &config, &public, &private) {
         Ok(proof) => proof,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Age proof generation error: {:?}", e)),
+        Err(e) => return zkp_error_response("Age proof generation error", &e),
     };
 
     // Serialize the proof.
-    let proof_str = match serialize_proof(&proof) {
-        Ok(s) => s,
+    let proof_payload = match serialize_proof_payload(&proof, pk.fingerprint(), req.compressed, req.proof_format) {
+        Ok(payload) => payload,
         Err(e) => return HttpResponse::InternalServerError().body(format!("Proof serialization error: {:?}", e)),
     };
 
+    let public_inputs = <AgeVerification as AttributeCircuit>::public_inputs(&public);
+
     let response = GeneratedProof {
-        proof: proof_str,
+        proof: proof_payload,
+        public_signals: public_signals(req.proof_format, &public_inputs),
         // Echo back the provided keys.
         proving_key: Some(req.proving_key.clone()),
         verifying_key: Some(req.verifying_key.clone()),
@@ -44,28 +85,53 @@ pub async fn generate_citizenship_proof(req: web::Json<CitizenshipProofGeneratio
     // Deserialize the provided proving key.
     let pk = match deserialize_proving_key(&req.proving_key) {
         Ok(key) => key,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Proving key deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Proving key deserialization error: {}", e)),
     };
+    if let Err(e) = check_proof_format(&pk, req.proof_format) {
+        return HttpResponse::BadRequest().body(format!("Proof format error: {}", e));
+    }
 
     // Convert inputs to field elements.
-    let merkle_root = Fr::from(req.merkle_root);
-    let path = Fr::from(req.path);
-    let leaf = Fr::from(req.leaf);
+    let nonce = match parse_field_element(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
+    };
+    let merkle_root = match parse_field_element(&req.merkle_root) {
+        Ok(root) => root,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Merkle root parsing error: {}", e)),
+    };
+    let leaf = match parse_field_element(&req.leaf) {
+        Ok(leaf) => leaf,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Leaf parsing error: {}", e)),
+    };
+    let siblings = match req.path.siblings.iter().map(|s| parse_field_element(s)).collect::<Result<Vec<_>, _>>() {
+        Ok(siblings) => siblings,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Merkle path parsing error: {}", e)),
+    };
+    let path = MerklePath {
+        siblings,
+        directions: req.path.directions.clone(),
+    };
 
     // Generate the proof.
-    let proof = match proof_system::prove_citizenship(&pk, Some(merkle_root), Some(path), Some(leaf)) {
+    let public = CitizenshipPublicInputs { nonce, merkle_root };
+    let private = CitizenshipPrivateInputs { leaf, path };
+    let proof = match prove::<CitizenshipVerification>(&pk, &req.depth, &public, &private) {
         Ok(proof) => proof,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Citizenship proof generation error: {:?}", e)),
+        Err(e) => return zkp_error_response("Citizenship proof generation error", &e),
     };
 
     // Serialize the proof.
-    let proof_str = match serialize_proof(&proof) {
-        Ok(s) => s,
+    let proof_payload = match serialize_proof_payload(&proof, pk.fingerprint(), req.compressed, req.proof_format) {
+        Ok(payload) => payload,
         Err(e) => return HttpResponse::InternalServerError().body(format!("Proof serialization error: {:?}", e)),
     };
 
+    let public_inputs = <CitizenshipVerification as AttributeCircuit>::public_inputs(&public);
+
     let response = GeneratedProof {
-        proof: proof_str,
+        proof: proof_payload,
+        public_signals: public_signals(req.proof_format, &public_inputs),
         proving_key: Some(req.proving_key.clone()),
         verifying_key: Some(req.verifying_key.clone()),
     };
@@ -78,28 +144,53 @@ pub async fn generate_college_credential_proof(req: web::Json<CollegeCredentialP
     // Deserialize the provided proving key.
     let pk = match deserialize_proving_key(&req.proving_key) {
         Ok(key) => key,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Proving key deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Proving key deserialization error: {}", e)),
+    };
+    if let Err(e) = check_proof_format(&pk, req.proof_format) {
+        return HttpResponse::BadRequest().body(format!("Proof format error: {}", e));
+    }
+
+    // Convert inputs to curve points and field elements.
+    let nonce = match parse_field_element(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
+    };
+    let university_public_key = match parse_public_key(&req.university_public_key.x, &req.university_public_key.y) {
+        Ok(key) => key,
+        Err(e) => return HttpResponse::BadRequest().body(format!("University public key parsing error: {}", e)),
+    };
+    let credential = match parse_field_element(&req.credential) {
+        Ok(credential) => credential,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Credential parsing error: {}", e)),
+    };
+    let holder_secret = match parse_field_element(&req.holder_secret) {
+        Ok(secret) => secret,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Holder secret parsing error: {}", e)),
+    };
+    let signature = match parse_signature(&req.signature.r.x, &req.signature.r.y, &req.signature.s) {
+        Ok(signature) => signature,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Signature parsing error: {}", e)),
     };
-
-    // Convert inputs to field elements.
-    let university_public_key = Fr::from(req.university_public_key);
-    let credential = Fr::from(req.credential);
-    let signature = Fr::from(req.signature);
 
     // Generate the proof.
-    let proof = match proof_system::prove_college_credential(&pk, Some(university_public_key), Some(credential), Some(signature)) {
+    let public = CollegeCredentialPublicInputs { nonce, university_public_key };
+    let private = CollegeCredentialPrivateInputs { credential, holder_secret, signature };
+    let proof = match prove::<CollegeCredentialVerification>(&pk, &(), &public, &private) {
         Ok(proof) => proof,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("College credential proof generation error: {:?}", e)),
+        Err(e) => return zkp_error_response("College credential proof generation error", &e),
     };
 
     // Serialize the proof.
-    let proof_str = match serialize_proof(&proof) {
-        Ok(s) => s,
+    let proof_payload = match serialize_proof_payload(&proof, pk.fingerprint(), req.compressed, req.proof_format) {
+        Ok(payload) => payload,
         Err(e) => return HttpResponse::InternalServerError().body(format!("Proof serialization error: {:?}", e)),
     };
 
+    let public_inputs = CollegeCredentialVerification::public_inputs(&public);
+
     let response = GeneratedProof {
-        proof: proof_str,
+        proof: proof_payload,
+        public_signals: public_signals(req.proof_format, &public_inputs),
         proving_key: Some(req.proving_key.clone()),
         verifying_key: Some(req.verifying_key.clone()),
     };
@@ -1,57 +1,196 @@
 use actix_web::{web, HttpResponse, Responder};
 use ark_bn254::Fr;
-use zkp_core::proof_system;
-use crate::utils::utils::{deserialize_proof, deserialize_verifying_key};
+use zkp_core::batch;
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgePublicInputs, AgeVerification};
+use zkp_core::circuits::citizenship_verification::witness_calculator::{
+    CitizenshipPublicInputs,
+    CitizenshipVerification,
+};
+use zkp_core::circuits::college_credential_verification::witness_calculator::{
+    CollegeCredentialPublicInputs,
+    CollegeCredentialVerification,
+};
+#[cfg(feature = "experimental-marlin")]
+use zkp_core::error::ZkpError;
+use zkp_core::keys::check_fingerprint;
+use crate::utils::{
+    zkp_error_response,
+    verify,
+    deserialize_proof,
+    deserialize_verifying_key,
+    parse_field_element,
+    parse_public_key,
+    AnyProof,
+    AnyVerifyingKey,
+};
 use crate::models::proof_verification::{
+    AgeProofBatchVerify,
     AgeProofVerify,
     CitizenshipProofVerify,
     CollegeCredentialProofVerify,
 };
-use crate::models::response::ProofStatus;
+use crate::models::response::{BatchProofStatus, ProofStatus};
+use crate::utils::challenge_store::ChallengeStore;
 
 // Verifies an age verification proof using the provided verifying key.
-pub async fn verify_age_proof(req: web::Json<AgeProofVerify>) -> impl Responder {
+pub async fn verify_age_proof(
+    req: web::Json<AgeProofVerify>,
+    challenges: web::Data<ChallengeStore>,
+) -> impl Responder {
     let vk = match deserialize_verifying_key(&req.verifying_key) {
         Ok(key) => key,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Verifying key deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Verifying key deserialization error: {}", e)),
+    };
+
+    // Decode everything before consuming the challenge, so a malformed request does not burn its nonce
+    let nonce = match parse_field_element(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
     };
 
     // Deserialize the proof from the Base64 string
-    let proof = match deserialize_proof(&req.proof) {
+    let proof = match deserialize_proof(&req.proof, &vk) {
         Ok(proof) => proof,
-        Err(e) => return HttpResponse::BadRequest().body(format!("Proof deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Proof deserialization error: {}", e)),
     };
 
-    // Verify the proof with the provided minimum age as the public input
-    let verified = match proof_system::verify_age(&vk, &proof, req.min_age) {
+    // Likewise reject a key for another circuit or shape first
+    let config = AgeConfig { num_bits: req.num_bits, upper_bounded: req.max_age.is_some() };
+    if let Err(e) = check_fingerprint::<Fr, AgeVerification>(&vk.fingerprint(), &config) {
+        return zkp_error_response("Age proof verification error", &e);
+    }
+
+    // Only accept proofs bound to a challenge this verifier issued; each challenge is good for one attempt
+    if !challenges.consume(&nonce) {
+        return HttpResponse::BadRequest().body("Unknown, expired or already used challenge nonce");
+    }
+
+    // Verify the proof with the provided age bounds as the public inputs
+    let public = AgePublicInputs { nonce, min_age: req.min_age, max_age: req.max_age };
+    let verified = match verify::<AgeVerification>(&vk, &config, &proof, &public) {
         Ok(result) => result,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Age proof verification error: {:?}", e)),
+        Err(e) => return zkp_error_response("Age proof verification error", &e),
     };
 
     let response = ProofStatus { proof_status: verified };
     HttpResponse::Ok().json(response)
 }
 
+// Verifies a batch of age verification proofs made with the same verifying key, in a single combined check for
+// Groth16 keys and one by one for Marlin keys. Proofs whose challenge nonce is unknown, expired or already used are
+// reported as failed without being checked.
+pub async fn batch_verify_age_proofs(
+    req: web::Json<AgeProofBatchVerify>,
+    challenges: web::Data<ChallengeStore>,
+) -> impl Responder {
+    let vk = match deserialize_verifying_key(&req.verifying_key) {
+        Ok(key) => key,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Verifying key deserialization error: {}", e)),
+    };
+
+    // Decode everything before consuming any challenge, so a malformed request does not burn nonces
+    let mut decoded = Vec::with_capacity(req.proofs.len());
+    for (i, item) in req.proofs.iter().enumerate() {
+        let nonce = match parse_field_element(&item.nonce) {
+            Ok(nonce) => nonce,
+            Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error in proof {}: {}", i, e)),
+        };
+        let proof = match deserialize_proof(&item.proof, &vk) {
+            Ok(proof) => proof,
+            Err(e) => return HttpResponse::BadRequest().body(format!("Proof deserialization error in proof {}: {}", i, e)),
+        };
+        decoded.push((proof, nonce, item.min_age, item.max_age));
+    }
+
+    // Likewise reject mixed shapes and a key for another circuit first
+    let config = match batch::age_batch_config(req.num_bits, &decoded) {
+        Ok(config) => config,
+        Err(e) => return zkp_error_response("Age proof batch verification error", &e),
+    };
+    if let Err(e) = check_fingerprint::<Fr, AgeVerification>(&vk.fingerprint(), &config) {
+        return zkp_error_response("Age proof batch verification error", &e);
+    }
+
+    let challenged: Vec<bool> = decoded.iter().map(|(_, nonce, _, _)| challenges.consume(nonce)).collect();
+    let batch: Vec<_> = decoded
+        .into_iter()
+        .zip(&challenged)
+        .filter_map(|(item, challenged)| challenged.then_some(item))
+        .collect();
+
+    let results = match &vk {
+        AnyVerifyingKey::Groth16(groth16_vk) => batch
+            .into_iter()
+            .map(|(proof, nonce, min_age, max_age)| match proof {
+                AnyProof::Groth16(proof) => Ok((*proof, nonce, min_age, max_age)),
+                #[cfg(feature = "experimental-marlin")]
+                AnyProof::Marlin(_) => {
+                    Err(ZkpError::InvalidInput("a Groth16 batch cannot hold Marlin proofs".to_string()))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|batch| batch::batch_verify_age(groth16_vk, req.num_bits, &batch)),
+        #[cfg(feature = "experimental-marlin")]
+        AnyVerifyingKey::Marlin(_) => batch
+            .iter()
+            .map(|(proof, nonce, min_age, max_age)| {
+                let public = AgePublicInputs { nonce: *nonce, min_age: *min_age, max_age: *max_age };
+                verify::<AgeVerification>(&vk, &config, proof, &public)
+            })
+            .collect(),
+    };
+    let mut verified = match results {
+        Ok(results) => results.into_iter(),
+        Err(e) => return zkp_error_response("Age proof batch verification error", &e),
+    };
+
+    let proof_statuses = challenged.iter().map(|challenged| *challenged && verified.next().unwrap_or(false)).collect();
+    HttpResponse::Ok().json(BatchProofStatus { proof_statuses })
+}
+
 // Verifies a citizenship verification proof using the provided verifying key.
-pub async fn verify_citizenship_proof(req: web::Json<CitizenshipProofVerify>) -> impl Responder {
+pub async fn verify_citizenship_proof(
+    req: web::Json<CitizenshipProofVerify>,
+    challenges: web::Data<ChallengeStore>,
+) -> impl Responder {
     let vk = match deserialize_verifying_key(&req.verifying_key) {
         Ok(key) => key,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Verifying key deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Verifying key deserialization error: {}", e)),
     };
 
     // Convert the public input (merkle_root) into a field element.
-    let merkle_root = Fr::from(req.merkle_root);
+    let merkle_root = match parse_field_element(&req.merkle_root) {
+        Ok(root) => root,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Merkle root parsing error: {}", e)),
+    };
+
+    // Decode everything before consuming the challenge, so a malformed request does not burn its nonce
+    let nonce = match parse_field_element(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
+    };
 
     // Deserialize the proof.
-    let proof = match deserialize_proof(&req.proof) {
+    let proof = match deserialize_proof(&req.proof, &vk) {
         Ok(proof) => proof,
-        Err(e) => return HttpResponse::BadRequest().body(format!("Proof deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Proof deserialization error: {}", e)),
     };
 
+    // Likewise reject a key for another circuit or shape first
+    if let Err(e) = check_fingerprint::<Fr, CitizenshipVerification>(&vk.fingerprint(), &req.depth) {
+        return zkp_error_response("Citizenship proof verification error", &e);
+    }
+
+    // Only accept proofs bound to a challenge this verifier issued; each challenge is good for one attempt
+    if !challenges.consume(&nonce) {
+        return HttpResponse::BadRequest().body("Unknown, expired or already used challenge nonce");
+    }
+
     // Verify the citizenship proof using the merkle_root as public input.
-    let verified = match proof_system::verify_citizenship(&vk, &proof, merkle_root) {
+    let public = CitizenshipPublicInputs { nonce, merkle_root };
+    let verified = match verify::<CitizenshipVerification>(&vk, &req.depth, &proof, &public) {
         Ok(result) => result,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Citizenship proof verification error: {:?}", e)),
+        Err(e) => return zkp_error_response("Citizenship proof verification error", &e),
     };
 
     let response = ProofStatus { proof_status: verified };
@@ -59,25 +198,48 @@ pub async fn verify_citizenship_proof(req: web::Json<CitizenshipProofVerify>) ->
 }
 
 /// Verifies a college credential verification proof using the provided verifying key.
-pub async fn verify_college_credential_proof(req: web::Json<CollegeCredentialProofVerify>) -> impl Responder {
+pub async fn verify_college_credential_proof(
+    req: web::Json<CollegeCredentialProofVerify>,
+    challenges: web::Data<ChallengeStore>,
+) -> impl Responder {
     let vk = match deserialize_verifying_key(&req.verifying_key) {
         Ok(key) => key,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Verifying key deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Verifying key deserialization error: {}", e)),
     };
 
-    // Convert the public input (university_public_key) to a field element
-    let university_public_key = Fr::from(req.university_public_key);
+    // Convert the public input (university_public_key) to a curve point
+    let university_public_key = match parse_public_key(&req.university_public_key.x, &req.university_public_key.y) {
+        Ok(key) => key,
+        Err(e) => return HttpResponse::BadRequest().body(format!("University public key parsing error: {}", e)),
+    };
+
+    // Decode everything before consuming the challenge, so a malformed request does not burn its nonce
+    let nonce = match parse_field_element(&req.nonce) {
+        Ok(nonce) => nonce,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Nonce parsing error: {}", e)),
+    };
 
     // Deserialize the proof.
-    let proof = match deserialize_proof(&req.proof) {
+    let proof = match deserialize_proof(&req.proof, &vk) {
         Ok(proof) => proof,
-        Err(e) => return HttpResponse::BadRequest().body(format!("Proof deserialization error: {:?}", e)),
+        Err(e) => return HttpResponse::BadRequest().body(format!("Proof deserialization error: {}", e)),
     };
 
+    // Likewise reject a key for another circuit or shape first
+    if let Err(e) = check_fingerprint::<Fr, CollegeCredentialVerification>(&vk.fingerprint(), &()) {
+        return zkp_error_response("College credential proof verification error", &e);
+    }
+
+    // Only accept proofs bound to a challenge this verifier issued; each challenge is good for one attempt
+    if !challenges.consume(&nonce) {
+        return HttpResponse::BadRequest().body("Unknown, expired or already used challenge nonce");
+    }
+
     // Verify the college credential proof using the university public key
-    let verified = match proof_system::verify_college_credential(&vk, &proof, university_public_key) {
+    let public = CollegeCredentialPublicInputs { nonce, university_public_key };
+    let verified = match verify::<CollegeCredentialVerification>(&vk, &(), &proof, &public) {
         Ok(result) => result,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("College credential proof verification error: {:?}", e)),
+        Err(e) => return zkp_error_response("College credential proof verification error", &e),
     };
 
     let response = ProofStatus { proof_status: verified };
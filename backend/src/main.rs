@@ -1,22 +1,45 @@
-use actix_web::{App, HttpServer};
+use actix_web::{rt, web, App, HttpServer};
+use std::time::Duration;
+use crate::utils::challenge_store::{ChallengeStore, CHALLENGE_SWEEP_INTERVAL};
 
 mod routes;
 mod controllers;
 mod utils;
 mod models;
 
+// Clients spend a while decoding multi-megabyte keys before their next request; a short keep-alive closes the
+// connection under them mid-upload
+const KEEP_ALIVE: Duration = Duration::from_secs(75);
+
 // ZKP Generator and Verifier Server
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     println!("Starting backend server on localhost:8080");
 
-    HttpServer::new(|| {
+    // Issued challenges are shared by every worker
+    let challenges = web::Data::new(ChallengeStore::default());
+
+    // Expired challenges are swept in the background, so issuing one stays cheap however many are outstanding
+    let store = challenges.clone();
+    rt::spawn(async move {
+        let mut sweep = rt::time::interval(CHALLENGE_SWEEP_INTERVAL);
+        loop {
+            sweep.tick().await;
+            store.sweep_expired();
+        }
+    });
+
+    HttpServer::new(move || {
         App::new()
+            .app_data(challenges.clone())
+            .configure(routes::challenge::init_routes)
             .configure(routes::age_verification::init_routes)
             .configure(routes::citizenship::init_routes)
             .configure(routes::college_degree::init_routes)
             .configure(routes::keys::init_routes)
+            .configure(routes::circuits::init_routes)
     })
+    .keep_alive(KEEP_ALIVE)
     .bind("localhost:8080")?
     .run()
     .await
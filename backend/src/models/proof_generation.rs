@@ -1,30 +1,140 @@
 use serde::{Serialize, Deserialize};
+use zkp_core::circuits::age_verification::witness_calculator::DEFAULT_AGE_BITS;
+use zkp_core::circuits::citizenship_verification::witness_calculator::DEFAULT_TREE_DEPTH;
+use zkp_core::snarkjs::SnarkjsProof;
 
-// Request payload for generating an age verification proof
+// Tree depth assumed when a request does not specify one
+pub fn default_tree_depth() -> usize {
+    DEFAULT_TREE_DEPTH
+}
+
+// Age bit width assumed when a request does not specify one
+pub fn default_age_bits() -> usize {
+    DEFAULT_AGE_BITS
+}
+
+// A proof as clients send and receive it: Base64 of a key file container, or a snarkjs proof.json object
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ProofPayload {
+    Encoded(String),
+    Snarkjs(Box<SnarkjsProof>),
+}
+
+// Shape generated proofs are returned in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofFormat {
+    #[default]
+    Base64,
+    Snarkjs,
+}
+
+// Request payload for generating an age verification proof. Supplying max_age requires upper bounded keys
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AgeProofGenerationRequest {
+    #[serde(default = "default_age_bits")]
+    pub num_bits: usize,
+    pub nonce: String,
     pub user_age: u64,
     pub min_age: u64,
+    #[serde(default)]
+    pub max_age: Option<u64>,
     pub proving_key: String,
     pub verifying_key: String,
+    #[serde(default)]
+    pub compressed: bool,
+    #[serde(default)]
+    pub proof_format: ProofFormat,
+}
+
+// Merkle authentication path with field elements encoded as decimal strings
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MerklePathRequest {
+    pub siblings: Vec<String>,
+    pub directions: Vec<bool>,
 }
 
 // Request payload for generating a citizenship verification proof
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CitizenshipProofGenerationRequest {
-    pub merkle_root: u64,
-    pub path: u64,
-    pub leaf: u64,
+    #[serde(default = "default_tree_depth")]
+    pub depth: usize,
+    pub nonce: String,
+    pub merkle_root: String,
+    pub leaf: String,
+    pub path: MerklePathRequest,
     pub proving_key: String,
     pub verifying_key: String,
+    #[serde(default)]
+    pub compressed: bool,
+    #[serde(default)]
+    pub proof_format: ProofFormat,
+}
+
+// Baby Jubjub curve point with coordinates encoded as decimal strings
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PointRequest {
+    pub x: String,
+    pub y: String,
 }
 
-// Request payload for generating a college credential verification proof
+// EdDSA signature with the scalar encoded as a decimal string
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignatureRequest {
+    pub r: PointRequest,
+    pub s: String,
+}
+
+// Request payload for generating a college credential verification proof; the university signed the credential
+// together with the commitment to holder_secret
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CollegeCredentialProofGenerationRequest {
-    pub university_public_key: u64,
-    pub credential: u64,
-    pub signature: u64,
+    pub nonce: String,
+    pub university_public_key: PointRequest,
+    pub credential: String,
+    pub holder_secret: String,
+    pub signature: SignatureRequest,
     pub proving_key: String,
     pub verifying_key: String,
+    #[serde(default)]
+    pub compressed: bool,
+    #[serde(default)]
+    pub proof_format: ProofFormat,
+}
+
+// Request payload for checking age inputs without proving; the nonce defaults to zero when no challenge is at hand
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgeInputCheckRequest {
+    #[serde(default = "default_age_bits")]
+    pub num_bits: usize,
+    #[serde(default)]
+    pub nonce: Option<String>,
+    pub user_age: u64,
+    pub min_age: u64,
+    #[serde(default)]
+    pub max_age: Option<u64>,
+}
+
+// Request payload for checking citizenship inputs without proving
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CitizenshipInputCheckRequest {
+    #[serde(default = "default_tree_depth")]
+    pub depth: usize,
+    #[serde(default)]
+    pub nonce: Option<String>,
+    pub merkle_root: String,
+    pub leaf: String,
+    pub path: MerklePathRequest,
+}
+
+// Request payload for checking college credential inputs without proving
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollegeCredentialInputCheckRequest {
+    #[serde(default)]
+    pub nonce: Option<String>,
+    pub university_public_key: PointRequest,
+    pub credential: String,
+    pub holder_secret: String,
+    pub signature: SignatureRequest,
 }
@@ -1,25 +1,54 @@
 use serde::{Serialize, Deserialize};
+use crate::models::proof_generation::{PointRequest, ProofPayload, default_age_bits, default_tree_depth};
 
 // Request payload for verifying an age verification proof
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AgeProofVerify {
-    pub proof: String,
+    #[serde(default = "default_age_bits")]
+    pub num_bits: usize,
+    pub proof: ProofPayload,
+    pub nonce: String,
     pub min_age: u64,
+    #[serde(default)]
+    pub max_age: Option<u64>,
+    pub verifying_key: String,
+}
+
+// One proof in a batch of age verification proofs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgeBatchProof {
+    pub proof: ProofPayload,
+    pub nonce: String,
+    pub min_age: u64,
+    #[serde(default)]
+    pub max_age: Option<u64>,
+}
+
+// Request payload for verifying many age verification proofs made with the same verifying key
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgeProofBatchVerify {
+    #[serde(default = "default_age_bits")]
+    pub num_bits: usize,
+    pub proofs: Vec<AgeBatchProof>,
     pub verifying_key: String,
 }
 
 // Request payload for verifying a citizenship verification proof
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CitizenshipProofVerify {
-    pub proof: String,
-    pub merkle_root: u64,
+    #[serde(default = "default_tree_depth")]
+    pub depth: usize,
+    pub proof: ProofPayload,
+    pub nonce: String,
+    pub merkle_root: String,
     pub verifying_key: String,
 }
 
 // Request payload for verifying a college credential verification proof
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CollegeCredentialProofVerify {
-    pub proof: String,
-    pub university_public_key: u64,
+    pub proof: ProofPayload,
+    pub nonce: String,
+    pub university_public_key: PointRequest,
     pub verifying_key: String,
 }
@@ -1,9 +1,12 @@
 use serde::{Serialize, Deserialize};
+use crate::models::proof_generation::ProofPayload;
 
-// Represents a generated proof
+// Represents a generated proof; snarkjs proofs come with their public.json signals
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GeneratedProof {
-    pub proof: String,
+    pub proof: ProofPayload,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_signals: Option<Vec<String>>,
     pub proving_key: Option<String>,
     pub verifying_key: Option<String>,
 }
@@ -14,9 +17,109 @@ pub struct ProofStatus {
     pub proof_status: bool,
 }
 
+// Represents the status of each proof in a batch, in request order
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchProofStatus {
+    pub proof_statuses: Vec<bool>,
+}
+
+// Represents a verifier challenge; the nonce is a decimal field element
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Challenge {
+    pub nonce: String,
+    pub expires_in_secs: u64,
+}
+
 // Represents the proving and verifying keys generated during setup
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Keys {
     pub proving_key: String,
     pub verifying_key: String,
 }
+
+// Proving system to generate keys for; Marlin keys come from a universal setup but give larger proofs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProvingSystemKind {
+    #[default]
+    Groth16,
+    Marlin,
+}
+
+// Query parameters for generating age keys
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgeKeysQuery {
+    #[serde(default = "crate::models::proof_generation::default_age_bits")]
+    pub num_bits: usize,
+    #[serde(default)]
+    pub upper_bounded: bool,
+    #[serde(default)]
+    pub compressed: bool,
+    #[serde(default)]
+    pub proving_system: ProvingSystemKind,
+}
+
+// Query parameters for generating citizenship keys
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CitizenshipKeysQuery {
+    #[serde(default = "crate::models::proof_generation::default_tree_depth")]
+    pub depth: usize,
+    #[serde(default)]
+    pub compressed: bool,
+    #[serde(default)]
+    pub proving_system: ProvingSystemKind,
+}
+
+// Query parameters for generating college credential keys
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollegeKeysQuery {
+    #[serde(default)]
+    pub compressed: bool,
+    #[serde(default)]
+    pub proving_system: ProvingSystemKind,
+}
+
+// Represents the outcome of checking inputs against a circuit; names the first violated constraint when unsatisfied
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputCheck {
+    pub satisfied: bool,
+    pub unsatisfied_constraint: Option<String>,
+}
+
+// Query parameters for listing circuits; the shapes to report for the circuits that take one
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircuitsQuery {
+    #[serde(default = "crate::models::proof_generation::default_age_bits")]
+    pub num_bits: usize,
+    #[serde(default = "crate::models::proof_generation::default_tree_depth")]
+    pub depth: usize,
+}
+
+// Represents the size in bytes of a key or proof file in both point encodings
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncodedSize {
+    pub compressed: usize,
+    pub uncompressed: usize,
+}
+
+// Represents a circuit: where this backend serves it, if it does, its shape, its dimensions and its key sizes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircuitDescription {
+    pub name: String,
+    pub version: u32,
+    pub curve: String,
+    pub endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_bits: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upper_bounded: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+    pub fingerprint: String,
+    pub num_constraints: usize,
+    pub num_witness_variables: usize,
+    pub num_public_inputs: usize,
+    pub proving_key_size: EncodedSize,
+    pub verifying_key_size: EncodedSize,
+    pub proof_size: EncodedSize,
+}
@@ -1,12 +1,20 @@
 use actix_web::web;
+use crate::routes::proof_generation_json_config;
+use crate::controllers::input_checker_controller::check_age_inputs;
 use crate::controllers::proof_generator_controller::generate_age_proof;
-use crate::controllers::proof_verifier_controller::verify_age_proof;
+use crate::controllers::proof_verifier_controller::{batch_verify_age_proofs, verify_age_proof};
 
-// Route for proof generation and verification for age threshold
+// Route for input checks, proof generation and verification for age threshold
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/age_verification")
-            .route("/generate", web::post().to(generate_age_proof))
-            .route("/verify", web::post().to(verify_age_proof)),
+            .route("/check", web::post().to(check_age_inputs))
+            .service(
+                web::resource("/generate")
+                    .app_data(proof_generation_json_config())
+                    .route(web::post().to(generate_age_proof)),
+            )
+            .route("/verify", web::post().to(verify_age_proof))
+            .route("/batch_verify", web::post().to(batch_verify_age_proofs)),
     );
 }
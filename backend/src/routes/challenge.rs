@@ -0,0 +1,7 @@
+use actix_web::web;
+use crate::controllers::challenge_controller::issue_challenge;
+
+// Route for issuing verifier challenges
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/challenge", web::post().to(issue_challenge));
+}
@@ -0,0 +1,7 @@
+use actix_web::web;
+use crate::controllers::circuits_controller::list_circuits;
+
+// Route for discovering the circuits this backend serves
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/circuits").route(web::get().to(list_circuits)));
+}
@@ -1,12 +1,19 @@
 use actix_web::web;
+use crate::routes::proof_generation_json_config;
+use crate::controllers::input_checker_controller::check_citizenship_inputs;
 use crate::controllers::proof_generator_controller::generate_citizenship_proof;
 use crate::controllers::proof_verifier_controller::verify_citizenship_proof;
 
-// Route for proof generation and verification for citizenship status
+// Route for input checks, proof generation and verification for citizenship status
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/citizenship")
-            .route("/generate", web::post().to(generate_citizenship_proof))
+            .route("/check", web::post().to(check_citizenship_inputs))
+            .service(
+                web::resource("/generate")
+                    .app_data(proof_generation_json_config())
+                    .route(web::post().to(generate_citizenship_proof)),
+            )
             .route("/verify", web::post().to(verify_citizenship_proof)),
     );
 }
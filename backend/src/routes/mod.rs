@@ -1,4 +1,62 @@
+use actix_web::web;
+use ark_bn254::Bn254;
+use std::sync::OnceLock;
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgeVerification};
+use zkp_core::circuits::citizenship_verification::witness_calculator::{CitizenshipVerification, MAX_TREE_DEPTH};
+use zkp_core::circuits::college_credential_verification::witness_calculator::CollegeCredentialVerification;
+use zkp_core::inspect::inspect;
+#[cfg(feature = "experimental-marlin")]
+use zkp_core::marlin;
+use zkp_core::range::MAX_RANGE_BITS;
+
 pub mod age_verification;
+pub mod challenge;
 pub mod citizenship;
 pub mod college_degree;
-pub mod keys;
\ No newline at end of file
+pub mod keys;
+pub mod circuits;
+
+// Room for everything in a proof generation request besides its two keys: inputs, Merkle paths and field names
+const REQUEST_OVERHEAD_BYTES: usize = 64 * 1024;
+
+// Proof generation requests carry their proving key inline as Base64, far past actix's default JSON limit. Only
+// those routes get a larger limit, sized for the largest uncompressed keys any served circuit can have under any
+// served proving system; every other route keeps the default. Marlin proving keys hold the powers of tau, so they
+// can outgrow the Groth16 keys of the same circuit.
+pub fn proof_generation_json_config() -> web::JsonConfig {
+    static LIMIT: OnceLock<usize> = OnceLock::new();
+    web::JsonConfig::default().limit(*LIMIT.get_or_init(max_proof_generation_request_bytes))
+}
+
+fn max_proof_generation_request_bytes() -> usize {
+    const AGE_CONFIG: AgeConfig = AgeConfig { num_bits: MAX_RANGE_BITS, upper_bounded: true };
+    let groth16 = [
+        inspect::<Bn254, AgeVerification>(&AGE_CONFIG),
+        inspect::<Bn254, CitizenshipVerification>(&MAX_TREE_DEPTH),
+        inspect::<Bn254, CollegeCredentialVerification>(&()),
+    ]
+    .map(|info| info.map(|info| (info.proving_key_size, info.verifying_key_size)));
+    #[cfg(feature = "experimental-marlin")]
+    let marlin = [
+        marlin::key_sizes::<AgeVerification>(&AGE_CONFIG),
+        marlin::key_sizes::<CitizenshipVerification>(&MAX_TREE_DEPTH),
+        marlin::key_sizes::<CollegeCredentialVerification>(&()),
+    ];
+    #[cfg(not(feature = "experimental-marlin"))]
+    let marlin: [_; 0] = [];
+
+    let largest = groth16
+        .into_iter()
+        .chain(marlin)
+        .map(|sizes| {
+            let (pk, vk) = sizes.expect("the largest shapes of the served circuits are valid");
+            base64_len(pk.uncompressed) + base64_len(vk.uncompressed)
+        })
+        .max()
+        .unwrap_or_default();
+    largest + REQUEST_OVERHEAD_BYTES
+}
+
+fn base64_len(bytes: usize) -> usize {
+    bytes.div_ceil(3) * 4
+}
@@ -0,0 +1,61 @@
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use rand::thread_rng;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zkp_core::nonce::generate_nonce;
+
+/// How long an issued challenge can be answered before it expires.
+pub const CHALLENGE_TTL: Duration = Duration::from_secs(300);
+
+/// How many challenges may be outstanding at once; new ones are refused beyond that.
+pub const MAX_OUTSTANDING_CHALLENGES: usize = 100_000;
+
+/// How often expired challenges are swept from the store.
+pub const CHALLENGE_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Verifier challenges that have been issued but not yet answered.
+///
+/// Each nonce is accepted by at most one verification, so a captured proof cannot be replayed. The store is capped
+/// at `MAX_OUTSTANDING_CHALLENGES`, counting expired challenges until the next sweep, so anonymous clients cannot
+/// grow it without bound.
+#[derive(Default)]
+pub struct ChallengeStore {
+    issued: Mutex<HashMap<String, Instant>>,
+}
+
+// Canonical decimal form of a nonce, used as the key of the store
+fn nonce_key(nonce: &Fr) -> String {
+    nonce.into_bigint().to_string()
+}
+
+impl ChallengeStore {
+    /// Draws a fresh nonce and remembers it until it is consumed or expires, or returns `None` if the store is full.
+    pub fn issue(&self) -> Option<Fr> {
+        let nonce = generate_nonce(&mut thread_rng());
+
+        let mut issued = self.issued.lock().expect("challenge store lock poisoned");
+        if issued.len() >= MAX_OUTSTANDING_CHALLENGES {
+            return None;
+        }
+        issued.insert(nonce_key(&nonce), Instant::now() + CHALLENGE_TTL);
+        Some(nonce)
+    }
+
+    /// Forgets expired challenges; run every `CHALLENGE_SWEEP_INTERVAL` rather than on every issue.
+    pub fn sweep_expired(&self) {
+        let now = Instant::now();
+        let mut issued = self.issued.lock().expect("challenge store lock poisoned");
+        issued.retain(|_, expires_at| *expires_at > now);
+    }
+
+    /// Removes a nonce, returning whether it had been issued and has not expired.
+    pub fn consume(&self, nonce: &Fr) -> bool {
+        let mut issued = self.issued.lock().expect("challenge store lock poisoned");
+        match issued.remove(&nonce_key(nonce)) {
+            Some(expires_at) => expires_at > Instant::now(),
+            None => false,
+        }
+    }
+}
@@ -1 +1,304 @@
-pub mod utils;
\ No newline at end of file
+pub mod challenge_store;
+
+use ark_groth16::Proof;
+use ark_bn254::{Bn254, Fr};
+use ark_serialize::Compress;
+use base64::{encode, decode};
+use ark_ff::PrimeField;
+use zkp_core::attribute::AttributeCircuit;
+use zkp_core::babyjubjub::Fs;
+use zkp_core::eddsa::{PublicKey, Signature};
+use zkp_core::error::ZkpError;
+use zkp_core::key_file::{self, ProvingSystemId};
+use zkp_core::keys::{CircuitFingerprint, CircuitProvingKey, CircuitVerifyingKey};
+#[cfg(feature = "experimental-marlin")]
+use zkp_core::marlin::{self, MarlinProof, MarlinProvingKey, MarlinVerifyingKey};
+use zkp_core::proof_system;
+use zkp_core::snarkjs;
+use rand::thread_rng;
+use crate::models::proof_generation::{ProofFormat, ProofPayload};
+use crate::models::response::ProvingSystemKind;
+use actix_web::HttpResponse;
+
+// Keys and proofs travel as Base64 of the `zkp_core::key_file` container, which carries the circuit fingerprint
+// and a payload digest, so truncated, corrupted or mismatched uploads are rejected before any proving work.
+// Clients may also send bare compressed or uncompressed encodings; the encoding is detected and every point is
+// checked to be on the curve and in the right subgroup. Proofs can instead travel as snarkjs proof.json objects,
+// for clients built on snarkjs tooling.
+//
+// Keys are Groth16 or Marlin, as byte 41 of the container says; bare encodings are Groth16. The proving system of
+// the key then decides how proofs are made, read and checked, so every endpoint serves both. Marlin is unaudited
+// and only served when the backend is built with the `experimental-marlin` feature; otherwise Marlin keys and
+// requests for them are refused.
+
+#[cfg(not(feature = "experimental-marlin"))]
+const MARLIN_DISABLED: &str = "Marlin needs the backend built with the experimental-marlin feature";
+
+/// A proving key under either proving system.
+pub enum AnyProvingKey {
+    Groth16(Box<CircuitProvingKey>),
+    #[cfg(feature = "experimental-marlin")]
+    Marlin(Box<MarlinProvingKey>),
+}
+
+/// A verifying key under either proving system.
+pub enum AnyVerifyingKey {
+    Groth16(Box<CircuitVerifyingKey>),
+    #[cfg(feature = "experimental-marlin")]
+    Marlin(Box<MarlinVerifyingKey>),
+}
+
+/// A proof under either proving system.
+pub enum AnyProof {
+    Groth16(Box<Proof<Bn254>>),
+    #[cfg(feature = "experimental-marlin")]
+    Marlin(Box<MarlinProof>),
+}
+
+impl AnyProvingKey {
+    pub fn fingerprint(&self) -> CircuitFingerprint {
+        match self {
+            AnyProvingKey::Groth16(pk) => pk.fingerprint,
+            #[cfg(feature = "experimental-marlin")]
+            AnyProvingKey::Marlin(pk) => pk.vk.fingerprint,
+        }
+    }
+
+    pub fn proving_system(&self) -> ProvingSystemId {
+        match self {
+            AnyProvingKey::Groth16(_) => ProvingSystemId::Groth16,
+            #[cfg(feature = "experimental-marlin")]
+            AnyProvingKey::Marlin(_) => ProvingSystemId::Marlin,
+        }
+    }
+}
+
+impl AnyVerifyingKey {
+    pub fn fingerprint(&self) -> CircuitFingerprint {
+        match self {
+            AnyVerifyingKey::Groth16(vk) => vk.fingerprint,
+            #[cfg(feature = "experimental-marlin")]
+            AnyVerifyingKey::Marlin(vk) => vk.fingerprint,
+        }
+    }
+}
+
+/// Sets up circuit `C` under the requested proving system.
+pub fn setup<C: AttributeCircuit<Fr>>(
+    config: &C::Config,
+    proving_system: ProvingSystemKind,
+) -> Result<(AnyProvingKey, AnyVerifyingKey), ZkpError> {
+    let mut rng = thread_rng();
+    Ok(match proving_system {
+        ProvingSystemKind::Groth16 => {
+            let (pk, vk) = proof_system::setup_with_rng::<Bn254, C, _>(config, &mut rng)?;
+            (AnyProvingKey::Groth16(Box::new(pk)), AnyVerifyingKey::Groth16(Box::new(vk)))
+        }
+        #[cfg(feature = "experimental-marlin")]
+        ProvingSystemKind::Marlin => {
+            let (pk, vk) = marlin::setup_with_rng::<C, _>(config, &mut rng)?;
+            (AnyProvingKey::Marlin(Box::new(pk)), AnyVerifyingKey::Marlin(Box::new(vk)))
+        }
+        #[cfg(not(feature = "experimental-marlin"))]
+        ProvingSystemKind::Marlin => return Err(ZkpError::InvalidInput(MARLIN_DISABLED.to_string())),
+    })
+}
+
+/// Proves circuit `C` under the proving system of the key.
+pub fn prove<C: AttributeCircuit<Fr>>(
+    pk: &AnyProvingKey,
+    config: &C::Config,
+    public: &C::PublicInputs,
+    private: &C::PrivateInputs,
+) -> Result<AnyProof, ZkpError> {
+    Ok(match pk {
+        AnyProvingKey::Groth16(pk) => {
+            AnyProof::Groth16(Box::new(proof_system::prove::<Bn254, C>(pk, config, public, private)?))
+        }
+        #[cfg(feature = "experimental-marlin")]
+        AnyProvingKey::Marlin(pk) => AnyProof::Marlin(Box::new(marlin::prove::<C>(pk, config, public, private)?)),
+    })
+}
+
+/// Verifies a proof for circuit `C` under the proving system of the key.
+pub fn verify<C: AttributeCircuit<Fr>>(
+    vk: &AnyVerifyingKey,
+    config: &C::Config,
+    proof: &AnyProof,
+    public: &C::PublicInputs,
+) -> Result<bool, ZkpError> {
+    match (vk, proof) {
+        (AnyVerifyingKey::Groth16(vk), AnyProof::Groth16(proof)) => {
+            proof_system::verify::<Bn254, C>(vk, config, proof, public)
+        }
+        #[cfg(feature = "experimental-marlin")]
+        (AnyVerifyingKey::Marlin(vk), AnyProof::Marlin(proof)) => marlin::verify::<C>(vk, config, proof, public),
+        #[cfg(feature = "experimental-marlin")]
+        _ => Err(ZkpError::InvalidInput("proof and verifying key are for different proving systems".to_string())),
+    }
+}
+
+/// Serializes a proof into a Base64 encoded container, labelled with the fingerprint of its circuit.
+pub fn serialize_proof(
+    proof: &AnyProof,
+    fingerprint: CircuitFingerprint,
+    compressed: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let compress = compression(compressed);
+    Ok(encode(match proof {
+        AnyProof::Groth16(proof) => key_file::encode_proof(proof, fingerprint, compress)?,
+        #[cfg(feature = "experimental-marlin")]
+        AnyProof::Marlin(proof) => key_file::encode_marlin_proof(proof, fingerprint, compress)?,
+    }))
+}
+
+/// Serializes a proof in the requested format: a Base64 encoded container or a snarkjs proof object.
+pub fn serialize_proof_payload(
+    proof: &AnyProof,
+    fingerprint: CircuitFingerprint,
+    compressed: bool,
+    format: ProofFormat,
+) -> Result<ProofPayload, Box<dyn std::error::Error>> {
+    Ok(match (format, proof) {
+        (ProofFormat::Snarkjs, AnyProof::Groth16(proof)) => {
+            ProofPayload::Snarkjs(Box::new(snarkjs::export_proof(proof)))
+        }
+        _ => ProofPayload::Encoded(serialize_proof(proof, fingerprint, compressed)?),
+    })
+}
+
+/// Checks that proofs made with the key can be returned in the requested format; snarkjs only knows Groth16.
+pub fn check_proof_format(pk: &AnyProvingKey, format: ProofFormat) -> Result<(), Box<dyn std::error::Error>> {
+    if format == ProofFormat::Snarkjs && pk.proving_system() != ProvingSystemId::Groth16 {
+        return Err("snarkjs proofs are only available for Groth16 keys".into());
+    }
+    Ok(())
+}
+
+/// Public inputs in snarkjs public.json form, returned alongside snarkjs proofs only.
+pub fn public_signals(format: ProofFormat, public_inputs: &[Fr]) -> Option<Vec<String>> {
+    (format == ProofFormat::Snarkjs).then(|| snarkjs::export_public_inputs(public_inputs))
+}
+
+/// Deserializes a proof, which must belong to the same circuit and proving system as the verifying key.
+///
+/// snarkjs proofs carry no circuit fingerprint, like bare encodings, so only verifying tells whether they match.
+/// Marlin proofs only travel as containers.
+pub fn deserialize_proof(payload: &ProofPayload, vk: &AnyVerifyingKey) -> Result<AnyProof, Box<dyn std::error::Error>> {
+    let (encoded, vk) = match (payload, vk) {
+        (ProofPayload::Snarkjs(json), AnyVerifyingKey::Groth16(_)) => {
+            return Ok(AnyProof::Groth16(Box::new(snarkjs::import_proof(json)?)));
+        }
+        #[cfg(feature = "experimental-marlin")]
+        (ProofPayload::Snarkjs(_), AnyVerifyingKey::Marlin(_)) => {
+            return Err("snarkjs proofs are only accepted for Groth16 verifying keys".into());
+        }
+        (ProofPayload::Encoded(encoded), vk) => (encoded, vk),
+    };
+    let bytes = decode(encoded)?;
+    let (fingerprint, proof) = match vk {
+        AnyVerifyingKey::Groth16(_) => {
+            let (fingerprint, proof) = key_file::decode_proof_auto(&bytes)?;
+            (fingerprint, AnyProof::Groth16(Box::new(proof)))
+        }
+        #[cfg(feature = "experimental-marlin")]
+        AnyVerifyingKey::Marlin(_) => {
+            let (fingerprint, proof) = key_file::decode_marlin_proof(&bytes)?;
+            (Some(fingerprint), AnyProof::Marlin(Box::new(proof)))
+        }
+    };
+    if fingerprint.is_some_and(|fingerprint| fingerprint != vk.fingerprint()) {
+        return Err("proof was generated for a different circuit than the verifying key".into());
+    }
+    Ok(proof)
+}
+
+/// Serializes a proving key into a Base64 encoded container.
+pub fn serialize_proving_key(pk: &AnyProvingKey, compressed: bool) -> Result<String, Box<dyn std::error::Error>> {
+    let compress = compression(compressed);
+    Ok(encode(match pk {
+        AnyProvingKey::Groth16(pk) => key_file::encode_proving_key(pk, compress)?,
+        #[cfg(feature = "experimental-marlin")]
+        AnyProvingKey::Marlin(pk) => key_file::encode_marlin_proving_key(pk, compress)?,
+    }))
+}
+
+/// Deserializes a Base64 encoded proving key, in any supported encoding.
+pub fn deserialize_proving_key(encoded: &str) -> Result<AnyProvingKey, Box<dyn std::error::Error>> {
+    let bytes = decode(encoded)?;
+    Ok(match key_file::proving_system(&bytes)? {
+        ProvingSystemId::Groth16 => AnyProvingKey::Groth16(Box::new(key_file::decode_proving_key_auto(&bytes)?)),
+        #[cfg(feature = "experimental-marlin")]
+        ProvingSystemId::Marlin => AnyProvingKey::Marlin(Box::new(key_file::decode_marlin_proving_key(&bytes)?)),
+        #[cfg(not(feature = "experimental-marlin"))]
+        ProvingSystemId::Marlin => return Err(MARLIN_DISABLED.into()),
+    })
+}
+
+/// Serializes a verifying key into a Base64 encoded container.
+pub fn serialize_verifying_key(vk: &AnyVerifyingKey, compressed: bool) -> Result<String, Box<dyn std::error::Error>> {
+    let compress = compression(compressed);
+    Ok(encode(match vk {
+        AnyVerifyingKey::Groth16(vk) => key_file::encode_verifying_key(vk, compress)?,
+        #[cfg(feature = "experimental-marlin")]
+        AnyVerifyingKey::Marlin(vk) => key_file::encode_marlin_verifying_key(vk, compress)?,
+    }))
+}
+
+/// Deserializes a Base64 encoded verifying key, in any supported encoding.
+pub fn deserialize_verifying_key(encoded: &str) -> Result<AnyVerifyingKey, Box<dyn std::error::Error>> {
+    let bytes = decode(encoded)?;
+    Ok(match key_file::proving_system(&bytes)? {
+        ProvingSystemId::Groth16 => AnyVerifyingKey::Groth16(Box::new(key_file::decode_verifying_key_auto(&bytes)?)),
+        #[cfg(feature = "experimental-marlin")]
+        ProvingSystemId::Marlin => AnyVerifyingKey::Marlin(Box::new(key_file::decode_marlin_verifying_key(&bytes)?)),
+        #[cfg(not(feature = "experimental-marlin"))]
+        ProvingSystemId::Marlin => return Err(MARLIN_DISABLED.into()),
+    })
+}
+
+// Compressed points halve the payload at the cost of a square root per point when decoding
+fn compression(compressed: bool) -> Compress {
+    if compressed { Compress::Yes } else { Compress::No }
+}
+
+/// Parses a canonical decimal string into a field element.
+///
+/// `from_str` silently reduces values past the modulus, so two strings could stand for the same element; anything
+/// that does not print back unchanged is rejected.
+pub fn parse_field_element<F: PrimeField>(value: &str) -> Result<F, Box<dyn std::error::Error>> {
+    F::from_str(value)
+        .ok()
+        .filter(|x| x.into_bigint().to_string() == value)
+        .ok_or_else(|| format!("Invalid field element: {}", value).into())
+}
+
+/// Parses decimal coordinates into a Baby Jubjub public key.
+pub fn parse_public_key(x: &str, y: &str) -> Result<PublicKey, Box<dyn std::error::Error>> {
+    let x: Fr = parse_field_element(x)?;
+    let y: Fr = parse_field_element(y)?;
+    PublicKey::from_coordinates(x, y).ok_or_else(|| "Public key is not a valid Baby Jubjub subgroup point".into())
+}
+
+/// Parses decimal values into an EdDSA signature.
+pub fn parse_signature(r_x: &str, r_y: &str, s: &str) -> Result<Signature, Box<dyn std::error::Error>> {
+    let r = parse_public_key(r_x, r_y)?.0;
+    let s: Fs = parse_field_element(s)?;
+    Ok(Signature { r, s })
+}
+
+/// Turns a proof system error into a response: bad inputs and mismatched keys are the client's fault (400),
+/// anything else is ours (500).
+pub fn zkp_error_response(context: &str, e: &ZkpError) -> HttpResponse {
+    match e {
+        ZkpError::UnsatisfiedConstraint { .. }
+        | ZkpError::InvalidInput(_)
+        | ZkpError::KeyCircuitMismatch(_)
+        | ZkpError::InvalidContribution { .. }
+        | ZkpError::InvalidFile(_)
+        | ZkpError::Serialization(_) => HttpResponse::BadRequest().body(format!("{}: {}", context, e)),
+        ZkpError::Setup(_) | ZkpError::Synthesis(_) => {
+            HttpResponse::InternalServerError().body(format!("{}: {}", context, e))
+        }
+    }
+}
@@ -1,10 +1,72 @@
 use reqwest::Client;
 use serde_json::json;
 use tokio::time::{sleep, Duration};
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use zkp_core::babyjubjub::Fs;
+use zkp_core::circuits::college_credential_verification::witness_calculator::college_credential_digest;
+use zkp_core::eddsa::SecretKey;
+use zkp_core::issuer::holder_commitment;
+use zkp_core::merkle::MerkleTree;
+
+// Small tree depth to keep citizenship key generation fast
+const TEST_TREE_DEPTH: usize = 4;
+
+// Secret of the graduate the sample college credential is issued to
+const HOLDER_SECRET: u64 = 31337;
+
+// Encodes a field element as a decimal string (Display drops a lone zero)
+fn to_decimal<F: PrimeField>(x: F) -> String {
+    x.into_bigint().to_string()
+}
+
+// Asks the verifier for a fresh challenge nonce
+async fn request_challenge(client: &Client) -> String {
+    let res = client
+        .post("http://localhost:8080/challenge")
+        .send()
+        .await
+        .expect("Failed to request challenge");
+    assert!(res.status().is_success(), "Challenge request failed with status: {}", res.status());
+    let challenge: serde_json::Value = res.json().await.expect("Failed to parse challenge JSON");
+    challenge["nonce"].as_str().expect("Missing 'nonce' field").to_string()
+}
+
+// Signs a sample credential for HOLDER_SECRET and returns the university key, credential and signature as JSON values
+fn sample_college_inputs() -> (serde_json::Value, serde_json::Value, serde_json::Value) {
+    let university_key = SecretKey(Fs::from(20u64));
+    let public_key = university_key.public_key();
+    let credential = Fr::from(18u64);
+    let signature = university_key.sign(college_credential_digest(credential, holder_commitment(HOLDER_SECRET.into())));
+
+    let public_key_json = json!({ "x": to_decimal(public_key.0.x), "y": to_decimal(public_key.0.y) });
+    let signature_json = json!({
+        "r": { "x": to_decimal(signature.r.x), "y": to_decimal(signature.r.y) },
+        "s": to_decimal(signature.s),
+    });
+    (public_key_json, json!(to_decimal(credential)), signature_json)
+}
+
+// Builds a sample registry and returns a leaf, its path and the root as JSON values for the citizenship endpoints
+fn sample_citizenship_inputs() -> (serde_json::Value, serde_json::Value, serde_json::Value) {
+    let leaves: Vec<Fr> = (10..16u64).map(Fr::from).collect();
+    let tree = MerkleTree::from_leaves(TEST_TREE_DEPTH, &leaves).expect("Failed to build registry tree");
+    let leaf = Fr::from(15u64);
+    let path = tree.path(tree.index_of(leaf).expect("Leaf not in registry")).expect("Failed to get path");
+    let root = tree.root();
+
+    let to_decimal = |x: &Fr| to_decimal(*x);
+    let path_json = json!({
+        "siblings": path.siblings.iter().map(to_decimal).collect::<Vec<_>>(),
+        "directions": path.directions,
+    });
+    (json!(to_decimal(&root)), json!(to_decimal(&leaf)), path_json)
+}
 
 #[tokio::test]
 async fn test_age_verification_generate() {
     let client = Client::new();
+    let nonce = request_challenge(&client).await;
     // Get keys for age verification
     let keys_res = client
         .get("http://localhost:8080/keys/age")
@@ -26,6 +88,7 @@ async fn test_age_verification_generate() {
 
     // Prepare request including keys
     let request_body = json!({
+        "nonce": nonce,
         "user_age": 25,
         "min_age": 18,
         "proving_key": proving_key,
@@ -48,12 +111,48 @@ async fn test_age_verification_generate() {
     println!("Age Verification Generate Response: {}", body);
 }
 
+#[tokio::test]
+async fn test_age_keys_reject_invalid_bit_width() {
+    let client = Client::new();
+    // Zero-bit ages are a client mistake, not a server failure
+    let res = client
+        .get("http://localhost:8080/keys/age?num_bits=0")
+        .send()
+        .await
+        .expect("Failed to get age keys");
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_citizenship_rejects_excessive_depth() {
+    let client = Client::new();
+    // Every endpoint taking a tree depth refuses one no circuit could be built for, instead of running out of memory
+    for url in [
+        "http://localhost:8080/keys/citizenship?depth=10000000000",
+        "http://localhost:8080/circuits?depth=10000000000",
+    ] {
+        let res = client.get(url).send().await.expect("Failed to send request");
+        assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST, "{}", url);
+    }
+
+    let (root, leaf, path) = sample_citizenship_inputs();
+    let check_request = json!({ "depth": 10_000_000_000u64, "merkle_root": root, "leaf": leaf, "path": path });
+    let res = client
+        .post("http://localhost:8080/citizenship/check")
+        .json(&check_request)
+        .send()
+        .await
+        .expect("Failed to send citizenship input check");
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn test_citizenship_generate() {
     let client = Client::new();
+    let nonce = request_challenge(&client).await;
     // Get keys for citizenship verification
     let keys_res = client
-        .get("http://localhost:8080/keys/citizenship")
+        .get(format!("http://localhost:8080/keys/citizenship?depth={}", TEST_TREE_DEPTH))
         .send()
         .await
         .expect("Failed to get citizenship keys");
@@ -70,10 +169,13 @@ async fn test_citizenship_generate() {
         .as_str()
         .expect("Missing 'verifying_key' field");
 
+    let (merkle_root, leaf, path) = sample_citizenship_inputs();
     let request_body = json!({
-        "merkle_root": 10,
-        "path": 5,
-        "leaf": 5,
+        "nonce": nonce,
+        "depth": TEST_TREE_DEPTH,
+        "merkle_root": merkle_root,
+        "leaf": leaf,
+        "path": path,
         "proving_key": proving_key,
         "verifying_key": verifying_key
     });
@@ -97,6 +199,7 @@ async fn test_citizenship_generate() {
 #[tokio::test]
 async fn test_college_degree_generate() {
     let client = Client::new();
+    let nonce = request_challenge(&client).await;
     // Get keys for college credential verification
     let keys_res = client
         .get("http://localhost:8080/keys/college")
@@ -116,10 +219,13 @@ async fn test_college_degree_generate() {
         .as_str()
         .expect("Missing 'verifying_key' field");
 
+    let (university_public_key, credential, signature) = sample_college_inputs();
     let request_body = json!({
-        "university_public_key": 20,
-        "credential": 18,
-        "signature": 2,
+        "nonce": nonce,
+        "university_public_key": university_public_key,
+        "credential": credential,
+        "holder_secret": HOLDER_SECRET.to_string(),
+        "signature": signature,
         "proving_key": proving_key,
         "verifying_key": verifying_key
     });
@@ -145,6 +251,7 @@ async fn test_college_degree_generate() {
 #[tokio::test]
 async fn test_age_verification_integration() {
     let client = Client::new();
+    let nonce = request_challenge(&client).await;
     // Get keys for age verification
     let keys_res = client
         .get("http://localhost:8080/keys/age")
@@ -166,6 +273,7 @@ async fn test_age_verification_integration() {
 
     // Generate an age verification proof using the keys.
     let gen_request = json!({
+        "nonce": nonce,
         "user_age": 25,
         "min_age": 18,
         "proving_key": proving_key,
@@ -195,6 +303,7 @@ async fn test_age_verification_integration() {
 
     // Verify the proof using the provided verifying key.
     let verify_request = json!({
+        "nonce": nonce,
         "proof": proof_str,
         "min_age": 18,
         "verifying_key": verifying_key
@@ -215,14 +324,103 @@ async fn test_age_verification_integration() {
         .await
         .expect("Failed to get age verification response text");
     println!("Age Verification Response: {}", verify_body);
+
+    // Replaying the same proof is rejected because its challenge has been used up
+    let replay_res = client
+        .post("http://localhost:8080/age_verification/verify")
+        .json(&verify_request)
+        .send()
+        .await
+        .expect("Failed to send replayed verify request");
+    assert_eq!(replay_res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_age_bracket_integration() {
+    let client = Client::new();
+    let nonce = request_challenge(&client).await;
+    // Get upper bounded keys for age bracket verification
+    let keys_res = client
+        .get("http://localhost:8080/keys/age?num_bits=8&upper_bounded=true")
+        .send()
+        .await
+        .expect("Failed to get age bracket keys");
+    assert!(
+        keys_res.status().is_success(),
+        "Expected success from keys endpoint, got {}",
+        keys_res.status()
+    );
+    let keys_json: serde_json::Value = keys_res.json().await.expect("Failed to parse age keys JSON");
+    let proving_key = keys_json["proving_key"]
+        .as_str()
+        .expect("Missing 'proving_key' field");
+    let verifying_key = keys_json["verifying_key"]
+        .as_str()
+        .expect("Missing 'verifying_key' field");
+
+    // Generate a proof that 18 <= 21 <= 25.
+    let gen_request = json!({
+        "nonce": nonce,
+        "num_bits": 8,
+        "user_age": 21,
+        "min_age": 18,
+        "max_age": 25,
+        "proving_key": proving_key,
+        "verifying_key": verifying_key
+    });
+    let gen_res = client
+        .post("http://localhost:8080/age_verification/generate")
+        .json(&gen_request)
+        .send()
+        .await
+        .expect("Failed to send generate request for age bracket");
+    assert!(
+        gen_res.status().is_success(),
+        "Generation failed with status: {}",
+        gen_res.status()
+    );
+    let gen_json: serde_json::Value = gen_res
+        .json()
+        .await
+        .expect("Failed to parse generation JSON");
+    let proof_str = gen_json["proof"]
+        .as_str()
+        .expect("Missing 'proof' field")
+        .to_string();
+
+    // Verify the proof against the same bracket.
+    let verify_request = json!({
+        "nonce": nonce,
+        "proof": proof_str,
+        "min_age": 18,
+        "max_age": 25,
+        "verifying_key": verifying_key
+    });
+    let verify_res = client
+        .post("http://localhost:8080/age_verification/verify")
+        .json(&verify_request)
+        .send()
+        .await
+        .expect("Failed to send verify request for age bracket");
+    assert!(
+        verify_res.status().is_success(),
+        "Verification failed with status: {}",
+        verify_res.status()
+    );
+    let verify_json: serde_json::Value = verify_res
+        .json()
+        .await
+        .expect("Failed to parse verification JSON");
+    assert_eq!(verify_json["proof_status"], true);
 }
 
 #[tokio::test]
 async fn test_citizenship_integration() {
     let client = Client::new();
+    let nonce = request_challenge(&client).await;
     // Get keys for citizenship verification
     let keys_res = client
-        .get("http://localhost:8080/keys/citizenship")
+        .get(format!("http://localhost:8080/keys/citizenship?depth={}", TEST_TREE_DEPTH))
         .send()
         .await
         .expect("Failed to get citizenship keys");
@@ -240,10 +438,13 @@ async fn test_citizenship_integration() {
         .expect("Missing 'verifying_key' field");
 
     // Generate a citizenship verification proof using the keys.
+    let (merkle_root, leaf, path) = sample_citizenship_inputs();
     let gen_request = json!({
-        "merkle_root": 25,
-        "path": 10,
-        "leaf": 15,
+        "nonce": nonce,
+        "depth": TEST_TREE_DEPTH,
+        "merkle_root": merkle_root,
+        "leaf": leaf,
+        "path": path,
         "proving_key": proving_key,
         "verifying_key": verifying_key
     });
@@ -271,8 +472,10 @@ async fn test_citizenship_integration() {
 
     // Verify the citizenship proof using the provided verifying key.
     let verify_request = json!({
+        "nonce": nonce,
         "proof": proof_str,
-        "merkle_root": 25,
+        "depth": TEST_TREE_DEPTH,
+        "merkle_root": merkle_root,
         "verifying_key": verifying_key
     });
     let verify_res = client
@@ -296,6 +499,7 @@ async fn test_citizenship_integration() {
 #[tokio::test]
 async fn test_college_credential_integration() {
     let client = Client::new();
+    let nonce = request_challenge(&client).await;
     // Get keys for college credential verification
     let keys_res = client
         .get("http://localhost:8080/keys/college")
@@ -316,10 +520,13 @@ async fn test_college_credential_integration() {
         .expect("Missing 'verifying_key' field");
 
     // Generate a college credential verification proof using the keys.
+    let (university_public_key, credential, signature) = sample_college_inputs();
     let gen_request = json!({
-        "university_public_key": 20,
-        "credential": 18,
-        "signature": 2,
+        "nonce": nonce,
+        "university_public_key": university_public_key,
+        "credential": credential,
+        "holder_secret": HOLDER_SECRET.to_string(),
+        "signature": signature,
         "proving_key": proving_key,
         "verifying_key": verifying_key
     });
@@ -347,8 +554,9 @@ async fn test_college_credential_integration() {
 
     // Verify the college credential proof using the provided verifying key.
     let verify_request = json!({
+        "nonce": nonce,
         "proof": proof_str,
-        "university_public_key": 20,
+        "university_public_key": university_public_key,
         "verifying_key": verifying_key
     });
     let verify_res = client
@@ -368,3 +576,509 @@ async fn test_college_credential_integration() {
         .expect("Failed to get college credential verification response text");
     println!("College Credential Verification Response: {}", verify_body);
 }
+
+#[tokio::test]
+async fn test_age_compressed_encoding_integration() {
+    let client = Client::new();
+    let nonce = request_challenge(&client).await;
+
+    // Ask for keys with compressed points
+    let keys_res = client
+        .get("http://localhost:8080/keys/age?compressed=true")
+        .send()
+        .await
+        .expect("Failed to get compressed age keys");
+    assert!(keys_res.status().is_success(), "Expected success from keys endpoint, got {}", keys_res.status());
+    let keys_json: serde_json::Value = keys_res.json().await.expect("Failed to parse age keys JSON");
+    let proving_key = keys_json["proving_key"].as_str().expect("Missing 'proving_key' field");
+    let verifying_key = keys_json["verifying_key"].as_str().expect("Missing 'verifying_key' field");
+
+    let gen_request = json!({
+        "nonce": nonce,
+        "user_age": 25,
+        "min_age": 18,
+        "proving_key": proving_key,
+        "verifying_key": verifying_key,
+        "compressed": true
+    });
+    let gen_res = client
+        .post("http://localhost:8080/age_verification/generate")
+        .json(&gen_request)
+        .send()
+        .await
+        .expect("Failed to send compressed generate request");
+    assert!(gen_res.status().is_success(), "Expected success status, got {}", gen_res.status());
+    let gen_json: serde_json::Value = gen_res.json().await.expect("Failed to parse generation JSON");
+    let proof_str = gen_json["proof"].as_str().expect("Missing 'proof' field").to_string();
+
+    // A corrupted proof is rejected while decoding
+    let mut corrupted = base64::decode(&proof_str).expect("Proof is not Base64");
+    *corrupted.last_mut().unwrap() ^= 1;
+    let verify_request = json!({
+        "nonce": request_challenge(&client).await,
+        "proof": base64::encode(&corrupted),
+        "min_age": 18,
+        "verifying_key": verifying_key
+    });
+    let verify_res = client
+        .post("http://localhost:8080/age_verification/verify")
+        .json(&verify_request)
+        .send()
+        .await
+        .expect("Failed to send verify request for corrupted proof");
+    assert_eq!(verify_res.status().as_u16(), 400, "Expected corrupted proof to be rejected");
+
+    // The intact proof verifies
+    let verify_request = json!({
+        "nonce": nonce,
+        "proof": proof_str,
+        "min_age": 18,
+        "verifying_key": verifying_key
+    });
+    let verify_res = client
+        .post("http://localhost:8080/age_verification/verify")
+        .json(&verify_request)
+        .send()
+        .await
+        .expect("Failed to send verify request for compressed proof");
+    assert!(verify_res.status().is_success(), "Expected success status, got {}", verify_res.status());
+    let verify_json: serde_json::Value = verify_res.json().await.expect("Failed to parse verify JSON");
+    assert_eq!(verify_json["proof_status"], true);
+}
+
+#[tokio::test]
+async fn test_age_batch_verify_integration() {
+    let client = Client::new();
+    let keys_res = client
+        .get("http://localhost:8080/keys/age")
+        .send()
+        .await
+        .expect("Failed to get age keys");
+    assert!(keys_res.status().is_success(), "Expected success from keys endpoint, got {}", keys_res.status());
+    let keys_json: serde_json::Value = keys_res.json().await.expect("Failed to parse age keys JSON");
+    let proving_key = keys_json["proving_key"].as_str().expect("Missing 'proving_key' field");
+    let verifying_key = keys_json["verifying_key"].as_str().expect("Missing 'verifying_key' field");
+
+    // Two proofs, each bound to its own challenge
+    let mut proofs = Vec::new();
+    for user_age in [25, 40] {
+        let nonce = request_challenge(&client).await;
+        let gen_request = json!({
+            "nonce": nonce,
+            "user_age": user_age,
+            "min_age": 18,
+            "proving_key": proving_key,
+            "verifying_key": verifying_key
+        });
+        let gen_res = client
+            .post("http://localhost:8080/age_verification/generate")
+            .json(&gen_request)
+            .send()
+            .await
+            .expect("Failed to send generate request for age verification");
+        assert!(gen_res.status().is_success(), "Expected success status, got {}", gen_res.status());
+        let gen_json: serde_json::Value = gen_res.json().await.expect("Failed to parse generation JSON");
+        proofs.push(json!({ "proof": gen_json["proof"], "nonce": nonce, "min_age": 18 }));
+    }
+
+    // Batches rejected as a whole, for mixing bounded and unbounded proofs or for a key of another bit width,
+    // leave every challenge unspent
+    let mut bounded = proofs[1].clone();
+    bounded["max_age"] = json!(65);
+    for (batch_request, reason) in [
+        (json!({ "proofs": [proofs[0], bounded], "verifying_key": verifying_key }), "mixed shapes"),
+        (json!({ "proofs": proofs, "num_bits": 16, "verifying_key": verifying_key }), "bit width"),
+    ] {
+        let batch_res = client
+            .post("http://localhost:8080/age_verification/batch_verify")
+            .json(&batch_request)
+            .send()
+            .await
+            .expect("Failed to send batch verify request");
+        assert_eq!(batch_res.status(), reqwest::StatusCode::BAD_REQUEST, "{}", reason);
+    }
+
+    // The same proof again reuses its challenge, so only the first copy counts
+    proofs.push(proofs[0].clone());
+
+    let batch_request = json!({ "proofs": proofs, "verifying_key": verifying_key });
+    let batch_res = client
+        .post("http://localhost:8080/age_verification/batch_verify")
+        .json(&batch_request)
+        .send()
+        .await
+        .expect("Failed to send batch verify request");
+    assert!(batch_res.status().is_success(), "Expected success status, got {}", batch_res.status());
+    let batch_json: serde_json::Value = batch_res.json().await.expect("Failed to parse batch verify JSON");
+    assert_eq!(batch_json["proof_statuses"], json!([true, true, false]));
+}
+
+#[tokio::test]
+async fn test_input_check_integration() {
+    let client = Client::new();
+
+    // No keys are needed to find out whether inputs would prove
+    for (user_age, satisfied) in [(25, true), (17, false)] {
+        let check_res = client
+            .post("http://localhost:8080/age_verification/check")
+            .json(&json!({ "user_age": user_age, "min_age": 18 }))
+            .send()
+            .await
+            .expect("Failed to send age input check");
+        assert!(check_res.status().is_success(), "Expected success status, got {}", check_res.status());
+        let check_json: serde_json::Value = check_res.json().await.expect("Failed to parse input check JSON");
+        assert_eq!(check_json["satisfied"], json!(satisfied));
+        if !satisfied {
+            let constraint = check_json["unsatisfied_constraint"].as_str().expect("Missing constraint name");
+            assert!(constraint.starts_with("user_age_at_least_min_age"), "Unexpected constraint {}", constraint);
+        }
+    }
+
+    // A leaf that is not in the registry does not reach the root
+    let (root, _, path) = sample_citizenship_inputs();
+    let check_request = json!({ "depth": TEST_TREE_DEPTH, "merkle_root": root, "leaf": "14", "path": path });
+    let check_res = client
+        .post("http://localhost:8080/citizenship/check")
+        .json(&check_request)
+        .send()
+        .await
+        .expect("Failed to send citizenship input check");
+    assert!(check_res.status().is_success(), "Expected success status, got {}", check_res.status());
+    let check_json: serde_json::Value = check_res.json().await.expect("Failed to parse input check JSON");
+    assert_eq!(check_json["satisfied"], json!(false));
+    assert_eq!(check_json["unsatisfied_constraint"], json!("root_matches"));
+
+    // Malformed inputs are still rejected outright
+    let check_res = client
+        .post("http://localhost:8080/age_verification/check")
+        .json(&json!({ "num_bits": 0, "user_age": 25, "min_age": 18 }))
+        .send()
+        .await
+        .expect("Failed to send age input check");
+    assert_eq!(check_res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    // Field elements must be canonical: the modulus itself would otherwise be read as zero
+    let modulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+    let check_request = json!({ "depth": TEST_TREE_DEPTH, "merkle_root": modulus, "leaf": "14", "path": path });
+    let check_res = client
+        .post("http://localhost:8080/citizenship/check")
+        .json(&check_request)
+        .send()
+        .await
+        .expect("Failed to send citizenship input check");
+    assert_eq!(check_res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_circuits_integration() {
+    let client = Client::new();
+    let res = client
+        .get(format!("http://localhost:8080/circuits?num_bits=8&depth={}", TEST_TREE_DEPTH))
+        .send()
+        .await
+        .expect("Failed to list circuits");
+    assert!(res.status().is_success(), "Expected success status, got {}", res.status());
+    let circuits: Vec<serde_json::Value> = res.json().await.expect("Failed to parse circuits JSON");
+    let endpoints: Vec<Option<&str>> = circuits.iter().map(|c| c["endpoint"].as_str()).collect();
+    assert_eq!(endpoints, [
+        Some("/age_verification"),
+        Some("/age_verification"),
+        Some("/citizenship"),
+        Some("/college_degree"),
+        None,
+        None,
+    ]);
+    let names: Vec<&str> = circuits[4..].iter().map(|c| c["name"].as_str().expect("Missing name")).collect();
+    assert_eq!(names, ["date_of_birth_verification", "signed_age_verification"]);
+
+    // An upper bound adds max_age to the nonce and min_age
+    assert_eq!(circuits[0]["num_public_inputs"], json!(2));
+    assert_eq!(circuits[1]["num_public_inputs"], json!(3));
+    assert_eq!(circuits[2]["depth"], json!(TEST_TREE_DEPTH));
+
+    // The reported sizes are those of the keys the backend hands out
+    let keys_res = client
+        .get("http://localhost:8080/keys/age?num_bits=8")
+        .send()
+        .await
+        .expect("Failed to get age keys");
+    let keys: serde_json::Value = keys_res.json().await.expect("Failed to parse keys JSON");
+    for (field, size) in [("proving_key", "proving_key_size"), ("verifying_key", "verifying_key_size")] {
+        let key = base64::decode(keys[field].as_str().expect("Missing key")).expect("Key is not Base64");
+        assert_eq!(json!(key.len()), circuits[0][size]["uncompressed"]);
+    }
+
+    // Shapes are validated like everywhere else
+    let res = client
+        .get("http://localhost:8080/circuits?num_bits=0")
+        .send()
+        .await
+        .expect("Failed to list circuits");
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_age_snarkjs_proof_format_integration() {
+    let client = Client::new();
+    let keys_res = client
+        .get("http://localhost:8080/keys/age?num_bits=8")
+        .send()
+        .await
+        .expect("Failed to get age keys");
+    let keys_json: serde_json::Value = keys_res.json().await.expect("Failed to parse age keys JSON");
+    let proving_key = keys_json["proving_key"].as_str().expect("Missing 'proving_key' field");
+    let verifying_key = keys_json["verifying_key"].as_str().expect("Missing 'verifying_key' field");
+
+    // snarkjs tooling gets a proof.json object and the public.json signals
+    let nonce = request_challenge(&client).await;
+    let gen_request = json!({
+        "num_bits": 8,
+        "nonce": nonce,
+        "user_age": 25,
+        "min_age": 18,
+        "proving_key": proving_key,
+        "verifying_key": verifying_key,
+        "proof_format": "snarkjs"
+    });
+    let gen_res = client
+        .post("http://localhost:8080/age_verification/generate")
+        .json(&gen_request)
+        .send()
+        .await
+        .expect("Failed to send generate request for age verification");
+    assert!(gen_res.status().is_success(), "Generation failed with status: {}", gen_res.status());
+    let gen_json: serde_json::Value = gen_res.json().await.expect("Failed to parse generation JSON");
+    let proof = gen_json["proof"].clone();
+    assert_eq!(proof["protocol"], json!("groth16"));
+    assert_eq!(proof["curve"], json!("bn128"));
+    assert_eq!(gen_json["public_signals"], json!([nonce, "18"]));
+
+    // The same object is accepted for verification
+    let verify = |nonce: String, proof: serde_json::Value| {
+        let client = client.clone();
+        let verify_request = json!({
+            "num_bits": 8,
+            "nonce": nonce,
+            "proof": proof,
+            "min_age": 18,
+            "verifying_key": verifying_key
+        });
+        async move {
+            client
+                .post("http://localhost:8080/age_verification/verify")
+                .json(&verify_request)
+                .send()
+                .await
+                .expect("Failed to send verify request for age verification")
+        }
+    };
+    let verify_res = verify(nonce, proof.clone()).await;
+    assert!(verify_res.status().is_success(), "Verification failed with status: {}", verify_res.status());
+    let verify_json: serde_json::Value = verify_res.json().await.expect("Failed to parse verification JSON");
+    assert_eq!(verify_json["proof_status"], json!(true));
+
+    // A point off the curve is a malformed proof, not a failed verification
+    let mut off_curve = proof;
+    off_curve["pi_a"][1] = json!("3");
+    let verify_res = verify(request_challenge(&client).await, off_curve).await;
+    assert_eq!(verify_res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[cfg(feature = "experimental-marlin")]
+async fn marlin_keys(client: &Client, url: &str) -> (String, String) {
+    let keys_res = client.get(url).send().await.expect("Failed to get Marlin keys");
+    assert!(keys_res.status().is_success(), "Expected success from keys endpoint, got {}", keys_res.status());
+    let keys_json: serde_json::Value = keys_res.json().await.expect("Failed to parse keys JSON");
+    let proving_key = keys_json["proving_key"].as_str().expect("Missing 'proving_key' field");
+    let verifying_key = keys_json["verifying_key"].as_str().expect("Missing 'verifying_key' field");
+
+    // Byte 41 of the key file names the proving system
+    let vk_bytes = base64::decode(verifying_key).expect("Verifying key is not Base64");
+    assert_eq!(vk_bytes[41], 1, "Expected a Marlin verifying key");
+    (proving_key.to_string(), verifying_key.to_string())
+}
+
+#[cfg(feature = "experimental-marlin")]
+#[tokio::test]
+async fn test_age_marlin_integration() {
+    let client = Client::new();
+    let (proving_key, verifying_key) =
+        marlin_keys(&client, "http://localhost:8080/keys/age?num_bits=8&proving_system=marlin").await;
+
+    // snarkjs only knows Groth16
+    let gen_request = json!({
+        "num_bits": 8,
+        "nonce": request_challenge(&client).await,
+        "user_age": 25,
+        "min_age": 18,
+        "proving_key": proving_key,
+        "verifying_key": verifying_key,
+        "proof_format": "snarkjs"
+    });
+    let gen_res = client
+        .post("http://localhost:8080/age_verification/generate")
+        .json(&gen_request)
+        .send()
+        .await
+        .expect("Failed to send generate request for age verification");
+    assert_eq!(gen_res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    // Two proofs, each bound to its own challenge
+    let mut proofs = Vec::new();
+    for user_age in [25, 40] {
+        let nonce = request_challenge(&client).await;
+        let gen_request = json!({
+            "num_bits": 8,
+            "nonce": nonce,
+            "user_age": user_age,
+            "min_age": 18,
+            "proving_key": proving_key,
+            "verifying_key": verifying_key
+        });
+        let gen_res = client
+            .post("http://localhost:8080/age_verification/generate")
+            .json(&gen_request)
+            .send()
+            .await
+            .expect("Failed to send generate request for age verification");
+        assert!(gen_res.status().is_success(), "Expected success status, got {}", gen_res.status());
+        let gen_json: serde_json::Value = gen_res.json().await.expect("Failed to parse generation JSON");
+        proofs.push(json!({ "proof": gen_json["proof"], "nonce": nonce, "min_age": 18 }));
+    }
+
+    // A Marlin proof is not read against a Groth16 key
+    let groth16_keys: serde_json::Value = client
+        .get("http://localhost:8080/keys/age?num_bits=8")
+        .send()
+        .await
+        .expect("Failed to get age keys")
+        .json()
+        .await
+        .expect("Failed to parse age keys JSON");
+    let verify = |nonce: serde_json::Value, verifying_key: serde_json::Value| {
+        let client = client.clone();
+        let verify_request = json!({
+            "num_bits": 8,
+            "nonce": nonce,
+            "proof": proofs[0]["proof"],
+            "min_age": 18,
+            "verifying_key": verifying_key
+        });
+        async move {
+            client
+                .post("http://localhost:8080/age_verification/verify")
+                .json(&verify_request)
+                .send()
+                .await
+                .expect("Failed to send verify request for age verification")
+        }
+    };
+    let verify_res = verify(json!(request_challenge(&client).await), groth16_keys["verifying_key"].clone()).await;
+    assert_eq!(verify_res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let verify_res = verify(proofs[0]["nonce"].clone(), json!(verifying_key)).await;
+    assert!(verify_res.status().is_success(), "Verification failed with status: {}", verify_res.status());
+    let verify_json: serde_json::Value = verify_res.json().await.expect("Failed to parse verification JSON");
+    assert_eq!(verify_json["proof_status"], json!(true));
+
+    // The first challenge is spent, so only the second proof still counts
+    let batch_request = json!({ "proofs": proofs, "num_bits": 8, "verifying_key": verifying_key });
+    let batch_res = client
+        .post("http://localhost:8080/age_verification/batch_verify")
+        .json(&batch_request)
+        .send()
+        .await
+        .expect("Failed to send batch verify request");
+    assert!(batch_res.status().is_success(), "Expected success status, got {}", batch_res.status());
+    let batch_json: serde_json::Value = batch_res.json().await.expect("Failed to parse batch verify JSON");
+    assert_eq!(batch_json["proof_statuses"], json!([false, true]));
+}
+
+#[cfg(feature = "experimental-marlin")]
+#[tokio::test]
+async fn test_citizenship_marlin_integration() {
+    let client = Client::new();
+    let url = format!("http://localhost:8080/keys/citizenship?depth={}&proving_system=marlin", TEST_TREE_DEPTH);
+    let (proving_key, verifying_key) = marlin_keys(&client, &url).await;
+
+    let nonce = request_challenge(&client).await;
+    let (merkle_root, leaf, path) = sample_citizenship_inputs();
+    let gen_request = json!({
+        "nonce": nonce,
+        "depth": TEST_TREE_DEPTH,
+        "merkle_root": merkle_root,
+        "leaf": leaf,
+        "path": path,
+        "proving_key": proving_key,
+        "verifying_key": verifying_key
+    });
+    let gen_res = client
+        .post("http://localhost:8080/citizenship/generate")
+        .json(&gen_request)
+        .send()
+        .await
+        .expect("Failed to send generate request for citizenship");
+    assert!(gen_res.status().is_success(), "Generation failed with status: {}", gen_res.status());
+    let gen_json: serde_json::Value = gen_res.json().await.expect("Failed to parse citizenship generation JSON");
+
+    let verify_request = json!({
+        "nonce": nonce,
+        "proof": gen_json["proof"],
+        "depth": TEST_TREE_DEPTH,
+        "merkle_root": merkle_root,
+        "verifying_key": verifying_key
+    });
+    let verify_res = client
+        .post("http://localhost:8080/citizenship/verify")
+        .json(&verify_request)
+        .send()
+        .await
+        .expect("Failed to send verify request for citizenship");
+    assert!(verify_res.status().is_success(), "Verification failed with status: {}", verify_res.status());
+    let verify_json: serde_json::Value = verify_res.json().await.expect("Failed to parse verification JSON");
+    assert_eq!(verify_json["proof_status"], json!(true));
+}
+
+#[cfg(feature = "experimental-marlin")]
+#[tokio::test]
+async fn test_college_credential_marlin_integration() {
+    let client = Client::new();
+    let (proving_key, verifying_key) =
+        marlin_keys(&client, "http://localhost:8080/keys/college?proving_system=marlin").await;
+
+    let nonce = request_challenge(&client).await;
+    let (university_public_key, credential, signature) = sample_college_inputs();
+    let gen_request = json!({
+        "nonce": nonce,
+        "university_public_key": university_public_key,
+        "credential": credential,
+        "holder_secret": HOLDER_SECRET.to_string(),
+        "signature": signature,
+        "proving_key": proving_key,
+        "verifying_key": verifying_key
+    });
+    let gen_res = client
+        .post("http://localhost:8080/college_degree/generate")
+        .json(&gen_request)
+        .send()
+        .await
+        .expect("Failed to send generate request for college credential");
+    assert!(gen_res.status().is_success(), "Generation failed with status: {}", gen_res.status());
+    let gen_json: serde_json::Value = gen_res.json().await.expect("Failed to parse college generation JSON");
+
+    let verify_request = json!({
+        "nonce": nonce,
+        "proof": gen_json["proof"],
+        "university_public_key": university_public_key,
+        "verifying_key": verifying_key
+    });
+    let verify_res = client
+        .post("http://localhost:8080/college_degree/verify")
+        .json(&verify_request)
+        .send()
+        .await
+        .expect("Failed to send verify request for college credential");
+    assert!(verify_res.status().is_success(), "Verification failed with status: {}", verify_res.status());
+    let verify_json: serde_json::Value = verify_res.json().await.expect("Failed to parse verification JSON");
+    assert_eq!(verify_json["proof_status"], json!(true));
+}
@@ -0,0 +1,58 @@
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use std::hash::Hash;
+
+use crate::error::ZkpError;
+
+/// An attribute that can be proven with a circuit over the scalar field `F`, BN254's by default
+///
+/// Implementors are marker types tying together the circuit, the inputs it takes and the order in which its public
+/// inputs are allocated. The generic [`setup`](crate::proof_system::setup), [`prove`](crate::proof_system::prove)
+/// and [`verify`](crate::proof_system::verify) functions work for any implementor, on any pairing engine whose
+/// scalar field is `F`.
+pub trait AttributeCircuit<F: PrimeField = Fr> {
+    /// Stable name of the circuit, hashed into its [fingerprint](crate::keys::circuit_fingerprint)
+    const NAME: &'static str;
+
+    /// Bumped whenever the circuit's constraints change, so keys for an older version are rejected
+    const VERSION: u32;
+
+    /// Shape of the circuit fixed at setup (e.g. a tree depth); `()` for circuits with a single shape
+    ///
+    /// Fingerprints are cached per shape, hence the `Hash` and `'static` bounds.
+    type Config: Clone + Eq + Hash + Send + Sync + 'static;
+
+    /// Values the verifier knows, including the challenge nonce
+    type PublicInputs;
+
+    /// Values only the prover knows
+    type PrivateInputs;
+
+    /// Constraint system checking the attribute
+    type Circuit: ConstraintSynthesizer<F>;
+
+    /// Rejects shapes the circuit cannot take, before any input is sampled or constraint synthesized
+    fn check_config(_config: &Self::Config) -> Result<(), ZkpError> {
+        Ok(())
+    }
+
+    /// Arbitrary valid inputs for a shape accepted by [`check_config`](Self::check_config), used to synthesize the
+    /// circuit during setup
+    fn sample_inputs(config: &Self::Config) -> (Self::PublicInputs, Self::PrivateInputs);
+
+    /// Rejects inputs that cannot fit the circuit before any constraint is synthesized
+    fn check_inputs(
+        _config: &Self::Config,
+        _public: &Self::PublicInputs,
+        _private: &Self::PrivateInputs,
+    ) -> Result<(), ZkpError> {
+        Ok(())
+    }
+
+    /// Builds the circuit with every input assigned
+    fn circuit(config: &Self::Config, public: &Self::PublicInputs, private: &Self::PrivateInputs) -> Self::Circuit;
+
+    /// Public inputs as field elements, in the order the circuit allocates them
+    fn public_inputs(public: &Self::PublicInputs) -> Vec<F>;
+}
@@ -0,0 +1,79 @@
+use ark_bn254::Fr;
+use ark_ec::{
+    models::CurveConfig,
+    twisted_edwards::{Affine, MontCurveConfig, Projective, TECurveConfig},
+};
+use ark_ff::MontFp;
+use ark_r1cs_std::{fields::fp::FpVar, groups::curves::twisted_edwards::AffineVar};
+
+// Baby Jubjub is the twisted Edwards curve whose base field is the BN254 scalar field, which makes
+// its group operations cheap to express in a BN254 circuit. Parameters follow EIP-2494, with the
+// generator of the prime-order subgroup (circomlib's `Base8`) used as the curve generator.
+
+// The field derive expands to code tripping lints newer than ark-ff 0.4, so it lives in its own module
+#[allow(unexpected_cfgs, non_local_definitions)]
+mod scalar_field {
+    use ark_ff::fields::{Fp256, MontBackend, MontConfig};
+
+    /// Scalar field of the prime-order subgroup of Baby Jubjub
+    #[derive(MontConfig)]
+    #[modulus = "2736030358979909402780800718157159386076813972158567259200215660948447373041"]
+    #[generator = "31"]
+    pub struct FsConfig;
+    pub type Fs = Fp256<MontBackend<FsConfig, 4>>;
+}
+
+pub use scalar_field::{Fs, FsConfig};
+
+/// Curve parameters of Baby Jubjub: `168700 x^2 + y^2 = 1 + 168696 x^2 y^2` over the BN254 scalar field
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct BabyJubjubConfig;
+
+pub type EdwardsAffine = Affine<BabyJubjubConfig>;
+pub type EdwardsProjective = Projective<BabyJubjubConfig>;
+
+/// In-circuit Baby Jubjub point over BN254 scalar field variables
+pub type EdwardsVar = AffineVar<BabyJubjubConfig, FpVar<Fr>>;
+
+impl CurveConfig for BabyJubjubConfig {
+    type BaseField = Fr;
+    type ScalarField = Fs;
+
+    /// COFACTOR = 8
+    const COFACTOR: &'static [u64] = &[8];
+
+    /// COFACTOR_INV = COFACTOR^{-1} mod l
+    const COFACTOR_INV: Fs =
+        MontFp!("2394026564107420727433200628387514462817212225638746351800188703329891451411");
+}
+
+impl TECurveConfig for BabyJubjubConfig {
+    /// COEFF_A = 168700
+    const COEFF_A: Fr = MontFp!("168700");
+
+    /// COEFF_D = 168696
+    const COEFF_D: Fr = MontFp!("168696");
+
+    /// Generator of the prime-order subgroup
+    const GENERATOR: EdwardsAffine = EdwardsAffine::new_unchecked(GENERATOR_X, GENERATOR_Y);
+
+    type MontCurveConfig = BabyJubjubConfig;
+}
+
+impl MontCurveConfig for BabyJubjubConfig {
+    /// COEFF_A = 2 (a + d) / (a - d) = 168698
+    const COEFF_A: Fr = MontFp!("168698");
+
+    /// COEFF_B = 4 / (a - d) = 1
+    const COEFF_B: Fr = MontFp!("1");
+
+    type TECurveConfig = BabyJubjubConfig;
+}
+
+/// GENERATOR_X
+pub const GENERATOR_X: Fr =
+    MontFp!("5299619240641551281634865583518297030282874472190772894086521144482721001553");
+
+/// GENERATOR_Y
+pub const GENERATOR_Y: Fr =
+    MontFp!("16950150798460657717958625567821834550301663161624707787222815936182638968203");
@@ -0,0 +1,132 @@
+use ark_bn254::{Bn254, Fr};
+use ark_ec::{CurveGroup, VariableBaseMSM, pairing::Pairing};
+use ark_ff::Zero;
+use ark_groth16::{Groth16, Proof, prepare_verifying_key};
+use rand::{CryptoRng, RngCore, thread_rng};
+
+use crate::attribute::AttributeCircuit;
+use crate::circuits::age_verification::witness_calculator::{AgeConfig, AgePublicInputs, AgeVerification};
+use crate::error::ZkpError;
+use crate::keys::{CircuitVerifyingKey, check_fingerprint};
+use crate::proof_system::check_public_input_count;
+
+// Batch verification of Groth16 proofs made with the same key. Each proof satisfies
+//
+//   e(A_i, B_i) = e(alpha, beta) * e(IC_i, gamma) * e(C_i, delta)
+//
+// where IC_i is the public input commitment. Raising the i-th equation to a random r_i and multiplying them all
+// gives a single check with n + 3 pairings instead of 4n, and the IC and C terms collapse into two MSMs:
+//
+//   prod e(r_i * A_i, B_i) * e(-sum(r_i) * alpha, beta) * e(-sum(r_i * IC_i), gamma) * e(-sum(r_i * C_i), delta) = 1
+//
+// A batch containing an invalid proof passes only if the r_i happen to cancel it out, which for 128-bit random
+// scalars has probability about 2^-128.
+
+/// Verifies many proofs for the same circuit, returning one result per proof
+///
+/// All proofs are first checked together in a single multi-pairing. If that fails, every proof is verified on
+/// its own to tell which ones are invalid.
+pub fn batch_verify<E: Pairing, C: AttributeCircuit<E::ScalarField>>(
+    vk: &CircuitVerifyingKey<E>,
+    config: &C::Config,
+    items: &[(Proof<E>, C::PublicInputs)],
+) -> Result<Vec<bool>, ZkpError> {
+    batch_verify_with_rng::<E, C, _>(vk, config, items, &mut thread_rng())
+}
+
+/// Batch verification drawing the random combination from a caller-supplied RNG
+///
+/// The RNG must be unpredictable to whoever produced the proofs, or they could craft invalid proofs that cancel out.
+pub fn batch_verify_with_rng<E: Pairing, C: AttributeCircuit<E::ScalarField>, R: RngCore + CryptoRng>(
+    vk: &CircuitVerifyingKey<E>,
+    config: &C::Config,
+    items: &[(Proof<E>, C::PublicInputs)],
+    rng: &mut R,
+) -> Result<Vec<bool>, ZkpError> {
+    check_fingerprint::<E::ScalarField, C>(&vk.fingerprint, config)?;
+    let public_inputs: Vec<Vec<E::ScalarField>> = items.iter().map(|(_, public)| C::public_inputs(public)).collect();
+    for inputs in &public_inputs {
+        check_public_input_count(&vk.key, inputs)?;
+    }
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let proofs: Vec<&Proof<E>> = items.iter().map(|(proof, _)| proof).collect();
+    if combined_check(vk, &proofs, &public_inputs, rng) {
+        return Ok(vec![true; items.len()]);
+    }
+
+    // At least one proof is invalid; find out which
+    let pvk = prepare_verifying_key(&vk.key);
+    proofs
+        .iter()
+        .zip(&public_inputs)
+        .map(|(proof, inputs)| Ok(Groth16::<E>::verify_proof(&pvk, proof, inputs)?))
+        .collect()
+}
+
+/// Batch verifies age proofs, given as (proof, nonce, min_age, max_age), made with keys of the given bit width
+///
+/// Bounded and unbounded proofs use different keys, so a batch must not mix them.
+pub fn batch_verify_age(
+    vk: &CircuitVerifyingKey,
+    num_bits: usize,
+    items: &[(Proof<Bn254>, Fr, u64, Option<u64>)],
+) -> Result<Vec<bool>, ZkpError> {
+    let config = age_batch_config(num_bits, items)?;
+    let items: Vec<(Proof<Bn254>, AgePublicInputs)> = items
+        .iter()
+        .map(|(proof, nonce, min_age, max_age)| {
+            (proof.clone(), AgePublicInputs { nonce: *nonce, min_age: *min_age, max_age: *max_age })
+        })
+        .collect();
+    batch_verify::<Bn254, AgeVerification>(vk, &config, &items)
+}
+
+/// Shape of the age circuit a batch of (proof, nonce, min_age, max_age) items was proven with
+///
+/// Fails when the batch mixes bounded and unbounded proofs. Callers can use it, with
+/// [`check_fingerprint`], to validate a batch before spending anything on it, such as challenge nonces.
+pub fn age_batch_config<T>(num_bits: usize, items: &[(T, Fr, u64, Option<u64>)]) -> Result<AgeConfig, ZkpError> {
+    let upper_bounded = items.first().is_some_and(|(_, _, _, max_age)| max_age.is_some());
+    if items.iter().any(|(_, _, _, max_age)| max_age.is_some() != upper_bounded) {
+        return Err(ZkpError::InvalidInput("a batch cannot mix bounded and unbounded age proofs".to_string()));
+    }
+    Ok(AgeConfig { num_bits, upper_bounded })
+}
+
+fn combined_check<E: Pairing, R: RngCore + CryptoRng>(
+    vk: &CircuitVerifyingKey<E>,
+    proofs: &[&Proof<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+    rng: &mut R,
+) -> bool {
+    let vk = &vk.key;
+    let r: Vec<E::ScalarField> = proofs
+        .iter()
+        .map(|_| E::ScalarField::from(((rng.next_u64() as u128) << 64) | rng.next_u64() as u128))
+        .collect();
+    let r_sum: E::ScalarField = r.iter().sum();
+
+    // sum(r_i * IC_i) as one MSM over the key's input bases
+    let mut input_scalars = vec![E::ScalarField::zero(); vk.gamma_abc_g1.len()];
+    input_scalars[0] = r_sum;
+    for (r_i, inputs) in r.iter().zip(public_inputs) {
+        for (scalar, input) in input_scalars[1..].iter_mut().zip(inputs) {
+            *scalar += *r_i * input;
+        }
+    }
+    let inputs_acc = E::G1::msm_unchecked(&vk.gamma_abc_g1, &input_scalars);
+
+    let c_points: Vec<E::G1Affine> = proofs.iter().map(|proof| proof.c).collect();
+    let c_acc = E::G1::msm_unchecked(&c_points, &r);
+
+    let mut g1: Vec<E::G1> = proofs.iter().zip(&r).map(|(proof, r_i)| proof.a * r_i).collect();
+    let mut g2: Vec<E::G2Affine> = proofs.iter().map(|proof| proof.b).collect();
+    g1.extend([-(vk.alpha_g1 * r_sum), -inputs_acc, -c_acc]);
+    g2.extend([vk.beta_g2, vk.gamma_g2, vk.delta_g2]);
+
+    let g1 = E::G1::normalize_batch(&g1);
+    E::multi_pairing(g1, g2).is_zero()
+}
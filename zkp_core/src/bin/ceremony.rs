@@ -0,0 +1,211 @@
+// Command line tool for running the setup ceremonies of `zkp_core::powers_of_tau` (phase 1, shared by every circuit)
+// and `zkp_core::ceremony` (phase 2, per circuit). Marlin needs no phase 2: `index` derives its keys for any circuit
+// straight from the powers of tau (with the `experimental-marlin` feature).
+//
+//   ceremony tau new <size> <powers-out>
+//   ceremony tau contribute <powers-in> <powers-out>
+//   ceremony tau verify <powers>
+//   ceremony init <circuit> <powers> <transcript-out> [depth | num_bits [upper_bounded]]
+//   ceremony contribute <transcript-in> <transcript-out>
+//   ceremony verify <circuit> <powers> <transcript> <proving-key-out> <verifying-key-out> [options]
+//   ceremony index <circuit> <powers> <proving-key-out> <verifying-key-out> [options]
+//
+// Circuits: age, date_of_birth, signed_age, citizenship, college_credential. Keys are written in the
+// `zkp_core::key_file` container format.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress};
+use rand::thread_rng;
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use zkp_core::ceremony::Ceremony;
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgeVerification, DEFAULT_AGE_BITS};
+use zkp_core::circuits::citizenship_verification::witness_calculator::{CitizenshipVerification, DEFAULT_TREE_DEPTH};
+use zkp_core::circuits::college_credential_verification::witness_calculator::CollegeCredentialVerification;
+use zkp_core::circuits::date_of_birth_verification::witness_calculator::DateOfBirthVerification;
+use zkp_core::circuits::signed_age_verification::witness_calculator::SignedAgeVerification;
+#[cfg(feature = "experimental-marlin")]
+use zkp_core::key_file::{save_marlin_proving_key, save_marlin_verifying_key};
+use zkp_core::key_file::{save_proving_key, save_verifying_key};
+#[cfg(feature = "experimental-marlin")]
+use zkp_core::marlin;
+use zkp_core::powers_of_tau::PowersOfTau;
+
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+const USAGE: &str = "usage:
+  ceremony tau new <size> <powers-out>
+  ceremony tau contribute <powers-in> <powers-out>
+  ceremony tau verify <powers>
+  ceremony init <circuit> <powers> <transcript-out> [options]
+  ceremony contribute <transcript-in> <transcript-out>
+  ceremony verify <circuit> <powers> <transcript> <proving-key-out> <verifying-key-out> [options]
+  ceremony index <circuit> <powers> <proving-key-out> <verifying-key-out> [options]    (experimental-marlin)
+      circuits: age, date_of_birth, signed_age, citizenship, college_credential
+      options: age: [num_bits] [upper_bounded]    citizenship: [depth]";
+
+// A circuit named on the command line, with its shape
+enum Circuit {
+    Age(AgeConfig),
+    DateOfBirth,
+    SignedAge,
+    Citizenship(usize),
+    CollegeCredential,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["tau", "new", size, out] => tau_new(size, out),
+        ["tau", "contribute", input, out] => tau_contribute(input, out),
+        ["tau", "verify", input] => tau_verify(input),
+        ["init", circuit, powers, out, options @ ..] => init(circuit, powers, out, options),
+        ["contribute", input, out] => contribute(input, out),
+        ["verify", circuit, powers, input, pk_out, vk_out, options @ ..] => {
+            verify(circuit, powers, input, pk_out, vk_out, options)
+        }
+        #[cfg(feature = "experimental-marlin")]
+        ["index", circuit, powers, pk_out, vk_out, options @ ..] => index(circuit, powers, pk_out, vk_out, options),
+        _ => Err(USAGE.into()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn tau_new(size: &str, out: &str) -> CliResult {
+    let powers = PowersOfTau::new(size.parse()?)?;
+    write_file(out, &powers)?;
+    println!("wrote powers of tau of size {} to {}; it needs contributions before use", size, out);
+    Ok(())
+}
+
+fn tau_contribute(input: &str, out: &str) -> CliResult {
+    let mut powers: PowersOfTau = read_file(input)?;
+
+    // Refuse to build on a transcript that is already broken
+    powers.verify()?;
+    let contribution = powers.contribute(&mut thread_rng());
+
+    write_file(out, &powers)?;
+    println!("contribution #{} hash: {}", powers.contributions.len(), to_hex(&contribution.hash()));
+    Ok(())
+}
+
+fn tau_verify(input: &str) -> CliResult {
+    let powers: PowersOfTau = read_file(input)?;
+    powers.verify()?;
+
+    for (i, contribution) in powers.contributions.iter().enumerate() {
+        println!("contribution #{} hash: {}", i + 1, to_hex(&contribution.hash()));
+    }
+    println!("powers of tau of size {} verified", powers.current.size());
+    Ok(())
+}
+
+fn init(circuit: &str, powers: &str, out: &str, options: &[&str]) -> CliResult {
+    let powers: PowersOfTau = read_file(powers)?;
+    let ceremony = match parse_circuit(circuit, options)? {
+        Circuit::Age(config) => Ceremony::new::<AgeVerification>(&powers, &config)?,
+        Circuit::DateOfBirth => Ceremony::new::<DateOfBirthVerification>(&powers, &())?,
+        Circuit::SignedAge => Ceremony::new::<SignedAgeVerification>(&powers, &())?,
+        Circuit::Citizenship(depth) => Ceremony::new::<CitizenshipVerification>(&powers, &depth)?,
+        Circuit::CollegeCredential => Ceremony::new::<CollegeCredentialVerification>(&powers, &())?,
+    };
+
+    write_file(out, &ceremony)?;
+    println!("wrote initial parameters for `{}` to {}", circuit, out);
+    Ok(())
+}
+
+fn contribute(input: &str, out: &str) -> CliResult {
+    let mut ceremony: Ceremony = read_file(input)?;
+
+    // Refuse to build on a transcript that is already broken
+    ceremony.verify_contributions()?;
+    let contribution = ceremony.contribute(&mut thread_rng());
+
+    write_file(out, &ceremony)?;
+    println!("contribution #{} hash: {}", ceremony.contributions.len(), to_hex(&contribution.hash()));
+    Ok(())
+}
+
+fn verify(circuit: &str, powers: &str, input: &str, pk_out: &str, vk_out: &str, options: &[&str]) -> CliResult {
+    let powers: PowersOfTau = read_file(powers)?;
+    let ceremony: Ceremony = read_file(input)?;
+    let (pk, vk) = match parse_circuit(circuit, options)? {
+        Circuit::Age(config) => ceremony.verify::<AgeVerification>(&powers, &config)?,
+        Circuit::DateOfBirth => ceremony.verify::<DateOfBirthVerification>(&powers, &())?,
+        Circuit::SignedAge => ceremony.verify::<SignedAgeVerification>(&powers, &())?,
+        Circuit::Citizenship(depth) => ceremony.verify::<CitizenshipVerification>(&powers, &depth)?,
+        Circuit::CollegeCredential => ceremony.verify::<CollegeCredentialVerification>(&powers, &())?,
+    };
+    println!("circuit fingerprint: {}", ceremony.fingerprint);
+
+    for (i, contribution) in ceremony.contributions.iter().enumerate() {
+        println!("contribution #{} hash: {}", i + 1, to_hex(&contribution.hash()));
+    }
+
+    save_proving_key(pk_out, &pk, Compress::No)?;
+    save_verifying_key(vk_out, &vk, Compress::No)?;
+
+    println!("transcript verified; wrote {} and {}", pk_out, vk_out);
+    Ok(())
+}
+
+#[cfg(feature = "experimental-marlin")]
+fn index(circuit: &str, powers: &str, pk_out: &str, vk_out: &str, options: &[&str]) -> CliResult {
+    let powers: PowersOfTau = read_file(powers)?;
+    let (pk, vk) = match parse_circuit(circuit, options)? {
+        Circuit::Age(config) => marlin::index::<AgeVerification>(&powers, &config)?,
+        Circuit::DateOfBirth => marlin::index::<DateOfBirthVerification>(&powers, &())?,
+        Circuit::SignedAge => marlin::index::<SignedAgeVerification>(&powers, &())?,
+        Circuit::Citizenship(depth) => marlin::index::<CitizenshipVerification>(&powers, &depth)?,
+        Circuit::CollegeCredential => marlin::index::<CollegeCredentialVerification>(&powers, &())?,
+    };
+    println!("circuit fingerprint: {}", vk.fingerprint);
+
+    save_marlin_proving_key(pk_out, &pk, Compress::No)?;
+    save_marlin_verifying_key(vk_out, &vk, Compress::No)?;
+
+    println!("powers of tau verified; wrote Marlin keys {} and {}", pk_out, vk_out);
+    Ok(())
+}
+
+fn parse_circuit(circuit: &str, options: &[&str]) -> Result<Circuit, Box<dyn std::error::Error>> {
+    let option = |i: usize| options.get(i).map(|v| v.parse::<usize>()).transpose();
+    Ok(match circuit {
+        "age" => {
+            let num_bits = option(0)?.unwrap_or(DEFAULT_AGE_BITS);
+            let upper_bounded = options.get(1).map(|v| v.parse::<bool>()).transpose()?.unwrap_or(false);
+            Circuit::Age(AgeConfig { num_bits, upper_bounded })
+        }
+        "date_of_birth" => Circuit::DateOfBirth,
+        "signed_age" => Circuit::SignedAge,
+        "citizenship" => Circuit::Citizenship(option(0)?.unwrap_or(DEFAULT_TREE_DEPTH)),
+        "college_credential" => Circuit::CollegeCredential,
+        _ => return Err(format!("unknown circuit `{}`\n{}", circuit, USAGE).into()),
+    })
+}
+
+fn read_file<T: CanonicalDeserialize>(path: &str) -> Result<T, Box<dyn std::error::Error>> {
+    let bytes = fs::read(path)?;
+    Ok(T::deserialize_uncompressed(bytes.as_slice())?)
+}
+
+fn write_file<T: CanonicalSerialize>(path: &str, value: &T) -> CliResult {
+    let mut bytes = Vec::new();
+    value.serialize_uncompressed(&mut bytes)?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
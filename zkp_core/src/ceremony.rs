@@ -0,0 +1,371 @@
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM, pairing::Pairing};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{CryptoRng, RngCore, thread_rng};
+use sha2::{Digest, Sha256};
+
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+use crate::keys::{CircuitFingerprint, CircuitProvingKey, CircuitVerifyingKey, circuit_matrices, fingerprint_matrices};
+use crate::powers_of_tau::PowersOfTau;
+
+// Multi-party phase-2 ceremony for Groth16 keys.
+//
+// Groth16 parameters contain a circuit-specific secret delta: the proving key holds `delta` in G1 and G2, and the
+// `h_query` and `l_query` elements are divided by it. Each participant multiplies delta by a fresh secret `d`
+// (dividing the queries by `d`) and throws `d` away, so the final delta is unknown unless every participant
+// colludes. A contribution carries a proof of knowledge of `d`: a random point `s` with `s_d = d * s`, and
+// `r_d = d * r` where `r` is hashed to G2 from the transcript so nobody knows its discrete log.
+//
+// The other secrets (tau, alpha, beta) come from a powers-of-tau (phase 1) ceremony, see `powers_of_tau`. The
+// initial parameters are derived from its accumulator without any randomness, with gamma and delta set to one, so
+// anyone holding the phase-1 transcript can recompute them and `Ceremony::verify` does.
+
+/// One participant's update of delta, with the proof that it was applied by someone who knows the secret
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution {
+    /// `delta` in G1 after this contribution
+    pub delta_g1: G1Affine,
+    /// Random base of the proof of knowledge
+    pub s: G1Affine,
+    /// `d * s`
+    pub s_delta: G1Affine,
+    /// `d * r`, where `r` is derived from the previous delta, `s` and `s_delta`
+    pub r_delta: G2Affine,
+}
+
+impl Contribution {
+    /// Digest a participant can publish to let others check their contribution is part of the transcript
+    pub fn hash(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes).expect("writing to a Vec cannot fail");
+        Sha256::digest(&bytes).into()
+    }
+}
+
+/// Transcript of a ceremony: the initial parameters, every contribution in order, and the current parameters
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Ceremony {
+    /// Fingerprint of the circuit the parameters belong to, carried over to the final keys
+    pub fingerprint: CircuitFingerprint,
+    pub initial: ProvingKey<Bn254>,
+    pub contributions: Vec<Contribution>,
+    pub current: ProvingKey<Bn254>,
+}
+
+impl Ceremony {
+    /// Starts a ceremony for circuit `C` of the given shape from a powers-of-tau transcript
+    ///
+    /// The transcript must verify and have at least one contribution, and be large enough for the circuit.
+    pub fn new<C: AttributeCircuit<Fr>>(powers: &PowersOfTau, config: &C::Config) -> Result<Self, ZkpError> {
+        let initial = initial_parameters::<C>(powers, config)?;
+        Ok(Self {
+            fingerprint: initial.fingerprint,
+            current: initial.key.clone(),
+            initial: initial.key,
+            contributions: Vec::new(),
+        })
+    }
+
+    /// Applies a contribution with fresh randomness from `rng` and returns it. The secret never leaves this call
+    pub fn contribute<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> Contribution {
+        let (next, contribution) = contribute(&self.current, rng);
+        self.current = next;
+        self.contributions.push(contribution.clone());
+        contribution
+    }
+
+    /// Checks the whole transcript against the powers of tau it started from and returns the final keys
+    ///
+    /// The initial parameters must be the ones derived from `powers` for circuit `C` of the given shape, on top of
+    /// the checks of [`Ceremony::verify_contributions`].
+    pub fn verify<C: AttributeCircuit<Fr>>(
+        &self,
+        powers: &PowersOfTau,
+        config: &C::Config,
+    ) -> Result<(CircuitProvingKey, CircuitVerifyingKey), ZkpError> {
+        let initial = initial_parameters::<C>(powers, config)?;
+        if initial.fingerprint != self.fingerprint || initial.key != self.initial {
+            return Err(ZkpError::InvalidInput(
+                "the initial parameters were not derived from these powers of tau for this circuit".to_string(),
+            ));
+        }
+        self.verify_contributions()
+    }
+
+    /// Checks the contributions and returns the final keys, trusting the initial parameters
+    ///
+    /// Every contribution must prove knowledge of its secret and extend the previous delta, and the current
+    /// parameters must differ from the initial ones only by the accumulated delta.
+    pub fn verify_contributions(&self) -> Result<(CircuitProvingKey, CircuitVerifyingKey), ZkpError> {
+        let mut delta_g1 = self.initial.delta_g1;
+        for (index, contribution) in self.contributions.iter().enumerate() {
+            check_contribution(&delta_g1, contribution)
+                .map_err(|reason| ZkpError::InvalidContribution { index, reason })?;
+            delta_g1 = contribution.delta_g1;
+        }
+
+        let last = self.contributions.len().saturating_sub(1);
+        if self.current.delta_g1 != delta_g1 {
+            return Err(ZkpError::InvalidContribution {
+                index: last,
+                reason: "current parameters do not end with the last contribution's delta".to_string(),
+            });
+        }
+        check_update(&self.initial, &self.current)
+            .map_err(|reason| ZkpError::InvalidContribution { index: last, reason })?;
+
+        let proving_key = CircuitProvingKey { fingerprint: self.fingerprint, key: self.current.clone() };
+        let verifying_key = proving_key.verifying_key();
+        Ok((proving_key, verifying_key))
+    }
+}
+
+/// Multiplies delta by a fresh secret, returning the updated parameters and the contribution proving it
+pub fn contribute<R: RngCore + CryptoRng>(
+    params: &ProvingKey<Bn254>,
+    rng: &mut R,
+) -> (ProvingKey<Bn254>, Contribution) {
+    let d = loop {
+        let d = Fr::rand(rng);
+        if !d.is_zero() {
+            break d;
+        }
+    };
+    let d_inv = d.inverse().expect("d is non-zero");
+
+    // Proof of knowledge of d
+    let s = G1Projective::rand(rng).into_affine();
+    let s_delta = (s * d).into_affine();
+    let r = transcript_point(DELTA_DOMAIN, &[&params.delta_g1, &s, &s_delta]);
+    let r_delta = (r * d).into_affine();
+
+    // delta *= d, and every element divided by delta is divided by d
+    let mut next = params.clone();
+    next.delta_g1 = (params.delta_g1 * d).into_affine();
+    next.vk.delta_g2 = (params.vk.delta_g2 * d).into_affine();
+    next.h_query = scale(&params.h_query, d_inv);
+    next.l_query = scale(&params.l_query, d_inv);
+
+    let contribution = Contribution {
+        delta_g1: next.delta_g1,
+        s,
+        s_delta,
+        r_delta,
+    };
+    (next, contribution)
+}
+
+/// Checks a single step of the ceremony, for participants who want to validate their predecessor's output
+pub fn verify_contribution(
+    before: &ProvingKey<Bn254>,
+    after: &ProvingKey<Bn254>,
+    contribution: &Contribution,
+) -> Result<(), ZkpError> {
+    let invalid = |reason| ZkpError::InvalidContribution { index: 0, reason };
+    check_contribution(&before.delta_g1, contribution).map_err(invalid)?;
+    if after.delta_g1 != contribution.delta_g1 {
+        return Err(invalid("parameters do not carry the contribution's delta".to_string()));
+    }
+    check_update(before, after).map_err(invalid)
+}
+
+// Domain separator for the transcript point of a contribution's proof of knowledge
+const DELTA_DOMAIN: &[u8] = b"zkp-ceremony-g2";
+
+// Groth16 parameters of circuit `C` for the secrets of the powers of tau, with gamma = delta = 1. This is what
+// `Groth16::generate_parameters` computes (with the libsnark R1CS-to-QAP reduction), except that the Lagrange
+// polynomials are evaluated at tau in the exponent, by an inverse FFT over the powers of tau
+fn initial_parameters<C: AttributeCircuit<Fr>>(
+    powers: &PowersOfTau,
+    config: &C::Config,
+) -> Result<CircuitProvingKey, ZkpError> {
+    powers.verify()?;
+    if powers.contributions.is_empty() {
+        return Err(ZkpError::InvalidInput("the powers of tau have no contributions, so tau is public".to_string()));
+    }
+
+    let matrices = circuit_matrices::<Fr, C>(config)?;
+    let fingerprint = fingerprint_matrices::<Fr, C>(&matrices);
+    let num_instance = matrices.num_instance_variables;
+    let num_constraints = matrices.num_constraints;
+    let domain = GeneralEvaluationDomain::<Fr>::new(num_constraints + num_instance)
+        .ok_or(ZkpError::Setup(SynthesisError::PolynomialDegreeTooLarge))?;
+    let size = domain.size();
+    let accumulator = &powers.current;
+    if size > accumulator.size() {
+        return Err(ZkpError::InvalidInput(format!(
+            "the circuit needs powers of tau of size {} but the transcript has {}",
+            size,
+            accumulator.size()
+        )));
+    }
+
+    // L_j(tau), alpha * L_j(tau) and beta * L_j(tau) in G1 and L_j(tau) in G2 for the domain's Lagrange basis
+    let lagrange_g1 = lagrange::<G1Projective>(&domain, &accumulator.tau_g1[..size]);
+    let lagrange_alpha = lagrange::<G1Projective>(&domain, &accumulator.alpha_tau_g1[..size]);
+    let lagrange_beta = lagrange::<G1Projective>(&domain, &accumulator.beta_tau_g1[..size]);
+    let lagrange_g2 = lagrange::<G2Projective>(&domain, &accumulator.tau_g2[..size]);
+
+    // A_i, B_i and C_i at tau, and beta * A_i + alpha * B_i + C_i. The reduction adds the constraint
+    // `x_i * 0 = 0` for every instance variable after the circuit's own constraints
+    let num_variables = num_instance + matrices.num_witness_variables;
+    let mut a = vec![G1Projective::zero(); num_variables];
+    let mut b_g1 = vec![G1Projective::zero(); num_variables];
+    let mut b_g2 = vec![G2Projective::zero(); num_variables];
+    let mut abc = vec![G1Projective::zero(); num_variables];
+    for i in 0..num_instance {
+        a[i] += lagrange_g1[num_constraints + i];
+        abc[i] += lagrange_beta[num_constraints + i];
+    }
+    for j in 0..num_constraints {
+        for (coefficient, i) in &matrices.a[j] {
+            a[*i] += lagrange_g1[j] * coefficient;
+            abc[*i] += lagrange_beta[j] * coefficient;
+        }
+        for (coefficient, i) in &matrices.b[j] {
+            b_g1[*i] += lagrange_g1[j] * coefficient;
+            b_g2[*i] += lagrange_g2[j] * coefficient;
+            abc[*i] += lagrange_alpha[j] * coefficient;
+        }
+        for (coefficient, i) in &matrices.c[j] {
+            abc[*i] += lagrange_g1[j] * coefficient;
+        }
+    }
+
+    // tau^i * Z(tau) = tau^(i + size) - tau^i, as Z is the vanishing polynomial x^size - 1
+    let h: Vec<G1Projective> = (0..size - 1)
+        .map(|i| accumulator.tau_g1[i + size].into_group() - accumulator.tau_g1[i])
+        .collect();
+
+    let mut abc = G1Projective::normalize_batch(&abc);
+    let l_query = abc.split_off(num_instance);
+    let key = ProvingKey {
+        vk: VerifyingKey {
+            alpha_g1: accumulator.alpha_tau_g1[0],
+            beta_g2: accumulator.beta_g2,
+            gamma_g2: G2Affine::generator(),
+            delta_g2: G2Affine::generator(),
+            gamma_abc_g1: abc,
+        },
+        beta_g1: accumulator.beta_tau_g1[0],
+        delta_g1: G1Affine::generator(),
+        a_query: G1Projective::normalize_batch(&a),
+        b_g1_query: G1Projective::normalize_batch(&b_g1),
+        b_g2_query: G2Projective::normalize_batch(&b_g2),
+        h_query: G1Projective::normalize_batch(&h),
+        l_query,
+    };
+    Ok(CircuitProvingKey { fingerprint, key })
+}
+
+// Inverse FFT of tau^i in the exponent: L_j(tau) = 1/n * sum_i omega^(-ij) * tau^i
+fn lagrange<G: CurveGroup<ScalarField = Fr>>(domain: &GeneralEvaluationDomain<Fr>, powers: &[G::Affine]) -> Vec<G> {
+    let mut points: Vec<G> = powers.iter().map(|p| p.into_group()).collect();
+    domain.ifft_in_place(&mut points);
+    points
+}
+
+fn scale(points: &[G1Affine], factor: Fr) -> Vec<G1Affine> {
+    let scaled: Vec<G1Projective> = points.iter().map(|p| *p * factor).collect();
+    G1Projective::normalize_batch(&scaled)
+}
+
+// Checks the proof of knowledge and that delta_g1 was multiplied by the same secret
+fn check_contribution(previous_delta_g1: &G1Affine, contribution: &Contribution) -> Result<(), String> {
+    let Contribution { delta_g1, s, s_delta, r_delta } = contribution;
+    if s.is_zero() || s_delta.is_zero() || delta_g1.is_zero() {
+        return Err("contribution contains the identity".to_string());
+    }
+
+    let r = transcript_point(DELTA_DOMAIN, &[previous_delta_g1, s, s_delta]);
+
+    // s_delta / s == r_delta / r, i.e. the contributor knows d
+    if !same_ratio((*s, *s_delta), (r, *r_delta)) {
+        return Err("proof of knowledge does not verify".to_string());
+    }
+
+    // delta_g1 / previous_delta_g1 == r_delta / r, i.e. delta was multiplied by that d
+    if !same_ratio((*previous_delta_g1, *delta_g1), (r, *r_delta)) {
+        return Err("delta was not updated with the proven secret".to_string());
+    }
+
+    Ok(())
+}
+
+// Checks that `after` differs from `before` only by a change of delta, applied consistently everywhere
+fn check_update(before: &ProvingKey<Bn254>, after: &ProvingKey<Bn254>) -> Result<(), String> {
+    let unchanged = before.vk.alpha_g1 == after.vk.alpha_g1
+        && before.vk.beta_g2 == after.vk.beta_g2
+        && before.vk.gamma_g2 == after.vk.gamma_g2
+        && before.vk.gamma_abc_g1 == after.vk.gamma_abc_g1
+        && before.beta_g1 == after.beta_g1
+        && before.a_query == after.a_query
+        && before.b_g1_query == after.b_g1_query
+        && before.b_g2_query == after.b_g2_query
+        && before.h_query.len() == after.h_query.len()
+        && before.l_query.len() == after.l_query.len();
+    if !unchanged {
+        return Err("parameters other than delta were modified".to_string());
+    }
+
+    // delta in G1 and G2 must agree. beta_g1 and beta_g2 are the powers-of-tau accumulator's beta over the standard
+    // generators, and its verification already checked they share that beta, so they serve as the known pair
+    if !same_ratio((after.beta_g1, after.delta_g1), (after.vk.beta_g2, after.vk.delta_g2)) {
+        return Err("delta differs between G1 and G2".to_string());
+    }
+
+    // h and l were divided by exactly the change in delta: e(sum(rho * after), delta') == e(sum(rho * before), delta)
+    let mut rng = thread_rng();
+    for (name, before_query, after_query) in [
+        ("h", &before.h_query, &after.h_query),
+        ("l", &before.l_query, &after.l_query),
+    ] {
+        let rho: Vec<Fr> = (0..before_query.len()).map(|_| Fr::rand(&mut rng)).collect();
+        let before_sum = G1Projective::msm(before_query, &rho).map_err(|_| "query length mismatch".to_string())?;
+        let after_sum = G1Projective::msm(after_query, &rho).map_err(|_| "query length mismatch".to_string())?;
+        if Bn254::pairing(after_sum, after.vk.delta_g2) != Bn254::pairing(before_sum, before.vk.delta_g2) {
+            return Err(format!("{}_query was not rescaled by the change in delta", name));
+        }
+    }
+
+    Ok(())
+}
+
+// e(a.0, b.1) == e(a.1, b.0), i.e. a.1 / a.0 == b.1 / b.0 in the exponent
+pub(crate) fn same_ratio(a: (G1Affine, G1Affine), b: (G2Affine, G2Affine)) -> bool {
+    Bn254::pairing(a.0, b.1) == Bn254::pairing(a.1, b.0)
+}
+
+// Hashes the state a contribution builds on to a G2 point of unknown discrete log (try-and-increment)
+pub(crate) fn transcript_point(domain: &[u8], points: &[&G1Affine]) -> G2Affine {
+    let mut transcript = Vec::new();
+    for point in points {
+        point.serialize_compressed(&mut transcript).expect("writing to a Vec cannot fail");
+    }
+
+    let mut counter = 0u64;
+    loop {
+        let coordinate = |i: u8| {
+            let digest = Sha256::new()
+                .chain_update(domain)
+                .chain_update(&transcript)
+                .chain_update(counter.to_le_bytes())
+                .chain_update([i])
+                .finalize();
+            Fq::from_le_bytes_mod_order(&digest)
+        };
+        let x = Fq2::new(coordinate(0), coordinate(1));
+        if let Some(point) = G2Affine::get_point_from_x_unchecked(x, false) {
+            let point = point.clear_cofactor();
+            if !point.is_zero() {
+                return point;
+            }
+        }
+        counter += 1;
+    }
+}
@@ -1,37 +1,173 @@
 use ark_ff::PrimeField;
 use ark_bn254::Fr;
+use ark_relations::ns;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
-use std::cmp::Ordering;
 
-// Define the circuit for proving user_age >= min_age
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+use crate::nonce::nonce_input_gadget;
+use crate::range::{MAX_RANGE_BITS, enforce_in_range_gadget, enforce_less_or_equal_gadget};
+
+/// Default bit width of the ages handled by the circuit, allowing ages up to 255
+pub const DEFAULT_AGE_BITS: usize = 8;
+
+// Define the circuit for proving min_age <= user_age, and user_age <= max_age when the circuit has an upper bound.
+// Every age is decomposed into num_bits bits, so values of 2^num_bits or more are unsatisfiable. The bit width and
+// whether max_age is present are fixed at setup and must match between key generation and proving.
 pub struct AgeVerificationCircuit<F: PrimeField> {
+    pub num_bits: usize,
+    pub upper_bounded: bool,
+    pub nonce: Option<F>,
     pub user_age: Option<F>,
     pub min_age: Option<F>,
+    pub max_age: Option<F>,
 }
 
 // AgeVerificationCircuit struct implements the trait ConstraintSynthesizer
 impl<F: PrimeField> ConstraintSynthesizer<F> for AgeVerificationCircuit<F> {
     fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
-        // Allocate the private witness (user_age) and public input (min_age)
-        let user_age_var = FpVar::<F>::new_witness(cs.clone(), || {
+        // Allocate the verifier's nonce first, so it is the first public input
+        nonce_input_gadget(ns!(cs, "nonce").cs(), self.nonce)?;
+
+        // Allocate the private witness (user_age) and public inputs (min_age, then max_age if bounded)
+        let user_age_var = FpVar::<F>::new_witness(ns!(cs, "user_age"), || {
             self.user_age.ok_or(SynthesisError::AssignmentMissing)
         })?; 
-        let min_age_var = FpVar::<F>::new_input(cs.clone(), || {
+        let min_age_var = FpVar::<F>::new_input(ns!(cs, "min_age"), || {
             self.min_age.ok_or(SynthesisError::AssignmentMissing)
         })?;
 
+        // Bound every age to num_bits bits
+        {
+            let _ns = ns!(cs, "user_age_in_range");
+            enforce_in_range_gadget(&user_age_var, self.num_bits)?;
+        }
+        {
+            let _ns = ns!(cs, "min_age_in_range");
+            enforce_in_range_gadget(&min_age_var, self.num_bits)?;
+        }
+
         // Enforce user_age >= min_age
-        user_age_var.enforce_cmp(&min_age_var, Ordering::Greater, true)?;
+        {
+            let _ns = ns!(cs, "user_age_at_least_min_age");
+            enforce_less_or_equal_gadget(&min_age_var, &user_age_var, self.num_bits)?;
+        }
+
+        if self.upper_bounded {
+            let max_age_var = FpVar::<F>::new_input(ns!(cs, "max_age"), || {
+                self.max_age.ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            {
+                let _ns = ns!(cs, "max_age_in_range");
+                enforce_in_range_gadget(&max_age_var, self.num_bits)?;
+            }
+
+            // Enforce user_age <= max_age
+            let _ns = ns!(cs, "user_age_at_most_max_age");
+            enforce_less_or_equal_gadget(&user_age_var, &max_age_var, self.num_bits)?;
+        }
 
         Ok(())
     }
 }
 
-// Generates the AgeVerificationCircuit with needed parameters
-pub fn calculate_age_verification_witness(user_age: u64, min_age: u64) -> AgeVerificationCircuit<Fr> {
+// Generates the AgeVerificationCircuit with needed parameters; the circuit is upper bounded when max_age is given
+pub fn calculate_age_verification_witness<F: PrimeField>(
+    num_bits: usize,
+    nonce: F,
+    user_age: u64,
+    min_age: u64,
+    max_age: Option<u64>,
+) -> AgeVerificationCircuit<F> {
     AgeVerificationCircuit {
-        user_age: Some(Fr::from(user_age)),  
-        min_age: Some(Fr::from(min_age)),  
+        num_bits,
+        upper_bounded: max_age.is_some(),
+        nonce: Some(nonce),
+        user_age: Some(F::from(user_age)),  
+        min_age: Some(F::from(min_age)),  
+        max_age: max_age.map(F::from),
+    }
+}
+
+/// Age threshold (or bracket) attribute, see [`AgeVerificationCircuit`]
+pub struct AgeVerification;
+
+/// Shape of the age circuit chosen at setup
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AgeConfig {
+    pub num_bits: usize,
+    pub upper_bounded: bool,
+}
+
+impl Default for AgeConfig {
+    fn default() -> Self {
+        Self { num_bits: DEFAULT_AGE_BITS, upper_bounded: false }
+    }
+}
+
+/// Public inputs of the age circuit; `max_age` must be given exactly when the circuit is upper bounded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgePublicInputs<F: PrimeField = Fr> {
+    pub nonce: F,
+    pub min_age: u64,
+    pub max_age: Option<u64>,
+}
+
+/// Private inputs of the age circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgePrivateInputs {
+    pub user_age: u64,
+}
+
+impl<F: PrimeField> AttributeCircuit<F> for AgeVerification {
+    const NAME: &'static str = "age_verification";
+    const VERSION: u32 = 1;
+
+    type Config = AgeConfig;
+    type PublicInputs = AgePublicInputs<F>;
+    type PrivateInputs = AgePrivateInputs;
+    type Circuit = AgeVerificationCircuit<F>;
+
+    fn check_config(config: &AgeConfig) -> Result<(), ZkpError> {
+        if config.num_bits == 0 || config.num_bits > MAX_RANGE_BITS {
+            return Err(ZkpError::InvalidInput(format!("ages must use 1 to {} bits", MAX_RANGE_BITS)));
+        }
+        Ok(())
+    }
+
+    fn sample_inputs(config: &AgeConfig) -> (AgePublicInputs<F>, AgePrivateInputs) {
+        let public = AgePublicInputs {
+            nonce: F::from(0u64),
+            min_age: 18,
+            max_age: config.upper_bounded.then_some(25),
+        };
+        (public, AgePrivateInputs { user_age: 20 })
+    }
+
+    fn check_inputs(config: &AgeConfig, public: &AgePublicInputs<F>, _: &AgePrivateInputs) -> Result<(), ZkpError> {
+        if config.upper_bounded != public.max_age.is_some() {
+            return Err(ZkpError::InvalidInput(
+                "max_age must be given exactly when the circuit is upper bounded".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn circuit(config: &AgeConfig, public: &AgePublicInputs<F>, private: &AgePrivateInputs) -> Self::Circuit {
+        AgeVerificationCircuit {
+            num_bits: config.num_bits,
+            upper_bounded: config.upper_bounded,
+            nonce: Some(public.nonce),
+            user_age: Some(F::from(private.user_age)),
+            min_age: Some(F::from(public.min_age)),
+            max_age: public.max_age.map(F::from),
+        }
+    }
+
+    fn public_inputs(public: &AgePublicInputs<F>) -> Vec<F> {
+        let mut inputs = vec![public.nonce, F::from(public.min_age)];
+        inputs.extend(public.max_age.map(F::from));
+        inputs
     }
 }
@@ -1,71 +1,225 @@
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, Namespace, SynthesisError};
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::prelude::*;
 use ark_bn254::Fr;
+use ark_ff::PrimeField;
 
-// A circuit simulating a “Merkle check” via a dummy Poseidon hash: hash(path, leaf) == merkle_root
-pub struct CitizenshipVerificationCircuit {
-    pub merkle_root: Option<Fr>,
-    pub path: Option<Fr>,
-    pub leaf: Option<Fr>,
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+use crate::nonce::nonce_input_gadget;
+use crate::poseidon::{PoseidonParameters, poseidon_hash, poseidon_hash_gadget};
+
+/// Tree depth used when a caller does not pick one (supports 2^20 registered citizens)
+pub const DEFAULT_TREE_DEPTH: usize = 20;
+
+/// Deepest tree a circuit can be set up for (2^32 registered citizens); the circuit grows linearly with the depth
+pub const MAX_TREE_DEPTH: usize = 32;
+
+/// Authentication path of a leaf in the citizen registry Merkle tree, ordered from the leaf level up
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath<F: PrimeField = Fr> {
+    /// Sibling hash at each level
+    pub siblings: Vec<F>,
+    /// Direction bit at each level: `true` when the current node is the right child
+    pub directions: Vec<bool>,
 }
 
-// A *dummy* poseidon_hash_gadget
-fn poseidon_hash_gadget(
-    inputs: Vec<FpVar<Fr>>,
-    _params_var: &FpVar<Fr>,
-) -> Result<FpVar<Fr>, SynthesisError> {
-    
-    // For demonstration, "hash" = sum(inputs).
-    let mut acc = FpVar::constant(Fr::from(0));
-    for inp in inputs {
-        acc += inp;
+impl<F: PrimeField> MerklePath<F> {
+    /// Number of levels covered by the path
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    /// Recomputes the root reached by hashing `leaf` up along this path
+    pub fn compute_root(&self, leaf: F) -> F {
+        let params = PoseidonParameters::<F>::shared(2);
+        self.siblings
+            .iter()
+            .zip(&self.directions)
+            .fold(leaf, |node, (sibling, is_right)| {
+                if *is_right {
+                    poseidon_hash(params, &[*sibling, node])
+                } else {
+                    poseidon_hash(params, &[node, *sibling])
+                }
+            })
     }
-    Ok(acc)
 }
 
-impl ConstraintSynthesizer<Fr> for CitizenshipVerificationCircuit {
-    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+// A circuit proving that a private leaf belongs to the Merkle tree with the public merkle_root.
+// The depth is fixed when the circuit is set up, so all paths proven against one key have the same length.
+pub struct CitizenshipVerificationCircuit<F: PrimeField = Fr> {
+    pub depth: usize,
+    pub nonce: Option<F>,
+    pub merkle_root: Option<F>,
+    pub leaf: Option<F>,
+    pub path: Option<MerklePath<F>>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for CitizenshipVerificationCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // A path of the wrong length can never match the circuit shape
+        if let Some(path) = &self.path
+            && (path.siblings.len() != self.depth || path.directions.len() != self.depth)
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        // Allocate the verifier's nonce first, so it is the first public input
+        nonce_input_gadget(ns!(cs, "nonce").cs(), self.nonce)?;
+
         // Allocate the root as a public input
-        let root_var = FpVar::new_input(cs.clone(), || {
+        let root_var = FpVar::new_input(ns!(cs, "merkle_root"), || {
             self.merkle_root.ok_or(SynthesisError::AssignmentMissing)
         })?;
 
-        // Allocate path as a private witness
-        let path_var = FpVar::new_witness(cs.clone(), || {
-            self.path.ok_or(SynthesisError::AssignmentMissing)
-        })?;
-
         // Allocate leaf as a private witness
-        let leaf_var = FpVar::new_witness(cs.clone(), || {
+        let leaf_var = FpVar::new_witness(ns!(cs, "leaf"), || {
             self.leaf.ok_or(SynthesisError::AssignmentMissing)
         })?;
 
-        // A dummy "Poseidon params" variable (constant in circuit). In a real circuit, you'd store real Poseidon parameters in the struct
-        let poseidon_params_var = FpVar::new_constant(cs.clone(), Fr::from(9999u64))?;
+        // An empty registry slot (leaf == 0) must never count as a registered citizen: leaf * inverse == 1 has no
+        // solution for a zero leaf. The inverse falls back to zero rather than going unassigned, so an empty leaf
+        // shows up as this constraint failing
+        {
+            let _ns = ns!(cs, "leaf_not_empty");
+            let inverse_var = FpVar::new_witness(cs.clone(), || Ok(leaf_var.value()?.inverse().unwrap_or_default()))?;
+            leaf_var.mul_equals(&inverse_var, &FpVar::one())?;
+        }
+
+        // Poseidon parameters for hashing two field elements, baked into the circuit as constants
+        let poseidon_params = PoseidonParameters::<F>::shared(2);
+
+        // Walk up the tree, hashing the current node with its sibling at every level
+        let mut node_var = leaf_var;
+        for level in 0..self.depth {
+            let _ns = level_namespace(&cs, level);
+            let sibling_var = FpVar::new_witness(cs.clone(), || {
+                self.path
+                    .as_ref()
+                    .map(|p| p.siblings[level])
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let is_right_var = Boolean::new_witness(cs.clone(), || {
+                self.path
+                    .as_ref()
+                    .map(|p| p.directions[level])
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?;
 
-        // "Hash" the path and leaf
-        let hash_var = poseidon_hash_gadget(
-            vec![path_var, leaf_var],
-            &poseidon_params_var,
-        )?;
+            // Order the pair according to the direction bit
+            let left_var = is_right_var.select(&sibling_var, &node_var)?;
+            let right_var = is_right_var.select(&node_var, &sibling_var)?;
 
-        // Enforce hash == root, which is our dummy merkle proof check
-        hash_var.enforce_equal(&root_var)?;
+            node_var = poseidon_hash_gadget(poseidon_params, &[left_var, right_var])?;
+        }
+
+        // Enforce the recomputed root == public root
+        let _ns = ns!(cs, "root_matches");
+        node_var.enforce_equal(&root_var)?;
 
         Ok(())
     }
 }
 
-/// Helper to build the circuit instance
-pub fn calculate_citizenship_verification_witness(
-    merkle_root: Option<Fr>,
-    path: Option<Fr>,
-    leaf: Option<Fr>,
-) -> CitizenshipVerificationCircuit {
+// Namespace of one tree level, named after its index (`merkle_level_3`) so a failing constraint points at the level.
+// Namespace names are static, so only levels below MAX_TREE_DEPTH get their own; deeper ones share `merkle_level`.
+fn level_namespace<F: PrimeField>(cs: &ConstraintSystemRef<F>, level: usize) -> Namespace<F> {
+    macro_rules! levels {
+        ($($i:literal)*) => {
+            match level {
+                $($i => ns!(cs, concat!("merkle_level_", $i)),)*
+                _ => ns!(cs, "merkle_level"),
+            }
+        };
+    }
+    levels!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31)
+}
+
+/// Helper to build the circuit instance for a tree of the given depth
+pub fn calculate_citizenship_verification_witness<F: PrimeField>(
+    depth: usize,
+    nonce: F,
+    merkle_root: F,
+    leaf: F,
+    path: MerklePath<F>,
+) -> CitizenshipVerificationCircuit<F> {
     CitizenshipVerificationCircuit {
-        merkle_root,
-        path,
-        leaf,
+        depth,
+        nonce: Some(nonce),
+        merkle_root: Some(merkle_root),
+        leaf: Some(leaf),
+        path: Some(path),
+    }
+}
+
+/// Citizenship attribute: membership of the registry tree, see [`CitizenshipVerificationCircuit`]
+pub struct CitizenshipVerification;
+
+/// Public inputs of the citizenship circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CitizenshipPublicInputs<F: PrimeField = Fr> {
+    pub nonce: F,
+    pub merkle_root: F,
+}
+
+/// Private inputs of the citizenship circuit: the hashed ID and its authentication path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CitizenshipPrivateInputs<F: PrimeField = Fr> {
+    pub leaf: F,
+    pub path: MerklePath<F>,
+}
+
+impl<F: PrimeField> AttributeCircuit<F> for CitizenshipVerification {
+    const NAME: &'static str = "citizenship_verification";
+    const VERSION: u32 = 1;
+
+    /// Depth of the registry tree
+    type Config = usize;
+    type PublicInputs = CitizenshipPublicInputs<F>;
+    type PrivateInputs = CitizenshipPrivateInputs<F>;
+    type Circuit = CitizenshipVerificationCircuit<F>;
+
+    fn check_config(depth: &usize) -> Result<(), ZkpError> {
+        if *depth > MAX_TREE_DEPTH {
+            return Err(ZkpError::InvalidInput(format!(
+                "tree depth {} exceeds the maximum of {}",
+                depth, MAX_TREE_DEPTH
+            )));
+        }
+        Ok(())
+    }
+
+    fn sample_inputs(depth: &usize) -> (CitizenshipPublicInputs<F>, CitizenshipPrivateInputs<F>) {
+        // A dummy leaf (hashed user ID) with a path of zeros
+        let leaf = F::from(98765u64);
+        let path = MerklePath {
+            siblings: vec![F::from(0u64); *depth],
+            directions: vec![false; *depth],
+        };
+        let public = CitizenshipPublicInputs { nonce: F::from(0u64), merkle_root: path.compute_root(leaf) };
+        (public, CitizenshipPrivateInputs { leaf, path })
+    }
+
+    fn check_inputs(depth: &usize, _: &CitizenshipPublicInputs<F>, private: &CitizenshipPrivateInputs<F>) -> Result<(), ZkpError> {
+        let path = &private.path;
+        if path.siblings.len() != *depth || path.directions.len() != *depth {
+            return Err(ZkpError::InvalidInput(format!(
+                "Merkle path has {} siblings and {} directions, expected {}",
+                path.siblings.len(),
+                path.directions.len(),
+                depth
+            )));
+        }
+        Ok(())
+    }
+
+    fn circuit(depth: &usize, public: &CitizenshipPublicInputs<F>, private: &CitizenshipPrivateInputs<F>) -> Self::Circuit {
+        calculate_citizenship_verification_witness(*depth, public.nonce, public.merkle_root, private.leaf, private.path.clone())
+    }
+
+    fn public_inputs(public: &CitizenshipPublicInputs<F>) -> Vec<F> {
+        vec![public.nonce, public.merkle_root]
     }
 }
@@ -1,51 +1,135 @@
-use ark_ff::PrimeField;
+use ark_relations::ns;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
 use ark_bn254::Fr;
 
-// This circuit is designed for college credential verification. In a real scenario, the university signs a hash (or commitment) 
-// of the user's credential using its private key. The circuit uses a signature verification gadget that takes as public input the 
-// university's public key (and possibly the user's public key) and as private witnesses the signed credential and signature. 
-// It then verifies that the signature is valid for the given credential, proving that the credential was issued by the university without 
-// revealing its sensitive details.
-pub struct CollegeCredentialVerificationCircuit<F: PrimeField> {
-    pub university_public_key: Option<F>,
-    pub credential: Option<F>,
-    pub signature: Option<F>
+use crate::attribute::AttributeCircuit;
+use crate::babyjubjub::Fs;
+use crate::eddsa::{PublicKey, PublicKeyVar, SecretKey, Signature, SignatureVar, enforce_signature_gadget};
+use crate::issuer::{credential_digest, credential_digest_gadget, holder_commitment, holder_commitment_gadget};
+use crate::nonce::nonce_input_gadget;
+
+// This circuit is designed for college credential verification. The university signs a digest of the user's credential
+// with its Baby Jubjub EdDSA key. The circuit takes the university's public key (a curve point) as public input and the
+// credential digest and signature as private witnesses, then verifies the signature in-circuit. This proves that the
+// credential was issued by the university without revealing its sensitive details.
+//
+// As with signed ages, the signed digest also covers a commitment to a secret only the holder knows
+// (`issuer::holder_commitment`), and the circuit recomputes it from the secret. A leaked or shared signature is
+// useless without the secret, so a proof shows the credential was issued to the prover.
+
+/// Digest a university signs to vouch for a holder's credential: the credential digest of
+/// (credential, holder commitment)
+pub fn college_credential_digest(credential: Fr, holder_commitment: Fr) -> Fr {
+    credential_digest(&[credential, holder_commitment])
+}
+
+pub struct CollegeCredentialVerificationCircuit {
+    pub nonce: Option<Fr>,
+    pub university_public_key: Option<PublicKey>,
+    pub credential: Option<Fr>,
+    pub holder_secret: Option<Fr>,
+    pub signature: Option<Signature>,
 }
 
-impl<F: PrimeField> ConstraintSynthesizer<F> for CollegeCredentialVerificationCircuit<F> {
-    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
-        // Allocate the public input: University Public Key
-        let public_key_var = FpVar::<F>::new_input(cs.clone(), || {
+impl ConstraintSynthesizer<Fr> for CollegeCredentialVerificationCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        // Allocate the verifier's nonce first, so it is the first public input
+        nonce_input_gadget(ns!(cs, "nonce").cs(), self.nonce)?;
+
+        // Allocate the public input: University Public Key (x and y coordinates)
+        let public_key_var = PublicKeyVar::new_input(ns!(cs, "university_public_key"), || {
             self.university_public_key.ok_or(SynthesisError::AssignmentMissing)
         })?;
-        
-        // Allocate the private witnesses: User's Credential and Signature
-        let credential_var = FpVar::<F>::new_witness(cs.clone(), || {
+
+        // Allocate the private witnesses: User's Credential, Holder Secret and Signature
+        let credential_var = FpVar::<Fr>::new_witness(ns!(cs, "credential"), || {
             self.credential.ok_or(SynthesisError::AssignmentMissing)
         })?;
-        let signature_var = FpVar::<F>::new_witness(cs.clone(), || {
+        let holder_secret_var = FpVar::<Fr>::new_witness(ns!(cs, "holder_secret"), || {
+            self.holder_secret.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let signature_var = SignatureVar::new_witness(ns!(cs, "signature"), || {
             self.signature.ok_or(SynthesisError::AssignmentMissing)
         })?;
-        
-        // Simulated digital signature check
-        let sum_var = &credential_var + &signature_var;
-        sum_var.enforce_equal(&public_key_var)?;
-        
+
+        // EdDSA signature check over the credential, issued to the holder who knows the committed secret
+        let _ns = ns!(cs, "signature_valid");
+        let commitment_var = holder_commitment_gadget(&holder_secret_var)?;
+        let digest_var = credential_digest_gadget(&[credential_var, commitment_var])?;
+        enforce_signature_gadget(&public_key_var, &digest_var, &signature_var)?;
+
         Ok(())
     }
 
 }
 
 pub fn calculate_college_credential_verification_witness(
-    university_public_key: Option<Fr>,
-    credential: Option<Fr>,
-    signature: Option<Fr>
-) -> CollegeCredentialVerificationCircuit<Fr> {
+    nonce: Fr,
+    university_public_key: PublicKey,
+    credential: Fr,
+    holder_secret: Fr,
+    signature: Signature,
+) -> CollegeCredentialVerificationCircuit {
     CollegeCredentialVerificationCircuit {
-        university_public_key,
-        credential,
-        signature,
+        nonce: Some(nonce),
+        university_public_key: Some(university_public_key),
+        credential: Some(credential),
+        holder_secret: Some(holder_secret),
+        signature: Some(signature),
+    }
+}
+
+/// College credential attribute: a credential signed by a university, see [`CollegeCredentialVerificationCircuit`]
+pub struct CollegeCredentialVerification;
+
+/// Public inputs of the college credential circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollegeCredentialPublicInputs {
+    pub nonce: Fr,
+    pub university_public_key: PublicKey,
+}
+
+/// Private inputs of the college credential circuit: the credential, the holder secret and the university's signature
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollegeCredentialPrivateInputs {
+    pub credential: Fr,
+    pub holder_secret: Fr,
+    pub signature: Signature,
+}
+
+impl AttributeCircuit for CollegeCredentialVerification {
+    const NAME: &'static str = "college_credential_verification";
+    const VERSION: u32 = 2;
+
+    type Config = ();
+    type PublicInputs = CollegeCredentialPublicInputs;
+    type PrivateInputs = CollegeCredentialPrivateInputs;
+    type Circuit = CollegeCredentialVerificationCircuit;
+
+    fn sample_inputs(_: &()) -> (CollegeCredentialPublicInputs, CollegeCredentialPrivateInputs) {
+        // A credential signed by a throwaway key, for a throwaway holder
+        let dummy_key = SecretKey(Fs::from(1u64));
+        let credential = Fr::from(18u64);
+        let holder_secret = Fr::from(1u64);
+        let public = CollegeCredentialPublicInputs { nonce: Fr::from(0u64), university_public_key: dummy_key.public_key() };
+        let signature = dummy_key.sign(college_credential_digest(credential, holder_commitment(holder_secret)));
+        (public, CollegeCredentialPrivateInputs { credential, holder_secret, signature })
+    }
+
+    fn circuit(_: &(), public: &CollegeCredentialPublicInputs, private: &CollegeCredentialPrivateInputs) -> Self::Circuit {
+        calculate_college_credential_verification_witness(
+            public.nonce,
+            public.university_public_key,
+            private.credential,
+            private.holder_secret,
+            private.signature,
+        )
     }
-}
\ No newline at end of file
+
+    fn public_inputs(public: &CollegeCredentialPublicInputs) -> Vec<Fr> {
+        let mut inputs = vec![public.nonce];
+        inputs.extend(public.university_public_key.to_public_inputs());
+        inputs
+    }
+}
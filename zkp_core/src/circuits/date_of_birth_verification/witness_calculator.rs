@@ -0,0 +1,234 @@
+use ark_ff::PrimeField;
+use ark_bn254::Fr;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, Namespace, SynthesisError};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use std::borrow::Borrow;
+
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+use crate::circuits::age_verification::witness_calculator::DEFAULT_AGE_BITS;
+use crate::nonce::nonce_input_gadget;
+use crate::range::{enforce_in_range_gadget, enforce_less_or_equal_gadget};
+
+// This circuit proves that a person born on a private date of birth is at least min_age years old on a public
+// reference date, without revealing the birth date. Dates are compared lexicographically as (year, month, day):
+// the person is old enough when (birth_year + min_age, birth_month, birth_day) <= (year, month, day). Someone born
+// on February 29 therefore comes of age on March 1 in non-leap years.
+
+/// Number of bits of a year; years up to 65535 are supported
+pub const YEAR_BITS: usize = 16;
+
+// Months and days are packed below the year as year * 2^9 + month * 2^5 + day, which orders dates lexicographically
+const MONTH_BITS: usize = 4;
+const DAY_BITS: usize = 5;
+
+// A shifted year (year + min_age) fits in YEAR_BITS + 1 bits, followed by the packed month and day
+const DATE_KEY_BITS: usize = YEAR_BITS + 1 + MONTH_BITS + DAY_BITS;
+
+/// Calendar date in the proleptic Gregorian calendar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Builds a date, rejecting months and days that do not exist (e.g. 2026-02-29)
+    pub fn new(year: u32, month: u32, day: u32) -> Option<Self> {
+        if year >= 1 << YEAR_BITS || !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    /// Field elements exposed as public inputs when this date is the reference date: year, month, day
+    pub fn to_public_inputs<F: PrimeField>(&self) -> Vec<F> {
+        vec![F::from(self.year), F::from(self.month), F::from(self.day)]
+    }
+}
+
+fn is_leap_year(year: u32) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Rejects dates that [`Date::new`] would not build
+pub fn check_date(date: &Date) -> Result<(), ZkpError> {
+    Date::new(date.year, date.month, date.day)
+        .map(|_| ())
+        .ok_or_else(|| ZkpError::InvalidInput(format!("{}-{:02}-{:02} is not a valid date", date.year, date.month, date.day)))
+}
+
+/// In-circuit date
+#[derive(Clone)]
+pub struct DateVar<F: PrimeField> {
+    pub year: FpVar<F>,
+    pub month: FpVar<F>,
+    pub day: FpVar<F>,
+}
+
+impl<F: PrimeField> AllocVar<Date, F> for DateVar<F> {
+    fn new_variable<T: Borrow<Date>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        // Evaluate the assignment once; it is missing while generating keys
+        let value = f().map(|date| *date.borrow());
+
+        let year = FpVar::new_variable(cs.clone(), || value.map(|d| F::from(d.year)), mode)?;
+        let month = FpVar::new_variable(cs.clone(), || value.map(|d| F::from(d.month)), mode)?;
+        let day = FpVar::new_variable(cs.clone(), || value.map(|d| F::from(d.day)), mode)?;
+
+        Ok(Self { year, month, day })
+    }
+}
+
+impl<F: PrimeField> DateVar<F> {
+    /// Enforces that the month lies in 1..=12 and the day in 1..=31, and packs the date shifted by
+    /// `years` into a single comparable value
+    fn key(&self, years: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+        let one = FpVar::one();
+
+        enforce_in_range_gadget(&self.year, YEAR_BITS)?;
+
+        // 1 <= month <= 12
+        enforce_in_range_gadget(&(&self.month - &one), MONTH_BITS)?;
+        enforce_less_or_equal_gadget(&self.month, &FpVar::constant(F::from(12u64)), MONTH_BITS)?;
+
+        // 1 <= day <= 31
+        enforce_in_range_gadget(&(&self.day - &one), DAY_BITS)?;
+        enforce_less_or_equal_gadget(&self.day, &FpVar::constant(F::from(31u64)), DAY_BITS)?;
+
+        let year_shift = FpVar::constant(F::from(1u64 << (MONTH_BITS + DAY_BITS)));
+        let month_shift = FpVar::constant(F::from(1u64 << DAY_BITS));
+        Ok((&self.year + years) * year_shift + &self.month * month_shift + &self.day)
+    }
+}
+
+/// Enforces that someone born on `birth_date` is at least `min_age` years old on `current_date`
+pub fn enforce_age_on_date_gadget<F: PrimeField>(
+    birth_date: &DateVar<F>,
+    current_date: &DateVar<F>,
+    min_age: &FpVar<F>,
+) -> Result<(), SynthesisError> {
+    enforce_in_range_gadget(min_age, DEFAULT_AGE_BITS)?;
+
+    // (birth_year + min_age, birth_month, birth_day) <= (year, month, day)
+    let coming_of_age = birth_date.key(min_age)?;
+    let current = current_date.key(&FpVar::zero())?;
+    enforce_less_or_equal_gadget(&coming_of_age, &current, DATE_KEY_BITS)
+}
+
+// Define the circuit for proving that the holder of a private date of birth is at least min_age on current_date
+pub struct DateOfBirthVerificationCircuit<F: PrimeField> {
+    pub nonce: Option<F>,
+    pub birth_date: Option<Date>,
+    pub current_date: Option<Date>,
+    pub min_age: Option<F>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for DateOfBirthVerificationCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // Allocate the verifier's nonce first, so it is the first public input
+        nonce_input_gadget(ns!(cs, "nonce").cs(), self.nonce)?;
+
+        // Allocate the public inputs: current date (year, month, day) followed by the age threshold
+        let current_date_var = DateVar::new_input(ns!(cs, "current_date"), || {
+            self.current_date.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let min_age_var = FpVar::<F>::new_input(ns!(cs, "min_age"), || {
+            self.min_age.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Allocate the private witness: date of birth
+        let birth_date_var = DateVar::new_witness(ns!(cs, "birth_date"), || {
+            self.birth_date.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let _ns = ns!(cs, "old_enough_on_current_date");
+        enforce_age_on_date_gadget(&birth_date_var, &current_date_var, &min_age_var)?;
+
+        Ok(())
+    }
+}
+
+// Generates the DateOfBirthVerificationCircuit with needed parameters
+pub fn calculate_date_of_birth_verification_witness<F: PrimeField>(
+    nonce: F,
+    birth_date: Date,
+    current_date: Date,
+    min_age: u64,
+) -> DateOfBirthVerificationCircuit<F> {
+    DateOfBirthVerificationCircuit {
+        nonce: Some(nonce),
+        birth_date: Some(birth_date),
+        current_date: Some(current_date),
+        min_age: Some(F::from(min_age)),
+    }
+}
+
+/// Date of birth attribute: being at least `min_age` on a reference date, see [`DateOfBirthVerificationCircuit`]
+pub struct DateOfBirthVerification;
+
+/// Public inputs of the date of birth circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DateOfBirthPublicInputs<F: PrimeField = Fr> {
+    pub nonce: F,
+    pub current_date: Date,
+    pub min_age: u64,
+}
+
+/// Private inputs of the date of birth circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DateOfBirthPrivateInputs {
+    pub birth_date: Date,
+}
+
+impl<F: PrimeField> AttributeCircuit<F> for DateOfBirthVerification {
+    const NAME: &'static str = "date_of_birth_verification";
+    const VERSION: u32 = 1;
+
+    type Config = ();
+    type PublicInputs = DateOfBirthPublicInputs<F>;
+    type PrivateInputs = DateOfBirthPrivateInputs;
+    type Circuit = DateOfBirthVerificationCircuit<F>;
+
+    fn sample_inputs(_: &()) -> (DateOfBirthPublicInputs<F>, DateOfBirthPrivateInputs) {
+        let public = DateOfBirthPublicInputs {
+            nonce: F::from(0u64),
+            current_date: Date { year: 2020, month: 1, day: 1 },
+            min_age: 18,
+        };
+        (public, DateOfBirthPrivateInputs { birth_date: Date { year: 2000, month: 1, day: 1 } })
+    }
+
+    fn check_inputs(_: &(), public: &DateOfBirthPublicInputs<F>, private: &DateOfBirthPrivateInputs) -> Result<(), ZkpError> {
+        check_date(&public.current_date)?;
+        check_date(&private.birth_date)
+    }
+
+    fn circuit(_: &(), public: &DateOfBirthPublicInputs<F>, private: &DateOfBirthPrivateInputs) -> Self::Circuit {
+        calculate_date_of_birth_verification_witness(public.nonce, private.birth_date, public.current_date, public.min_age)
+    }
+
+    fn public_inputs(public: &DateOfBirthPublicInputs<F>) -> Vec<F> {
+        let mut inputs = vec![public.nonce];
+        inputs.extend(public.current_date.to_public_inputs::<F>());
+        inputs.push(F::from(public.min_age));
+        inputs
+    }
+}
@@ -0,0 +1,181 @@
+use ark_bn254::Fr;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+
+use crate::attribute::AttributeCircuit;
+use crate::babyjubjub::Fs;
+use crate::circuits::date_of_birth_verification::witness_calculator::{Date, DateVar, check_date, enforce_age_on_date_gadget};
+use crate::eddsa::{PublicKey, PublicKeyVar, SecretKey, Signature, SignatureVar, enforce_signature_gadget};
+use crate::error::ZkpError;
+use crate::issuer::{credential_digest, credential_digest_gadget, holder_commitment, holder_commitment_gadget};
+use crate::nonce::nonce_input_gadget;
+
+// This circuit combines the date of birth check with an issuer signature. An issuer (e.g. a registry office) signs
+// the digest of the holder's date of birth, and the circuit proves both that the signature is valid under the
+// issuer's public key and that the signed date of birth makes the holder at least min_age on current_date. The
+// date of birth and the signature stay private, so the verifier only learns that a trusted issuer vouched for an
+// old enough holder.
+//
+// The signed digest also covers a commitment to a secret only the holder knows (`issuer::holder_commitment`), and
+// the circuit recomputes that commitment from the secret. A signature that leaks, or that the holder hands out, is
+// therefore useless to anyone without the secret: it vouches for one holder, not for whoever presents it.
+
+/// Digest an issuer signs to vouch for a holder's date of birth: the credential digest of
+/// (year, month, day, holder commitment)
+pub fn birth_date_credential_digest(birth_date: &Date, holder_commitment: Fr) -> Fr {
+    let mut attributes = birth_date.to_public_inputs();
+    attributes.push(holder_commitment);
+    credential_digest(&attributes)
+}
+
+// Define the circuit for proving an issuer-signed date of birth meets the age threshold on current_date
+pub struct SignedAgeVerificationCircuit {
+    pub nonce: Option<Fr>,
+    pub issuer_public_key: Option<PublicKey>,
+    pub current_date: Option<Date>,
+    pub min_age: Option<Fr>,
+    pub birth_date: Option<Date>,
+    pub holder_secret: Option<Fr>,
+    pub signature: Option<Signature>,
+}
+
+impl ConstraintSynthesizer<Fr> for SignedAgeVerificationCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        // Allocate the verifier's nonce first, so it is the first public input
+        nonce_input_gadget(ns!(cs, "nonce").cs(), self.nonce)?;
+
+        // Allocate the public inputs: issuer public key (x, y), current date (year, month, day) and the age threshold
+        let public_key_var = PublicKeyVar::new_input(ns!(cs, "issuer_public_key"), || {
+            self.issuer_public_key.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let current_date_var = DateVar::new_input(ns!(cs, "current_date"), || {
+            self.current_date.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let min_age_var = FpVar::<Fr>::new_input(ns!(cs, "min_age"), || {
+            self.min_age.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Allocate the private witnesses: date of birth, holder secret and the issuer's signature over them
+        let birth_date_var = DateVar::new_witness(ns!(cs, "birth_date"), || {
+            self.birth_date.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let holder_secret_var = FpVar::<Fr>::new_witness(ns!(cs, "holder_secret"), || {
+            self.holder_secret.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let signature_var = SignatureVar::new_witness(ns!(cs, "signature"), || {
+            self.signature.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // The issuer signed exactly this date of birth, for the holder who knows the committed secret
+        {
+            let _ns = ns!(cs, "signature_valid");
+            let commitment_var = holder_commitment_gadget(&holder_secret_var)?;
+            let digest_var = credential_digest_gadget(&[
+                birth_date_var.year.clone(),
+                birth_date_var.month.clone(),
+                birth_date_var.day.clone(),
+                commitment_var,
+            ])?;
+            enforce_signature_gadget(&public_key_var, &digest_var, &signature_var)?;
+        }
+
+        // And the signed date of birth meets the threshold
+        let _ns = ns!(cs, "old_enough_on_current_date");
+        enforce_age_on_date_gadget(&birth_date_var, &current_date_var, &min_age_var)?;
+
+        Ok(())
+    }
+}
+
+// Generates the SignedAgeVerificationCircuit with needed parameters
+pub fn calculate_signed_age_verification_witness(
+    nonce: Fr,
+    issuer_public_key: PublicKey,
+    birth_date: Date,
+    holder_secret: Fr,
+    signature: Signature,
+    current_date: Date,
+    min_age: u64,
+) -> SignedAgeVerificationCircuit {
+    SignedAgeVerificationCircuit {
+        nonce: Some(nonce),
+        issuer_public_key: Some(issuer_public_key),
+        current_date: Some(current_date),
+        min_age: Some(Fr::from(min_age)),
+        birth_date: Some(birth_date),
+        holder_secret: Some(holder_secret),
+        signature: Some(signature),
+    }
+}
+
+/// Issuer-signed age attribute, see [`SignedAgeVerificationCircuit`]
+pub struct SignedAgeVerification;
+
+/// Public inputs of the signed age circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedAgePublicInputs {
+    pub nonce: Fr,
+    pub issuer_public_key: PublicKey,
+    pub current_date: Date,
+    pub min_age: u64,
+}
+
+/// Private inputs of the signed age circuit: the date of birth, the holder secret and the issuer's signature
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedAgePrivateInputs {
+    pub birth_date: Date,
+    pub holder_secret: Fr,
+    pub signature: Signature,
+}
+
+impl AttributeCircuit for SignedAgeVerification {
+    const NAME: &'static str = "signed_age_verification";
+    const VERSION: u32 = 2;
+
+    type Config = ();
+    type PublicInputs = SignedAgePublicInputs;
+    type PrivateInputs = SignedAgePrivateInputs;
+    type Circuit = SignedAgeVerificationCircuit;
+
+    fn sample_inputs(_: &()) -> (SignedAgePublicInputs, SignedAgePrivateInputs) {
+        // A date of birth signed by a throwaway key, for a throwaway holder
+        let dummy_key = SecretKey(Fs::from(1u64));
+        let birth_date = Date { year: 2000, month: 1, day: 1 };
+        let holder_secret = Fr::from(1u64);
+        let public = SignedAgePublicInputs {
+            nonce: Fr::from(0u64),
+            issuer_public_key: dummy_key.public_key(),
+            current_date: Date { year: 2020, month: 1, day: 1 },
+            min_age: 18,
+        };
+        let signature = dummy_key.sign(birth_date_credential_digest(&birth_date, holder_commitment(holder_secret)));
+        (public, SignedAgePrivateInputs { birth_date, holder_secret, signature })
+    }
+
+    fn check_inputs(_: &(), public: &SignedAgePublicInputs, private: &SignedAgePrivateInputs) -> Result<(), ZkpError> {
+        check_date(&public.current_date)?;
+        check_date(&private.birth_date)
+    }
+
+    fn circuit(_: &(), public: &SignedAgePublicInputs, private: &SignedAgePrivateInputs) -> Self::Circuit {
+        calculate_signed_age_verification_witness(
+            public.nonce,
+            public.issuer_public_key,
+            private.birth_date,
+            private.holder_secret,
+            private.signature,
+            public.current_date,
+            public.min_age,
+        )
+    }
+
+    fn public_inputs(public: &SignedAgePublicInputs) -> Vec<Fr> {
+        // Allocation order: nonce, key coordinates, current date, threshold
+        let mut inputs = vec![public.nonce];
+        inputs.extend(public.issuer_public_key.to_public_inputs());
+        inputs.extend(public.current_date.to_public_inputs::<Fr>());
+        inputs.push(Fr::from(public.min_age));
+        inputs
+    }
+}
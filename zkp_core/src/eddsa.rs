@@ -0,0 +1,209 @@
+use ark_bn254::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{Namespace, SynthesisError};
+use sha2::{Digest, Sha512};
+use std::borrow::Borrow;
+use std::fmt;
+
+use crate::babyjubjub::{EdwardsAffine, EdwardsVar, Fs};
+use crate::poseidon::{PoseidonParameters, poseidon_hash, poseidon_hash_gadget};
+
+// EdDSA over Baby Jubjub with Poseidon as the message hash.
+//
+// A signature on a field element `m` under the public key `A = sk * G` is a pair `(R, s)` such that
+// `s * G == R + h * A`, where `h = Poseidon(R.x, R.y, A.x, A.y, m)`. Both the native verifier and the
+// gadget multiply `A` by the full integer `h`, so they accept exactly the same signatures. Both also require
+// `s < l`, the subgroup order: `s + l` would satisfy the same equation, making every signature malleable.
+//
+// Signing is deterministic, as in RFC 8032: the nonce is SHA-512 of the secret key and the message, reduced mod l.
+// Reducing 512 bits leaves no measurable bias, whereas a single field element reduced mod l would favour small
+// nonces, and biased nonces leak the key.
+
+// Domain separation for the nonce hash
+const NONCE_DOMAIN: &[u8] = b"zkp_core/eddsa/nonce";
+
+/// Number of bits used to represent the scalar `s` of a signature
+pub const SCALAR_BITS: usize = Fs::MODULUS_BIT_SIZE as usize;
+
+/// Secret signing key, a scalar of the Baby Jubjub prime-order subgroup
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SecretKey(pub Fs);
+
+// Keys end up in logs through `{:?}` on anything holding them, so the scalar is never printed
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(<redacted>)")
+    }
+}
+
+/// Public verification key, a point of the Baby Jubjub prime-order subgroup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicKey(pub EdwardsAffine);
+
+/// EdDSA signature `(R, s)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub r: EdwardsAffine,
+    pub s: Fs,
+}
+
+// Reduces a BN254 scalar field element modulo the subgroup order
+fn base_to_scalar(value: &Fr) -> Fs {
+    Fs::from_le_bytes_mod_order(&value.into_bigint().to_bytes_le())
+}
+
+/// Computes the challenge `h = Poseidon(R.x, R.y, A.x, A.y, message)`
+pub fn challenge_hash(r: &EdwardsAffine, public_key: &PublicKey, message: Fr) -> Fr {
+    let params = PoseidonParameters::<Fr>::shared(5);
+    poseidon_hash(params, &[r.x, r.y, public_key.0.x, public_key.0.y, message])
+}
+
+impl SecretKey {
+    /// Derives the matching public key `A = sk * G`
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey((EdwardsAffine::generator() * self.0).into_affine())
+    }
+
+    /// Signs a field element. The nonce is derived deterministically from the key and the message
+    pub fn sign(&self, message: Fr) -> Signature {
+        let public_key = self.public_key();
+
+        // r = SHA-512(domain || sk || message) mod l, so a nonce is never reused across different messages
+        let digest = Sha512::new()
+            .chain_update(NONCE_DOMAIN)
+            .chain_update(self.0.into_bigint().to_bytes_le())
+            .chain_update(message.into_bigint().to_bytes_le())
+            .finalize();
+        let nonce = Fs::from_le_bytes_mod_order(&digest);
+        let r = (EdwardsAffine::generator() * nonce).into_affine();
+
+        // s = r + h * sk mod l
+        let h = base_to_scalar(&challenge_hash(&r, &public_key, message));
+        Signature { r, s: nonce + h * self.0 }
+    }
+}
+
+impl PublicKey {
+    /// Builds a public key from affine coordinates, rejecting points outside the prime-order subgroup
+    pub fn from_coordinates(x: Fr, y: Fr) -> Option<Self> {
+        let point = EdwardsAffine::new_unchecked(x, y);
+        if point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+            Some(Self(point))
+        } else {
+            None
+        }
+    }
+
+    /// Field elements exposed as public inputs of circuits verifying signatures under this key
+    pub fn to_public_inputs(&self) -> Vec<Fr> {
+        vec![self.0.x, self.0.y]
+    }
+
+    /// Verifies a signature natively, accepting exactly what [`enforce_signature_gadget`] accepts
+    pub fn verify(&self, message: Fr, signature: &Signature) -> bool {
+        if !signature.r.is_on_curve() || !signature.r.is_in_correct_subgroup_assuming_on_curve() {
+            return false;
+        }
+
+        // `s` is an `Fs`, so it is always below l, the bound the gadget enforces on its bits
+        let h = challenge_hash(&signature.r, self, message);
+        let lhs = EdwardsAffine::generator().mul_bigint(signature.s.into_bigint());
+        let rhs = self.0.mul_bigint(h.into_bigint()) + signature.r;
+        lhs == rhs
+    }
+}
+
+/// In-circuit public key
+#[derive(Clone)]
+pub struct PublicKeyVar {
+    pub point: EdwardsVar,
+}
+
+/// In-circuit signature, with the scalar kept as little-endian bits
+#[derive(Clone)]
+pub struct SignatureVar {
+    pub r: EdwardsVar,
+    pub s_bits: Vec<Boolean<Fr>>,
+}
+
+impl PublicKeyVar {
+    /// Coordinates of the key, in the same order as [`PublicKey::to_public_inputs`]
+    pub fn coordinates(&self) -> Vec<FpVar<Fr>> {
+        vec![self.point.x.clone(), self.point.y.clone()]
+    }
+}
+
+impl AllocVar<PublicKey, Fr> for PublicKeyVar {
+    fn new_variable<T: Borrow<PublicKey>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let point = EdwardsVar::new_variable(cs, || f().map(|pk| pk.borrow().0), mode)?;
+        Ok(Self { point })
+    }
+}
+
+impl AllocVar<Signature, Fr> for SignatureVar {
+    fn new_variable<T: Borrow<Signature>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        // Evaluate the assignment once; it is missing while generating keys
+        let value = f().map(|sig| *sig.borrow());
+
+        // Witness allocation checks that R lies in the prime-order subgroup
+        let r = EdwardsVar::new_variable(cs.clone(), || value.map(|sig| sig.r), mode)?;
+
+        let s_bits = match value {
+            Ok(sig) => sig.s.into_bigint().to_bits_le()[..SCALAR_BITS]
+                .iter()
+                .map(|bit| Boolean::new_variable(cs.clone(), || Ok(*bit), mode))
+                .collect::<Result<Vec<_>, _>>()?,
+            Err(_) => (0..SCALAR_BITS)
+                .map(|_| Boolean::new_variable(cs.clone(), || Err::<bool, _>(SynthesisError::AssignmentMissing), mode))
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        Ok(Self { r, s_bits })
+    }
+}
+
+/// Enforces that `signature` is a valid signature on `message` under `public_key`
+pub fn enforce_signature_gadget(
+    public_key: &PublicKeyVar,
+    message: &FpVar<Fr>,
+    signature: &SignatureVar,
+) -> Result<(), SynthesisError> {
+    // h = Poseidon(R.x, R.y, A.x, A.y, message)
+    let params = PoseidonParameters::<Fr>::shared(5);
+    let h = poseidon_hash_gadget(
+        params,
+        &[
+            signature.r.x.clone(),
+            signature.r.y.clone(),
+            public_key.point.x.clone(),
+            public_key.point.y.clone(),
+            message.clone(),
+        ],
+    )?;
+    let h_bits = h.to_bits_le()?;
+
+    // s < l, so each signature has a single encoding
+    Boolean::enforce_smaller_or_equal_than_le(&signature.s_bits, (-Fs::from(1u64)).into_bigint())?;
+
+    // s * G, with the generator baked in as a constant
+    let generator = EdwardsVar::constant(EdwardsAffine::generator().into_group());
+    let lhs = generator.scalar_mul_le(signature.s_bits.iter())?;
+
+    // R + h * A
+    let rhs = public_key.point.scalar_mul_le(h_bits.iter())? + &signature.r;
+
+    lhs.enforce_equal(&rhs)
+}
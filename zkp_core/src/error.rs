@@ -0,0 +1,74 @@
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::SerializationError;
+use std::fmt;
+
+/// Errors returned by the proof system
+#[derive(Debug)]
+pub enum ZkpError {
+    /// The inputs do not satisfy the circuit; names the first failing constraint when it is known
+    UnsatisfiedConstraint { constraint: Option<String> },
+    /// An input is malformed or does not fit the circuit (e.g. a Merkle path of the wrong length)
+    InvalidInput(String),
+    /// The key was generated for a different circuit, or circuit shape, than the one being used
+    KeyCircuitMismatch(String),
+    /// A key, proof or input could not be encoded or decoded
+    Serialization(SerializationError),
+    /// A key or proof file is truncated, corrupted, or holds a different kind of object than expected
+    InvalidFile(String),
+    /// Generating the proving and verifying keys failed
+    Setup(SynthesisError),
+    /// A setup ceremony transcript does not check out at the given contribution
+    InvalidContribution { index: usize, reason: String },
+    /// Any other failure while synthesizing, proving or verifying
+    Synthesis(SynthesisError),
+}
+
+impl fmt::Display for ZkpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkpError::UnsatisfiedConstraint { constraint: Some(name) } => {
+                write!(f, "inputs do not satisfy the circuit (constraint `{}`)", name)
+            }
+            ZkpError::UnsatisfiedConstraint { constraint: None } => write!(f, "inputs do not satisfy the circuit"),
+            ZkpError::InvalidInput(reason) => write!(f, "invalid input: {}", reason),
+            ZkpError::KeyCircuitMismatch(reason) => write!(f, "key does not match the circuit: {}", reason),
+            ZkpError::Serialization(e) => write!(f, "serialization error: {}", e),
+            ZkpError::InvalidFile(reason) => write!(f, "invalid key or proof file: {}", reason),
+            ZkpError::Setup(e) => write!(f, "setup failed: {}", e),
+            ZkpError::InvalidContribution { index, reason } => {
+                write!(f, "ceremony contribution {} is invalid: {}", index, reason)
+            }
+            ZkpError::Synthesis(e) => write!(f, "synthesis error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ZkpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ZkpError::Serialization(e) => Some(e),
+            ZkpError::Setup(e) | ZkpError::Synthesis(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<SynthesisError> for ZkpError {
+    fn from(e: SynthesisError) -> Self {
+        match e {
+            SynthesisError::Unsatisfiable => ZkpError::UnsatisfiedConstraint { constraint: None },
+            SynthesisError::AssignmentMissing => ZkpError::InvalidInput("an input was not assigned".to_string()),
+            // Groth16 reports a public input count that differs from the key's as a malformed key
+            SynthesisError::MalformedVerifyingKey => {
+                ZkpError::KeyCircuitMismatch("the number of public inputs differs from the key's".to_string())
+            }
+            e => ZkpError::Synthesis(e),
+        }
+    }
+}
+
+impl From<SerializationError> for ZkpError {
+    fn from(e: SerializationError) -> Self {
+        ZkpError::Serialization(e)
+    }
+}
@@ -0,0 +1,88 @@
+use ark_ec::AffineRepr;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_serialize::{CanonicalSerialize, Compress};
+
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+use crate::key_file::HEADER_LEN;
+use crate::keys::{CircuitFingerprint, Curve, circuit_matrices, fingerprint_matrices};
+
+// Circuit introspection. The circuit is synthesized in setup mode, exactly as the Groth16 setup and the circuit
+// fingerprint do, and its R1CS dimensions are reported together with the size of the keys a setup would produce.
+// Key sizes are derived from the dimensions rather than by running a setup, which takes seconds for the larger
+// circuits: a Groth16 key holds a fixed number of points plus one point per variable, constraint or public input.
+
+// Length prefix `CanonicalSerialize` writes before every vector
+const VEC_LEN_PREFIX: usize = 8;
+
+/// Size in bytes of an object in both point encodings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SerializedSize {
+    pub compressed: usize,
+    pub uncompressed: usize,
+}
+
+/// Dimensions of an attribute circuit of a given shape and the sizes of its Groth16 keys and proofs
+///
+/// Sizes are those of the [key file](crate::key_file) encoding, header included.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitInfo {
+    pub name: &'static str,
+    pub version: u32,
+    pub curve: &'static str,
+    pub fingerprint: CircuitFingerprint,
+    pub num_constraints: usize,
+    /// Private variables, including those the gadgets allocate internally
+    pub num_witness_variables: usize,
+    /// Public inputs a verifier passes, not counting the constant one
+    pub num_public_inputs: usize,
+    pub proving_key_size: SerializedSize,
+    pub verifying_key_size: SerializedSize,
+    pub proof_size: SerializedSize,
+}
+
+/// Synthesizes circuit `C` of the given shape on curve `E` and reports its dimensions and key sizes
+pub fn inspect<E: Curve, C: AttributeCircuit<E::ScalarField>>(config: &C::Config) -> Result<CircuitInfo, ZkpError> {
+    let matrices = circuit_matrices::<E::ScalarField, C>(config)?;
+    let num_instance = matrices.num_instance_variables;
+    let num_witness = matrices.num_witness_variables;
+
+    // The setup evaluates the QAP over a domain covering every constraint plus one per instance variable
+    let domain = GeneralEvaluationDomain::<E::ScalarField>::new(matrices.num_constraints + num_instance)
+        .ok_or_else(|| ZkpError::InvalidInput("the circuit is too large for the scalar field".to_string()))?;
+
+    let size = |compress| {
+        let g1 = E::G1Affine::generator().serialized_size(compress);
+        let g2 = E::G2Affine::generator().serialized_size(compress);
+        let fingerprint = CircuitFingerprint([0; 32]).serialized_size(compress);
+
+        // alpha_g1, beta_g2, gamma_g2, delta_g2, gamma_abc_g1
+        let vk = g1 + 3 * g2 + VEC_LEN_PREFIX + num_instance * g1;
+        // vk, beta_g1, delta_g1, a_query, b_g1_query, b_g2_query, h_query, l_query
+        let pk = vk
+            + 2 * g1
+            + 5 * VEC_LEN_PREFIX
+            + (num_instance + num_witness) * (2 * g1 + g2)
+            + (domain.size() - 1) * g1
+            + num_witness * g1;
+        // a, b, c
+        let proof = 2 * g1 + g2;
+
+        (HEADER_LEN + fingerprint + pk, HEADER_LEN + fingerprint + vk, HEADER_LEN + proof)
+    };
+    let (pk_compressed, vk_compressed, proof_compressed) = size(Compress::Yes);
+    let (pk_uncompressed, vk_uncompressed, proof_uncompressed) = size(Compress::No);
+
+    Ok(CircuitInfo {
+        name: C::NAME,
+        version: C::VERSION,
+        curve: E::NAME,
+        fingerprint: fingerprint_matrices::<E::ScalarField, C>(&matrices),
+        num_constraints: matrices.num_constraints,
+        num_witness_variables: num_witness,
+        num_public_inputs: num_instance - 1,
+        proving_key_size: SerializedSize { compressed: pk_compressed, uncompressed: pk_uncompressed },
+        verifying_key_size: SerializedSize { compressed: vk_compressed, uncompressed: vk_uncompressed },
+        proof_size: SerializedSize { compressed: proof_compressed, uncompressed: proof_uncompressed },
+    })
+}
@@ -0,0 +1,168 @@
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use rand::{CryptoRng, RngCore};
+use std::fmt;
+
+use crate::babyjubjub::{EdwardsAffine, Fs};
+use crate::eddsa::{PublicKey, SecretKey, Signature};
+use crate::poseidon::{PoseidonParameters, poseidon_hash, poseidon_hash_gadget};
+
+/// Length in bytes of an encoded secret key
+pub const SECRET_KEY_BYTES: usize = 32;
+
+/// Length in bytes of an encoded public key (compressed curve point)
+pub const PUBLIC_KEY_BYTES: usize = 32;
+
+/// Length in bytes of an encoded signature (compressed `R` followed by `s`)
+pub const SIGNATURE_BYTES: usize = 64;
+
+/// Signing keys of a credential issuer (e.g. a university or a registry office)
+///
+/// Signatures use the same Baby Jubjub EdDSA scheme that the credential circuits verify, so anything
+/// signed here can be proven in zero knowledge by the credential holder.
+#[derive(Clone, PartialEq, Eq)]
+pub struct IssuerKeypair {
+    secret_key: SecretKey,
+    public_key: PublicKey,
+}
+
+// Only the public half is printed
+impl fmt::Debug for IssuerKeypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IssuerKeypair").field("public_key", &self.public_key).finish_non_exhaustive()
+    }
+}
+
+impl IssuerKeypair {
+    /// Generates a fresh keypair from a cryptographically secure RNG
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self::from_secret_key(SecretKey(Fs::rand(rng)))
+    }
+
+    /// Rebuilds a keypair from its secret key
+    pub fn from_secret_key(secret_key: SecretKey) -> Self {
+        Self {
+            public_key: secret_key.public_key(),
+            secret_key,
+        }
+    }
+
+    /// Secret half of the keypair; keep it inside the issuance service
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.secret_key
+    }
+
+    /// Public half of the keypair, given to verifiers as the circuit's public input
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Signs a credential digest (see [`credential_digest`])
+    pub fn sign_credential(&self, digest: Fr) -> Signature {
+        self.secret_key.sign(digest)
+    }
+
+    /// Encodes the secret key for storage
+    pub fn to_bytes(&self) -> Vec<u8> {
+        secret_key_to_bytes(&self.secret_key)
+    }
+
+    /// Decodes a keypair produced by [`IssuerKeypair::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        secret_key_from_bytes(bytes).map(Self::from_secret_key)
+    }
+}
+
+/// Hashes the attributes of a credential into the single field element an issuer signs
+///
+/// The attributes are absorbed one at a time, starting from their count, so credentials with different
+/// numbers of attributes never share a digest.
+pub fn credential_digest(attributes: &[Fr]) -> Fr {
+    let params = PoseidonParameters::<Fr>::shared(2);
+    attributes
+        .iter()
+        .fold(Fr::from(attributes.len() as u64), |acc, attribute| {
+            poseidon_hash(params, &[acc, *attribute])
+        })
+}
+
+/// In-circuit counterpart of [`credential_digest`]
+pub fn credential_digest_gadget(attributes: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+    let params = PoseidonParameters::<Fr>::shared(2);
+    attributes
+        .iter()
+        .try_fold(FpVar::constant(Fr::from(attributes.len() as u64)), |acc, attribute| {
+            poseidon_hash_gadget(params, &[acc, attribute.clone()])
+        })
+}
+
+/// Commitment to a secret only the credential holder knows, signed by the issuer along with the attributes
+///
+/// Circuits recompute it from the secret, so a signed credential only proves anything for its holder.
+pub fn holder_commitment(holder_secret: Fr) -> Fr {
+    poseidon_hash(PoseidonParameters::shared(1), &[holder_secret])
+}
+
+/// In-circuit counterpart of [`holder_commitment`]
+pub fn holder_commitment_gadget(holder_secret: &FpVar<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+    poseidon_hash_gadget(PoseidonParameters::shared(1), std::slice::from_ref(holder_secret))
+}
+
+/// Verifies an issuer's signature on a credential digest off-circuit
+pub fn verify_credential(public_key: &PublicKey, digest: Fr, signature: &Signature) -> bool {
+    public_key.verify(digest, signature)
+}
+
+/// Encodes a secret key as 32 little-endian bytes
+pub fn secret_key_to_bytes(secret_key: &SecretKey) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(SECRET_KEY_BYTES);
+    secret_key.0.serialize_compressed(&mut bytes).expect("writing to a Vec cannot fail");
+    bytes
+}
+
+/// Decodes a secret key, rejecting values outside the scalar field
+pub fn secret_key_from_bytes(bytes: &[u8]) -> Result<SecretKey, SerializationError> {
+    check_length(bytes, SECRET_KEY_BYTES)?;
+    Ok(SecretKey(Fs::deserialize_compressed(bytes)?))
+}
+
+/// Encodes a public key as a 32-byte compressed point
+pub fn public_key_to_bytes(public_key: &PublicKey) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(PUBLIC_KEY_BYTES);
+    public_key.0.serialize_compressed(&mut bytes).expect("writing to a Vec cannot fail");
+    bytes
+}
+
+/// Decodes a public key, rejecting points off the curve or outside the prime-order subgroup
+pub fn public_key_from_bytes(bytes: &[u8]) -> Result<PublicKey, SerializationError> {
+    check_length(bytes, PUBLIC_KEY_BYTES)?;
+    Ok(PublicKey(EdwardsAffine::deserialize_compressed(bytes)?))
+}
+
+/// Encodes a signature as the compressed point `R` followed by the scalar `s`
+pub fn signature_to_bytes(signature: &Signature) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(SIGNATURE_BYTES);
+    signature.r.serialize_compressed(&mut bytes).expect("writing to a Vec cannot fail");
+    signature.s.serialize_compressed(&mut bytes).expect("writing to a Vec cannot fail");
+    bytes
+}
+
+/// Decodes a signature produced by [`signature_to_bytes`]
+pub fn signature_from_bytes(bytes: &[u8]) -> Result<Signature, SerializationError> {
+    check_length(bytes, SIGNATURE_BYTES)?;
+    let (r_bytes, s_bytes) = bytes.split_at(PUBLIC_KEY_BYTES);
+    Ok(Signature {
+        r: EdwardsAffine::deserialize_compressed(r_bytes)?,
+        s: Fs::deserialize_compressed(s_bytes)?,
+    })
+}
+
+fn check_length(bytes: &[u8], expected: usize) -> Result<(), SerializationError> {
+    if bytes.len() != expected {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(())
+}
@@ -0,0 +1,457 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_groth16::Proof;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Validate};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+use crate::error::ZkpError;
+use crate::keys::{CircuitFingerprint, CircuitProvingKey, CircuitVerifyingKey, Curve};
+#[cfg(feature = "experimental-marlin")]
+use crate::marlin::{MarlinProof, MarlinProvingKey, MarlinVerifyingKey};
+
+// Container format for proving keys, verifying keys and proofs. Every file is a fixed 80 byte header followed by
+// the `CanonicalSerialize` encoding of the object (all integers little-endian):
+//
+//   offset  size  field
+//        0     4  magic, "ZKPA"
+//        4     2  format version, currently 1
+//        6     1  kind: 1 = proving key, 2 = verifying key, 3 = proof
+//        7     1  curve: 1 = BN254, 2 = BLS12-381
+//        8    32  fingerprint of the circuit the object belongs to
+//       40     1  compression: 0 = uncompressed points, 1 = compressed points
+//       41     1  proving system: 0 = Groth16, 1 = Marlin (BN254 only, with the `experimental-marlin` feature)
+//       42     6  reserved, zero
+//       48    32  SHA-256 digest of the payload
+//       80     -  payload
+//
+// Keys repeat their fingerprint in the payload and it must agree with the header. A reader rejects any other
+// version or kind, a curve or proving system other than the one it asked for, a payload whose digest does not
+// match, and trailing bytes. Readers that accept either proving system look at `proving_system` first.
+//
+// The `*_auto` decoders are meant for data from untrusted clients. Besides containers they accept the bare
+// `CanonicalSerialize` encoding in either compressed or uncompressed form, detecting which one was sent. Every
+// decoder here validates points, so a point off the curve or outside the prime order subgroup is rejected.
+
+/// First bytes of every file
+pub const MAGIC: [u8; 4] = *b"ZKPA";
+/// Version written by this crate, the only one it reads
+pub const FORMAT_VERSION: u16 = 1;
+/// Size of the header preceding the payload
+pub const HEADER_LEN: usize = 80;
+
+fn curve_name(id: u8) -> Option<&'static str> {
+    [(Bn254::ID, Bn254::NAME), (Bls12_381::ID, Bls12_381::NAME)]
+        .into_iter()
+        .find_map(|(known, name)| (known == id).then_some(name))
+}
+
+/// Kind of object stored in a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    ProvingKey = 1,
+    VerifyingKey = 2,
+    Proof = 3,
+}
+
+impl Kind {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Kind::ProvingKey),
+            2 => Some(Kind::VerifyingKey),
+            3 => Some(Kind::Proof),
+            _ => None,
+        }
+    }
+}
+
+/// Proving system an object belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingSystemId {
+    Groth16 = 0,
+    Marlin = 1,
+}
+
+impl ProvingSystemId {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(ProvingSystemId::Groth16),
+            1 => Some(ProvingSystemId::Marlin),
+            _ => None,
+        }
+    }
+}
+
+/// Decoded file header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: u16,
+    pub kind: Kind,
+    /// [`Curve::ID`] of the curve the object lives on
+    pub curve: u8,
+    pub fingerprint: CircuitFingerprint,
+    /// Whether curve points in the payload are compressed
+    pub compressed: bool,
+    pub proving_system: ProvingSystemId,
+    pub digest: [u8; 32],
+}
+
+impl Header {
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[0..4].copy_from_slice(&MAGIC);
+        bytes[4..6].copy_from_slice(&self.version.to_le_bytes());
+        bytes[6] = self.kind as u8;
+        bytes[7] = self.curve;
+        bytes[8..40].copy_from_slice(&self.fingerprint.0);
+        bytes[40] = self.compressed as u8;
+        bytes[41] = self.proving_system as u8;
+        bytes[48..80].copy_from_slice(&self.digest);
+        bytes
+    }
+
+    /// Parses and checks the header at the start of `bytes`
+    pub fn parse(bytes: &[u8]) -> Result<Self, ZkpError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(invalid(format!("file is truncated: {} bytes is shorter than the header", bytes.len())));
+        }
+        if bytes[0..4] != MAGIC {
+            return Err(invalid("not a key or proof file (bad magic bytes)"));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != FORMAT_VERSION {
+            return Err(invalid(format!("unsupported format version {} (expected {})", version, FORMAT_VERSION)));
+        }
+        let kind = Kind::from_byte(bytes[6]).ok_or_else(|| invalid(format!("unknown object kind {}", bytes[6])))?;
+        let curve = bytes[7];
+        if curve_name(curve).is_none() {
+            return Err(invalid(format!("unsupported curve {}", curve)));
+        }
+        let compressed = match bytes[40] {
+            0 => false,
+            1 => true,
+            flag => return Err(invalid(format!("unknown compression flag {}", flag))),
+        };
+        let proving_system = ProvingSystemId::from_byte(bytes[41])
+            .ok_or_else(|| invalid(format!("unsupported proving system {}", bytes[41])))?;
+        if bytes[42..48].iter().any(|b| *b != 0) {
+            return Err(invalid("reserved header bytes are not zero"));
+        }
+
+        let mut fingerprint = [0u8; 32];
+        fingerprint.copy_from_slice(&bytes[8..40]);
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&bytes[48..80]);
+        Ok(Header {
+            version,
+            kind,
+            curve,
+            fingerprint: CircuitFingerprint(fingerprint),
+            compressed,
+            proving_system,
+            digest,
+        })
+    }
+}
+
+/// Encodes a proving key into the container format
+pub fn encode_proving_key<E: Curve>(pk: &CircuitProvingKey<E>, compress: Compress) -> Result<Vec<u8>, ZkpError> {
+    encode::<E, _>(ProvingSystemId::Groth16, Kind::ProvingKey, pk.fingerprint, pk, compress)
+}
+
+/// Decodes a proving key, checking the header, digest and every curve point
+pub fn decode_proving_key<E: Curve>(bytes: &[u8]) -> Result<CircuitProvingKey<E>, ZkpError> {
+    let (header, pk): (_, CircuitProvingKey<E>) = decode::<E, _>(ProvingSystemId::Groth16, Kind::ProvingKey, bytes)?;
+    check_key_fingerprint(&header, &pk.fingerprint)?;
+    Ok(pk)
+}
+
+/// Encodes a verifying key into the container format
+pub fn encode_verifying_key<E: Curve>(vk: &CircuitVerifyingKey<E>, compress: Compress) -> Result<Vec<u8>, ZkpError> {
+    encode::<E, _>(ProvingSystemId::Groth16, Kind::VerifyingKey, vk.fingerprint, vk, compress)
+}
+
+/// Decodes a verifying key, checking the header, digest and every curve point
+pub fn decode_verifying_key<E: Curve>(bytes: &[u8]) -> Result<CircuitVerifyingKey<E>, ZkpError> {
+    let (header, vk): (_, CircuitVerifyingKey<E>) =
+        decode::<E, _>(ProvingSystemId::Groth16, Kind::VerifyingKey, bytes)?;
+    check_key_fingerprint(&header, &vk.fingerprint)?;
+    Ok(vk)
+}
+
+/// Encodes a proof, labelled with the fingerprint of the circuit it was generated for
+pub fn encode_proof<E: Curve>(
+    proof: &Proof<E>,
+    fingerprint: CircuitFingerprint,
+    compress: Compress,
+) -> Result<Vec<u8>, ZkpError> {
+    encode::<E, _>(ProvingSystemId::Groth16, Kind::Proof, fingerprint, proof, compress)
+}
+
+/// Decodes a proof and the fingerprint of the circuit it claims to belong to
+pub fn decode_proof<E: Curve>(bytes: &[u8]) -> Result<(CircuitFingerprint, Proof<E>), ZkpError> {
+    let (header, proof) = decode::<E, _>(ProvingSystemId::Groth16, Kind::Proof, bytes)?;
+    Ok((header.fingerprint, proof))
+}
+
+/// Writes a proving key file
+pub fn save_proving_key<E: Curve>(path: impl AsRef<Path>, pk: &CircuitProvingKey<E>, compress: Compress) -> Result<(), ZkpError> {
+    write(path, &encode_proving_key(pk, compress)?)
+}
+
+/// Reads a proving key file
+pub fn load_proving_key<E: Curve>(path: impl AsRef<Path>) -> Result<CircuitProvingKey<E>, ZkpError> {
+    decode_proving_key(&read(path)?)
+}
+
+/// Writes a verifying key file
+pub fn save_verifying_key<E: Curve>(
+    path: impl AsRef<Path>,
+    vk: &CircuitVerifyingKey<E>,
+    compress: Compress,
+) -> Result<(), ZkpError> {
+    write(path, &encode_verifying_key(vk, compress)?)
+}
+
+/// Reads a verifying key file
+pub fn load_verifying_key<E: Curve>(path: impl AsRef<Path>) -> Result<CircuitVerifyingKey<E>, ZkpError> {
+    decode_verifying_key(&read(path)?)
+}
+
+/// Writes a proof file
+pub fn save_proof<E: Curve>(
+    path: impl AsRef<Path>,
+    proof: &Proof<E>,
+    fingerprint: CircuitFingerprint,
+    compress: Compress,
+) -> Result<(), ZkpError> {
+    write(path, &encode_proof(proof, fingerprint, compress)?)
+}
+
+/// Reads a proof file
+pub fn load_proof<E: Curve>(path: impl AsRef<Path>) -> Result<(CircuitFingerprint, Proof<E>), ZkpError> {
+    decode_proof(&read(path)?)
+}
+
+/// Encodes a Marlin proving key into the container format
+#[cfg(feature = "experimental-marlin")]
+pub fn encode_marlin_proving_key(pk: &MarlinProvingKey, compress: Compress) -> Result<Vec<u8>, ZkpError> {
+    encode::<Bn254, _>(ProvingSystemId::Marlin, Kind::ProvingKey, pk.vk.fingerprint, pk, compress)
+}
+
+/// Decodes a Marlin proving key, checking the header, digest and every curve point
+#[cfg(feature = "experimental-marlin")]
+pub fn decode_marlin_proving_key(bytes: &[u8]) -> Result<MarlinProvingKey, ZkpError> {
+    let (header, pk): (_, MarlinProvingKey) = decode::<Bn254, _>(ProvingSystemId::Marlin, Kind::ProvingKey, bytes)?;
+    check_key_fingerprint(&header, &pk.vk.fingerprint)?;
+    Ok(pk)
+}
+
+/// Encodes a Marlin verifying key into the container format
+#[cfg(feature = "experimental-marlin")]
+pub fn encode_marlin_verifying_key(vk: &MarlinVerifyingKey, compress: Compress) -> Result<Vec<u8>, ZkpError> {
+    encode::<Bn254, _>(ProvingSystemId::Marlin, Kind::VerifyingKey, vk.fingerprint, vk, compress)
+}
+
+/// Decodes a Marlin verifying key, checking the header, digest and every curve point
+#[cfg(feature = "experimental-marlin")]
+pub fn decode_marlin_verifying_key(bytes: &[u8]) -> Result<MarlinVerifyingKey, ZkpError> {
+    let (header, vk): (_, MarlinVerifyingKey) =
+        decode::<Bn254, _>(ProvingSystemId::Marlin, Kind::VerifyingKey, bytes)?;
+    check_key_fingerprint(&header, &vk.fingerprint)?;
+    Ok(vk)
+}
+
+/// Encodes a Marlin proof, labelled with the fingerprint of the circuit it was generated for
+#[cfg(feature = "experimental-marlin")]
+pub fn encode_marlin_proof(
+    proof: &MarlinProof,
+    fingerprint: CircuitFingerprint,
+    compress: Compress,
+) -> Result<Vec<u8>, ZkpError> {
+    encode::<Bn254, _>(ProvingSystemId::Marlin, Kind::Proof, fingerprint, proof, compress)
+}
+
+/// Decodes a Marlin proof and the fingerprint of the circuit it claims to belong to
+#[cfg(feature = "experimental-marlin")]
+pub fn decode_marlin_proof(bytes: &[u8]) -> Result<(CircuitFingerprint, MarlinProof), ZkpError> {
+    let (header, proof) = decode::<Bn254, _>(ProvingSystemId::Marlin, Kind::Proof, bytes)?;
+    Ok((header.fingerprint, proof))
+}
+
+/// Writes a Marlin proving key file
+#[cfg(feature = "experimental-marlin")]
+pub fn save_marlin_proving_key(
+    path: impl AsRef<Path>,
+    pk: &MarlinProvingKey,
+    compress: Compress,
+) -> Result<(), ZkpError> {
+    write(path, &encode_marlin_proving_key(pk, compress)?)
+}
+
+/// Reads a Marlin proving key file
+#[cfg(feature = "experimental-marlin")]
+pub fn load_marlin_proving_key(path: impl AsRef<Path>) -> Result<MarlinProvingKey, ZkpError> {
+    decode_marlin_proving_key(&read(path)?)
+}
+
+/// Writes a Marlin verifying key file
+#[cfg(feature = "experimental-marlin")]
+pub fn save_marlin_verifying_key(
+    path: impl AsRef<Path>,
+    vk: &MarlinVerifyingKey,
+    compress: Compress,
+) -> Result<(), ZkpError> {
+    write(path, &encode_marlin_verifying_key(vk, compress)?)
+}
+
+/// Reads a Marlin verifying key file
+#[cfg(feature = "experimental-marlin")]
+pub fn load_marlin_verifying_key(path: impl AsRef<Path>) -> Result<MarlinVerifyingKey, ZkpError> {
+    decode_marlin_verifying_key(&read(path)?)
+}
+
+/// Proving system of a container, from byte 41 of its header
+///
+/// Bare encodings carry no header and are taken to be Groth16, the only system the `*_auto` decoders accept.
+pub fn proving_system(bytes: &[u8]) -> Result<ProvingSystemId, ZkpError> {
+    if !is_container(bytes) {
+        return Ok(ProvingSystemId::Groth16);
+    }
+    Ok(Header::parse(bytes)?.proving_system)
+}
+
+/// Decodes a proving key sent as a container or as a bare compressed or uncompressed encoding
+pub fn decode_proving_key_auto<E: Curve>(bytes: &[u8]) -> Result<CircuitProvingKey<E>, ZkpError> {
+    if is_container(bytes) {
+        return decode_proving_key(bytes);
+    }
+    decode_bare(bytes)
+}
+
+/// Decodes a verifying key sent as a container or as a bare compressed or uncompressed encoding
+pub fn decode_verifying_key_auto<E: Curve>(bytes: &[u8]) -> Result<CircuitVerifyingKey<E>, ZkpError> {
+    if is_container(bytes) {
+        return decode_verifying_key(bytes);
+    }
+    decode_bare(bytes)
+}
+
+/// Decodes a proof sent as a container or as a bare compressed or uncompressed encoding
+///
+/// Only containers say which circuit the proof belongs to; the fingerprint is `None` for bare encodings.
+pub fn decode_proof_auto<E: Curve>(bytes: &[u8]) -> Result<(Option<CircuitFingerprint>, Proof<E>), ZkpError> {
+    if is_container(bytes) {
+        let (fingerprint, proof) = decode_proof(bytes)?;
+        return Ok((Some(fingerprint), proof));
+    }
+    Ok((None, decode_bare(bytes)?))
+}
+
+/// Whether `bytes` start like a container rather than a bare encoding
+pub fn is_container(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+fn encode<E: Curve, T: CanonicalSerialize>(
+    proving_system: ProvingSystemId,
+    kind: Kind,
+    fingerprint: CircuitFingerprint,
+    value: &T,
+    compress: Compress,
+) -> Result<Vec<u8>, ZkpError> {
+    let mut payload = Vec::with_capacity(value.serialized_size(compress));
+    value.serialize_with_mode(&mut payload, compress)?;
+
+    let header = Header {
+        version: FORMAT_VERSION,
+        kind,
+        curve: E::ID,
+        fingerprint,
+        compressed: matches!(compress, Compress::Yes),
+        proving_system,
+        digest: Sha256::digest(&payload).into(),
+    };
+    let mut bytes = header.to_bytes().to_vec();
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+fn decode<E: Curve, T: CanonicalDeserialize>(
+    proving_system: ProvingSystemId,
+    expected: Kind,
+    bytes: &[u8],
+) -> Result<(Header, T), ZkpError> {
+    let header = Header::parse(bytes)?;
+    if header.kind != expected {
+        return Err(invalid(format!("expected a {:?} file but found a {:?}", expected, header.kind)));
+    }
+    if header.proving_system != proving_system {
+        return Err(invalid(format!(
+            "expected a {:?} {:?} but the file is for {:?}",
+            proving_system, expected, header.proving_system
+        )));
+    }
+    if header.curve != E::ID {
+        return Err(invalid(format!(
+            "expected an object on {} but the file is for {}",
+            E::NAME,
+            curve_name(header.curve).unwrap_or("an unknown curve")
+        )));
+    }
+
+    let payload = &bytes[HEADER_LEN..];
+    let digest: [u8; 32] = Sha256::digest(payload).into();
+    if digest != header.digest {
+        return Err(invalid("payload digest does not match the header; the file is truncated or corrupted"));
+    }
+
+    let mut reader = payload;
+    let compress = if header.compressed { Compress::Yes } else { Compress::No };
+    let value = T::deserialize_with_mode(&mut reader, compress, Validate::Yes)?;
+    if !reader.is_empty() {
+        return Err(invalid(format!("{} unexpected bytes after the payload", reader.len())));
+    }
+    Ok((header, value))
+}
+
+// The two encodings of an object differ in length, so at most one of them consumes the input exactly
+fn decode_bare<T: CanonicalDeserialize>(bytes: &[u8]) -> Result<T, ZkpError> {
+    let attempt = |compress| -> Result<T, SerializationError> {
+        let mut reader = bytes;
+        let value = T::deserialize_with_mode(&mut reader, compress, Validate::Yes)?;
+        if !reader.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(value)
+    };
+
+    attempt(Compress::Yes).or_else(|compressed_error| {
+        attempt(Compress::No).map_err(|uncompressed_error| {
+            invalid(format!(
+                "not a valid compressed ({}) or uncompressed ({}) encoding",
+                compressed_error, uncompressed_error
+            ))
+        })
+    })
+}
+
+fn check_key_fingerprint(header: &Header, fingerprint: &CircuitFingerprint) -> Result<(), ZkpError> {
+    if header.fingerprint != *fingerprint {
+        return Err(invalid("the header fingerprint does not match the key's"));
+    }
+    Ok(())
+}
+
+fn read(path: impl AsRef<Path>) -> Result<Vec<u8>, ZkpError> {
+    fs::read(path).map_err(|e| ZkpError::Serialization(SerializationError::IoError(e)))
+}
+
+fn write(path: impl AsRef<Path>, bytes: &[u8]) -> Result<(), ZkpError> {
+    fs::write(path, bytes).map_err(|e| ZkpError::Serialization(SerializationError::IoError(e)))
+}
+
+fn invalid(reason: impl Into<String>) -> ZkpError {
+    ZkpError::InvalidFile(reason.into())
+}
@@ -0,0 +1,178 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_relations::r1cs::{ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha2::{Digest, Sha256};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::{Mutex, OnceLock};
+
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+
+// Groth16 keys are only meaningful for the exact constraint system they were generated from, but nothing in the
+// keys themselves says which one that was. Keys produced here carry a fingerprint of the circuit: a SHA-256 hash
+// of its name, version and R1CS matrices. Proving and verifying recompute the fingerprint of the circuit in use
+// and refuse keys that carry a different one, instead of producing a proof that can never verify or a bare `false`.
+// The scalar field's modulus is hashed too, so the same circuit gets a different fingerprint on each curve.
+//
+// Fingerprinting synthesizes the whole circuit, which would otherwise happen on every proof and verification.
+// Results are cached per circuit name, version, field and shape; `check_config` bounds the shapes a circuit
+// accepts, so the cache stays small.
+
+/// A pairing-friendly curve attribute circuits can be set up on
+///
+/// `ID` is the byte identifying the curve in key files; it must never change once keys have been written.
+pub trait Curve: Pairing {
+    const ID: u8;
+    const NAME: &'static str;
+}
+
+impl Curve for Bn254 {
+    const ID: u8 = 1;
+    const NAME: &'static str = "bn254";
+}
+
+impl Curve for Bls12_381 {
+    const ID: u8 = 2;
+    const NAME: &'static str = "bls12-381";
+}
+
+/// SHA-256 fingerprint identifying a circuit, its version and its shape
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CircuitFingerprint(pub [u8; 32]);
+
+impl fmt::Display for CircuitFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Groth16 proving key tagged with the fingerprint of the circuit it was generated for
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CircuitProvingKey<E: Pairing = Bn254> {
+    pub fingerprint: CircuitFingerprint,
+    pub key: ProvingKey<E>,
+}
+
+impl<E: Pairing> CircuitProvingKey<E> {
+    /// The matching verifying key, carrying the same fingerprint
+    pub fn verifying_key(&self) -> CircuitVerifyingKey<E> {
+        CircuitVerifyingKey {
+            fingerprint: self.fingerprint,
+            key: self.key.vk.clone(),
+        }
+    }
+}
+
+/// Groth16 verifying key tagged with the fingerprint of the circuit it was generated for
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CircuitVerifyingKey<E: Pairing = Bn254> {
+    pub fingerprint: CircuitFingerprint,
+    pub key: VerifyingKey<E>,
+}
+
+/// Computes the fingerprint of an attribute circuit of the given shape over the field `F`
+///
+/// The circuit is synthesized in setup mode, so the result only depends on its constraints, never on input values.
+pub fn circuit_fingerprint<F: PrimeField, C: AttributeCircuit<F>>(
+    config: &C::Config,
+) -> Result<CircuitFingerprint, ZkpError> {
+    let key = (C::NAME, C::VERSION, TypeId::of::<F>(), TypeId::of::<C::Config>());
+    if let Some(fingerprint) = cached_fingerprints::<C::Config, _>(key, |shapes| shapes.get(config).copied()) {
+        return Ok(fingerprint);
+    }
+
+    let fingerprint = fingerprint_matrices::<F, C>(&circuit_matrices::<F, C>(config)?);
+    cached_fingerprints::<C::Config, _>(key, |shapes| shapes.insert(config.clone(), fingerprint));
+    Ok(fingerprint)
+}
+
+// (circuit name, version, scalar field, config type) -> fingerprint of every shape computed so far. The shape maps
+// are type-erased so circuits with different config types share one cache; the key pins down the config type.
+type FingerprintKey = (&'static str, u32, TypeId, TypeId);
+type FingerprintCache = HashMap<FingerprintKey, Box<dyn Any + Send + Sync>>;
+
+fn cached_fingerprints<K: Eq + Hash + Send + Sync + 'static, T>(
+    key: FingerprintKey,
+    f: impl FnOnce(&mut HashMap<K, CircuitFingerprint>) -> T,
+) -> T {
+    static CACHE: OnceLock<Mutex<FingerprintCache>> = OnceLock::new();
+    let mut cache = CACHE.get_or_init(Default::default).lock().expect("fingerprint cache lock poisoned");
+    let shapes = cache
+        .entry(key)
+        .or_insert_with(|| Box::new(HashMap::<K, CircuitFingerprint>::new()))
+        .downcast_mut::<HashMap<K, CircuitFingerprint>>()
+        .expect("the cache key includes the config type");
+    f(shapes)
+}
+
+// Synthesizes the circuit the same way the Groth16 setup does, so the matrices match the ones keys are made from
+pub(crate) fn circuit_matrices<F: PrimeField, C: AttributeCircuit<F>>(
+    config: &C::Config,
+) -> Result<ConstraintMatrices<F>, ZkpError> {
+    C::check_config(config)?;
+    let (public, private) = C::sample_inputs(config);
+    C::check_inputs(config, &public, &private)?;
+
+    let cs = ConstraintSystem::<F>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    C::circuit(config, &public, &private).generate_constraints(cs.clone())?;
+    cs.finalize();
+    cs.to_matrices()
+        .ok_or_else(|| ZkpError::InvalidInput("constraint matrices are unavailable".to_string()))
+}
+
+pub(crate) fn fingerprint_matrices<F: PrimeField, C: AttributeCircuit<F>>(
+    matrices: &ConstraintMatrices<F>,
+) -> CircuitFingerprint {
+    let mut hasher = Sha256::new();
+    hasher.update(b"zkp-circuit-fingerprint");
+    hasher.update((C::NAME.len() as u64).to_le_bytes());
+    hasher.update(C::NAME.as_bytes());
+    hasher.update(C::VERSION.to_le_bytes());
+    let modulus = F::MODULUS.to_bytes_le();
+    hasher.update((modulus.len() as u64).to_le_bytes());
+    hasher.update(modulus);
+    for count in [matrices.num_instance_variables, matrices.num_witness_variables, matrices.num_constraints] {
+        hasher.update((count as u64).to_le_bytes());
+    }
+    for matrix in [&matrices.a, &matrices.b, &matrices.c] {
+        for row in matrix {
+            hasher.update((row.len() as u64).to_le_bytes());
+            for (coefficient, index) in row {
+                hasher.update(coefficient.into_bigint().to_bytes_le());
+                hasher.update((*index as u64).to_le_bytes());
+            }
+        }
+    }
+
+    CircuitFingerprint(hasher.finalize().into())
+}
+
+/// Fails with [`ZkpError::KeyCircuitMismatch`] unless `fingerprint` belongs to the circuit of the given shape
+pub fn check_fingerprint<F: PrimeField, C: AttributeCircuit<F>>(
+    fingerprint: &CircuitFingerprint,
+    config: &C::Config,
+) -> Result<(), ZkpError> {
+    let expected = circuit_fingerprint::<F, C>(config)?;
+    if *fingerprint != expected {
+        return Err(ZkpError::KeyCircuitMismatch(format!(
+            "the key was generated for circuit {} but `{}` v{} is {}",
+            fingerprint,
+            C::NAME,
+            C::VERSION,
+            expected
+        )));
+    }
+    Ok(())
+}
@@ -1,4 +1,26 @@
+pub mod attribute;
+pub mod babyjubjub;
+pub mod batch;
+pub mod ceremony;
+pub mod eddsa;
+pub mod error;
+pub mod inspect;
+pub mod issuer;
+pub mod key_file;
+pub mod keys;
+#[cfg(feature = "experimental-marlin")]
+pub mod marlin;
+pub mod merkle;
+pub mod nonce;
+pub mod poseidon;
+pub mod powers_of_tau;
 pub mod proof_system;
+pub mod proving_system;
+pub mod range;
+pub mod satisfiability;
+pub mod snarkjs;
+pub mod solidity;
+pub mod verifier;
 
 pub mod circuits {
     pub mod age_verification {
@@ -10,4 +32,10 @@ pub mod circuits {
     pub mod college_credential_verification {
         pub mod witness_calculator;
     }
+    pub mod date_of_birth_verification {
+        pub mod witness_calculator;
+    }
+    pub mod signed_age_verification {
+        pub mod witness_calculator;
+    }
 }
@@ -0,0 +1,788 @@
+use ark_bn254::{Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, UniformRand, Zero, batch_inversion};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain};
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisError,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress};
+use rand::{CryptoRng, RngCore, thread_rng};
+use sha2::{Digest, Sha512};
+use std::array;
+use std::iter;
+
+use crate::attribute::AttributeCircuit;
+use crate::ceremony::same_ratio;
+use crate::error::ZkpError;
+use crate::inspect::SerializedSize;
+use crate::key_file::HEADER_LEN;
+use crate::keys::{CircuitFingerprint, check_fingerprint, circuit_matrices, fingerprint_matrices};
+use crate::powers_of_tau::PowersOfTau;
+use crate::satisfiability::first_unsatisfied;
+
+// Marlin, a zkSNARK with a universal setup, over BN254.
+//
+// Groth16 keys embed secrets specific to one circuit, so every circuit and shape needs its own phase-2 ceremony.
+// Marlin only needs the powers of a secret tau that `powers_of_tau` produces: keys for any circuit that fits are
+// derived from the accumulator without further secrets ("indexing"), so a new circuit or Merkle depth needs no new
+// ceremony. Proofs are about 2 kB instead of 128 bytes, and verifying costs four pairings and some field arithmetic.
+//
+// The constraint system Az * Bz = Cz is laid out over a multiplicative subgroup H, with the instance variables on
+// the subgroup X of H, and the nonzero matrix entries are described by `row`, `col` and `val` polynomials over a
+// subgroup K, plus `row_col` = `row * col` on K so the third sumcheck stays of low degree. The verifying key commits
+// to those twelve polynomials. A proof runs three sumchecks:
+//
+//   1. over H: s + r(alpha, X) * (eta_A z_A + eta_B z_B + eta_C z_A z_B) - t * z sums to the mask's sum, which
+//      holds when z_A * z_B = z_C and t is the eta-combination of the matrices at row alpha
+//   2. over H: t(beta_1) is r(alpha, X) times the eta-combination of the matrices at column beta_1
+//   3. over K: that combination at (beta_2, beta_1) is what `row`, `col` and `val` say it is
+//
+// Every polynomial is committed with KZG and opened at the challenge points in three batches. The `g` polynomials
+// of the sumchecks must have degree below the domain size; each is committed a second time shifted up to the
+// largest degree the powers of tau reach, which is only possible for polynomials within the bound. Challenges come
+// from a SHA-512 Fiat-Shamir transcript seeded with the verifying key and the public inputs. The witness
+// polynomials carry random multiples of the vanishing polynomial of H, which hide them from their commitments and
+// evaluations without changing them on H.
+//
+// This is not a maintained library and it has not been audited, so the module is only built with the
+// `experimental-marlin` feature. Its tests replay checked-in vectors, so any change to the transcript, the
+// polynomial layout or the encoding shows up as a failure rather than as silently different proofs.
+
+type Domain = Radix2EvaluationDomain<Fr>;
+
+// Degree of the random multiple of v_H added to each witness polynomial
+const MASK_DEGREE: usize = 2;
+
+const TRANSCRIPT_DOMAIN: &[u8] = b"zkp-marlin";
+
+/// Verifying key of a circuit: commitments to its matrices and the sizes needed to interpret them
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MarlinVerifyingKey {
+    pub fingerprint: CircuitFingerprint,
+    /// Public inputs the circuit takes, not counting the constant one
+    pub num_public_inputs: u64,
+    /// Size of the domain H of constraints and variables
+    pub constraint_domain_size: u64,
+    /// Size of the domain K of nonzero matrix entries
+    pub matrix_domain_size: u64,
+    /// Highest degree the powers of tau the key was indexed from can commit to
+    pub max_degree: u64,
+    /// Commitments to `row`, `col`, `row_col` and `val` of A, B and C, in that order
+    pub index: [G1Affine; 12],
+    /// `tau` in G2
+    pub tau_g2: G2Affine,
+}
+
+/// Proving key: the verifying key and the powers of tau to commit with
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MarlinProvingKey {
+    pub vk: MarlinVerifyingKey,
+    /// `tau^i` in G1 for `i <= max_degree`
+    pub powers: Vec<G1Affine>,
+}
+
+impl MarlinProvingKey {
+    /// Verifying key matching this proving key
+    pub fn verifying_key(&self) -> MarlinVerifyingKey {
+        self.vk.clone()
+    }
+}
+
+/// Marlin proof: the prover's commitments round by round, the evaluations it opens them at and the openings
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MarlinProof {
+    /// `w`, `z_A`, `z_B` and the mask `s` of the first sumcheck
+    pub witness_commitments: [G1Affine; 4],
+    /// Sum of `s` over H
+    pub mask_sum: Fr,
+    /// `t`, `g_1`, `g_1` shifted and `h_1`
+    pub first_commitments: [G1Affine; 4],
+    /// `g_2`, `g_2` shifted and `h_2`
+    pub second_commitments: [G1Affine; 3],
+    /// Sum proven by the third sumcheck: the eta-combination of the matrices at `(beta_2, beta_1)`
+    pub matrix_sum: Fr,
+    /// `g_3`, `g_3` shifted and `h_3`
+    pub third_commitments: [G1Affine; 3],
+    /// The witness and first sumcheck polynomials at `beta_1`
+    pub first_evaluations: [Fr; 8],
+    /// The second sumcheck polynomials at `beta_2`
+    pub second_evaluations: [Fr; 3],
+    /// The index polynomials and the third sumcheck polynomials at `beta_3`
+    pub third_evaluations: [Fr; 15],
+    /// KZG openings at `beta_1`, `beta_2` and `beta_3`
+    pub openings: [G1Affine; 3],
+}
+
+/// Size of the powers of tau accumulator circuit `C` of the given shape needs to be indexed
+pub fn powers_of_tau_size<C: AttributeCircuit<Fr>>(config: &C::Config) -> Result<usize, ZkpError> {
+    let (_, h, k) = domains(&circuit_matrices::<Fr, C>(config)?)?;
+    Ok(accumulator_size(required_degree(h.size(), k.size())))
+}
+
+/// Sizes of the proving and verifying keys [`setup_with_rng`] produces for circuit `C` of the given shape
+///
+/// Sizes are those of the [key file](crate::key_file) encoding, header included. Keys indexed from a larger
+/// accumulator carry all of its powers and are larger.
+pub fn key_sizes<C: AttributeCircuit<Fr>>(config: &C::Config) -> Result<(SerializedSize, SerializedSize), ZkpError> {
+    let num_powers = 2 * powers_of_tau_size::<C>(config)? - 1;
+    let vk = MarlinVerifyingKey {
+        fingerprint: CircuitFingerprint([0; 32]),
+        num_public_inputs: 0,
+        constraint_domain_size: 0,
+        matrix_domain_size: 0,
+        max_degree: 0,
+        index: [G1Affine::generator(); 12],
+        tau_g2: G2Affine::generator(),
+    };
+    let size = |compress| {
+        let vk = vk.serialized_size(compress);
+        // vk, then the powers as a vector
+        let pk = vk + 0u64.serialized_size(compress) + num_powers * G1Affine::generator().serialized_size(compress);
+        (HEADER_LEN + pk, HEADER_LEN + vk)
+    };
+    let (pk_compressed, vk_compressed) = size(Compress::Yes);
+    let (pk_uncompressed, vk_uncompressed) = size(Compress::No);
+    Ok((
+        SerializedSize { compressed: pk_compressed, uncompressed: pk_uncompressed },
+        SerializedSize { compressed: vk_compressed, uncompressed: vk_uncompressed },
+    ))
+}
+
+/// Derives keys for circuit `C` of the given shape from the powers of tau of a ceremony
+///
+/// The transcript is verified and needs at least one contribution; the same accumulator can index any number of
+/// circuits.
+pub fn index<C: AttributeCircuit<Fr>>(
+    powers: &PowersOfTau,
+    config: &C::Config,
+) -> Result<(MarlinProvingKey, MarlinVerifyingKey), ZkpError> {
+    powers.verify()?;
+    if powers.contributions.is_empty() {
+        return Err(ZkpError::InvalidInput("the powers of tau have no contributions, so tau is public".to_string()));
+    }
+
+    let matrices = circuit_matrices::<Fr, C>(config)?;
+    let fingerprint = fingerprint_matrices::<Fr, C>(&matrices);
+    let num_public_inputs = matrices.num_instance_variables as u64 - 1;
+    let index = Index::new(matrices)?;
+    let tau_g1 = &powers.current.tau_g1;
+    let max_degree = tau_g1.len() - 1;
+    if max_degree < index.max_degree() {
+        return Err(ZkpError::InvalidInput(format!(
+            "the circuit needs powers of tau of size {} but the transcript has {}",
+            accumulator_size(index.max_degree()),
+            powers.current.size()
+        )));
+    }
+
+    let commitments = index.polynomials.iter().map(|p| commit(tau_g1, p)).collect::<Result<Vec<_>, _>>()?;
+    let vk = MarlinVerifyingKey {
+        fingerprint,
+        num_public_inputs,
+        constraint_domain_size: index.h.size() as u64,
+        matrix_domain_size: index.k.size() as u64,
+        max_degree: max_degree as u64,
+        index: commitments.try_into().expect("there are twelve index polynomials"),
+        tau_g2: powers.current.tau_g2[1],
+    };
+    Ok((MarlinProvingKey { vk: vk.clone(), powers: tau_g1.clone() }, vk))
+}
+
+/// Indexes circuit `C` from powers of tau with a single contribution drawn from `rng`
+///
+/// Like a Groth16 setup, the keys are only sound if this process forgets its contribution. Keys other parties must
+/// trust should come from [`index`] with the accumulator of a public ceremony.
+pub fn setup_with_rng<C: AttributeCircuit<Fr>, R: RngCore + CryptoRng>(
+    config: &C::Config,
+    rng: &mut R,
+) -> Result<(MarlinProvingKey, MarlinVerifyingKey), ZkpError> {
+    let mut powers = PowersOfTau::new(powers_of_tau_size::<C>(config)?)?;
+    powers.contribute(rng);
+    index::<C>(&powers, config)
+}
+
+/// Generates a proof for any attribute circuit with keys indexed for its shape
+///
+/// Inputs that do not satisfy the circuit fail with [`ZkpError::UnsatisfiedConstraint`] naming the first violated
+/// constraint.
+pub fn prove<C: AttributeCircuit<Fr>>(
+    pk: &MarlinProvingKey,
+    config: &C::Config,
+    public: &C::PublicInputs,
+    private: &C::PrivateInputs,
+) -> Result<MarlinProof, ZkpError> {
+    prove_with_rng::<C, _>(pk, config, public, private, &mut thread_rng())
+}
+
+/// Generates a proof drawing its masks from a caller-supplied RNG
+pub fn prove_with_rng<C: AttributeCircuit<Fr>, R: RngCore + CryptoRng>(
+    pk: &MarlinProvingKey,
+    config: &C::Config,
+    public: &C::PublicInputs,
+    private: &C::PrivateInputs,
+    rng: &mut R,
+) -> Result<MarlinProof, ZkpError> {
+    C::check_config(config)?;
+    C::check_inputs(config, public, private)?;
+    check_fingerprint::<Fr, C>(&pk.vk.fingerprint, config)?;
+    let public_inputs = C::public_inputs(public);
+    check_public_input_count(&pk.vk, &public_inputs)?;
+    if let Some(name) = first_unsatisfied(C::circuit(config, public, private))? {
+        return Err(ZkpError::UnsatisfiedConstraint { constraint: Some(name) });
+    }
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    C::circuit(config, public, private).generate_constraints(cs.clone())?;
+    cs.finalize();
+    let matrices =
+        cs.to_matrices().ok_or_else(|| ZkpError::InvalidInput("constraint matrices are unavailable".to_string()))?;
+    let assignment: Vec<Fr> = {
+        let inner = cs.borrow().ok_or_else(|| ZkpError::InvalidInput("the constraint system is empty".to_string()))?;
+        inner.instance_assignment.iter().chain(&inner.witness_assignment).copied().collect()
+    };
+
+    // The assignment is laid out by the matrices of this synthesis, which must be the ones the key was indexed from
+    if fingerprint_matrices::<Fr, C>(&matrices) != pk.vk.fingerprint {
+        return Err(ZkpError::KeyCircuitMismatch("the circuit synthesized differently than when indexed".to_string()));
+    }
+    let index = Index::new(matrices)?;
+    let vk = &pk.vk;
+    if index.h.size() as u64 != vk.constraint_domain_size
+        || index.k.size() as u64 != vk.matrix_domain_size
+        || pk.powers.len() as u64 != vk.max_degree + 1
+    {
+        return Err(ZkpError::InvalidInput("malformed Marlin proving key".to_string()));
+    }
+
+    Prover { pk, index: &index, public_inputs: &public_inputs, assignment: &assignment }.prove(rng)
+}
+
+/// Verifies a proof for any attribute circuit against its public inputs
+///
+/// Fails with [`ZkpError::KeyCircuitMismatch`] when the key was indexed for another circuit, version or shape.
+pub fn verify<C: AttributeCircuit<Fr>>(
+    vk: &MarlinVerifyingKey,
+    config: &C::Config,
+    proof: &MarlinProof,
+    public: &C::PublicInputs,
+) -> Result<bool, ZkpError> {
+    check_fingerprint::<Fr, C>(&vk.fingerprint, config)?;
+    let public_inputs = C::public_inputs(public);
+    check_public_input_count(vk, &public_inputs)?;
+    verify_proof(vk, &public_inputs, proof)
+}
+
+fn check_public_input_count(vk: &MarlinVerifyingKey, public_inputs: &[Fr]) -> Result<(), ZkpError> {
+    if public_inputs.len() as u64 != vk.num_public_inputs {
+        return Err(ZkpError::KeyCircuitMismatch(format!(
+            "the key expects {} public inputs but the circuit has {}",
+            vk.num_public_inputs,
+            public_inputs.len()
+        )));
+    }
+    Ok(())
+}
+
+// The matrices laid out over H and K, which the indexer commits to and the prover recomputes from its synthesis
+struct Index {
+    matrices: ConstraintMatrices<Fr>,
+    h: Domain,
+    k: Domain,
+    x: Domain,
+    // Position in H of every variable: the instance variables on X, the witness variables on the rest in order
+    positions: Vec<usize>,
+    // `row`, `col`, `row_col` and `val` of A, B and C over K
+    evaluations: [Vec<Fr>; 12],
+    // The same polynomials in coefficient form
+    polynomials: [Vec<Fr>; 12],
+}
+
+impl Index {
+    fn new(matrices: ConstraintMatrices<Fr>) -> Result<Self, ZkpError> {
+        let (x, h, k) = domains(&matrices)?;
+
+        let stride = h.size() / x.size();
+        let positions: Vec<usize> = (0..matrices.num_instance_variables)
+            .map(|i| i * stride)
+            .chain((0..h.size()).filter(|i| i % stride != 0).take(matrices.num_witness_variables))
+            .collect();
+
+        // M(X, Y) = sum over the entries of u_H(X, row) u_H(Y, col) val, so that M agrees with the matrix on H when
+        // val is the entry divided by u_H(row, row) u_H(col, col) = |H|^2 / (row col)
+        let elements: Vec<Fr> = h.elements().collect();
+        let scale = h.size_inv().square();
+        let mut evaluations: [Vec<Fr>; 12] = Default::default();
+        for (m, matrix) in [&matrices.a, &matrices.b, &matrices.c].into_iter().enumerate() {
+            let (mut rows, mut cols, mut vals) = (Vec::new(), Vec::new(), Vec::new());
+            for (r, entries) in matrix.iter().enumerate() {
+                for (value, variable) in entries {
+                    let (row, col) = (elements[r], elements[positions[*variable]]);
+                    rows.push(row);
+                    cols.push(col);
+                    vals.push(*value * row * col * scale);
+                }
+            }
+
+            // Padding entries have a zero value and add nothing
+            rows.resize(k.size(), Fr::ONE);
+            cols.resize(k.size(), Fr::ONE);
+            vals.resize(k.size(), Fr::zero());
+            evaluations[4 * m + 2] = rows.iter().zip(&cols).map(|(row, col)| *row * col).collect();
+            evaluations[4 * m] = rows;
+            evaluations[4 * m + 1] = cols;
+            evaluations[4 * m + 3] = vals;
+        }
+        let polynomials = evaluations.each_ref().map(|evaluations| k.ifft(evaluations));
+
+        Ok(Self { matrices, h, k, x, positions, evaluations, polynomials })
+    }
+
+    fn matrices(&self) -> [&Vec<Vec<(Fr, usize)>>; 3] {
+        [&self.matrices.a, &self.matrices.b, &self.matrices.c]
+    }
+
+    // Highest degree committed to: `h_1` of the first sumcheck or `h_3` of the third
+    fn max_degree(&self) -> usize {
+        required_degree(self.h.size(), self.k.size())
+    }
+}
+
+// Domains X of the instance, H of constraints and variables and K of nonzero matrix entries
+fn domains(matrices: &ConstraintMatrices<Fr>) -> Result<(Domain, Domain, Domain), ZkpError> {
+    let x = domain(matrices.num_instance_variables)?;
+    let h = domain((x.size() + matrices.num_witness_variables).max(matrices.num_constraints))?;
+    let k = domain(matrices.a_num_non_zero.max(matrices.b_num_non_zero).max(matrices.c_num_non_zero))?;
+    Ok((x, h, k))
+}
+
+fn required_degree(h: usize, k: usize) -> usize {
+    (2 * h + 2 * MASK_DEGREE - 1).max(3 * k - 4)
+}
+
+// An accumulator of size n has powers up to degree 2n - 2 in G1
+fn accumulator_size(max_degree: usize) -> usize {
+    (max_degree.div_ceil(2) + 1).next_power_of_two()
+}
+
+// Smallest subgroup of at least `size` elements, and of at least two so degree bounds of |D| - 2 exist
+fn domain(size: usize) -> Result<Domain, ZkpError> {
+    Domain::new(size.max(2)).ok_or(ZkpError::Setup(SynthesisError::PolynomialDegreeTooLarge))
+}
+
+struct Prover<'a> {
+    pk: &'a MarlinProvingKey,
+    index: &'a Index,
+    public_inputs: &'a [Fr],
+    assignment: &'a [Fr],
+}
+
+impl Prover<'_> {
+    fn prove<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<MarlinProof, ZkpError> {
+        let Index { h, k, x, positions, .. } = self.index;
+        let (n, powers) = (h.size(), self.pk.powers.as_slice());
+        let max_degree = powers.len() - 1;
+        let elements: Vec<Fr> = h.elements().collect();
+        let mut transcript = Transcript::new(&self.pk.vk, self.public_inputs);
+
+        // z over H, and its public part interpolated over X
+        let mut z = vec![Fr::zero(); n];
+        for (position, value) in positions.iter().zip(self.assignment) {
+            z[*position] = *value;
+        }
+        let mut public = self.assignment[..self.public_inputs.len() + 1].to_vec();
+        public.resize(x.size(), Fr::zero());
+        let x_hat = x.ifft(&public);
+
+        // w is (z - x_hat) / v_X off X and zero on X, so that z = x_hat + w * v_X on H
+        let x_hat_on_h = h.fft(&x_hat);
+        let stride = n / x.size();
+        let mut inverses: Vec<Fr> = elements
+            .iter()
+            .enumerate()
+            .map(|(i, e)| if i % stride == 0 { Fr::ONE } else { x.evaluate_vanishing_polynomial(*e) })
+            .collect();
+        batch_inversion(&mut inverses);
+        let w_on_h: Vec<Fr> =
+            (0..n).map(|i| if i % stride == 0 { Fr::zero() } else { (z[i] - x_hat_on_h[i]) * inverses[i] }).collect();
+        let w = mask(h.ifft(&w_on_h), h, rng);
+
+        let [z_a, z_b] = [&self.index.matrices.a, &self.index.matrices.b].map(|matrix| {
+            let mut product: Vec<Fr> = matrix.iter().map(|row| linear_combination(row, self.assignment)).collect();
+            product.resize(n, Fr::zero());
+            mask(h.ifft(&product), h, rng)
+        });
+        let s: Vec<Fr> = (0..2 * n + 2 * MASK_DEGREE).map(|_| Fr::rand(rng)).collect();
+        // The powers of an element of H sum to zero over H unless the exponent is a multiple of |H|
+        let mask_sum = h.size_as_field_element() * s.iter().step_by(n).sum::<Fr>();
+
+        let witness_commitments =
+            [commit(powers, &w)?, commit(powers, &z_a)?, commit(powers, &z_b)?, commit(powers, &s)?];
+        transcript.absorb(&witness_commitments);
+        transcript.absorb(&mask_sum);
+        let alpha = transcript.challenge_outside(h);
+        let eta: [Fr; 3] = array::from_fn(|_| transcript.challenge());
+
+        // First sumcheck. r(alpha, X) = (v_H(alpha) - v_H(X)) / (alpha - X) has coefficients alpha^(n - 1 - i) and
+        // is v_H(alpha) / (alpha - X) on H
+        let mut r_alpha: Vec<Fr> = iter::successors(Some(Fr::ONE), |p| Some(*p * alpha)).take(n).collect();
+        r_alpha.reverse();
+        let mut r_alpha_on_h: Vec<Fr> = elements.iter().map(|e| alpha - e).collect();
+        batch_inversion(&mut r_alpha_on_h);
+        let vanishing_alpha = h.evaluate_vanishing_polynomial(alpha);
+        r_alpha_on_h.iter_mut().for_each(|r| *r *= vanishing_alpha);
+
+        // t(X) = sum_M eta_M sum_{row in H} r(alpha, row) M(row, X)
+        let mut t_on_h = vec![Fr::zero(); n];
+        for (eta, matrix) in eta.iter().zip(self.index.matrices()) {
+            for (r, entries) in matrix.iter().enumerate() {
+                for (value, variable) in entries {
+                    t_on_h[positions[*variable]] += *eta * r_alpha_on_h[r] * value;
+                }
+            }
+        }
+        let t = h.ifft(&t_on_h);
+
+        let mut z_hat = vec![Fr::zero(); w.len() + x.size()];
+        for (i, c) in w.iter().enumerate() {
+            z_hat[i + x.size()] += c;
+            z_hat[i] -= c;
+        }
+        z_hat.iter_mut().zip(&x_hat).for_each(|(z, c)| *z += c);
+
+        let e = domain(3 * n + 2 * MASK_DEGREE)?;
+        let [s_e, r_e, a_e, b_e, t_e, z_e] = [&s, &r_alpha, &z_a, &z_b, &t, &z_hat].map(|p| e.fft(p));
+        let q_1: Vec<Fr> = (0..e.size())
+            .map(|i| {
+                let constraints = eta[0] * a_e[i] + eta[1] * b_e[i] + eta[2] * a_e[i] * b_e[i];
+                s_e[i] + r_e[i] * constraints - t_e[i] * z_e[i]
+            })
+            .collect();
+        let (h_1, g_1) = split_sumcheck(e.ifft(&q_1), h, mask_sum);
+        let g_1_shifted = shift(&g_1, max_degree - (n - 2));
+
+        let first_commitments =
+            [commit(powers, &t)?, commit(powers, &g_1)?, commit(powers, &g_1_shifted)?, commit(powers, &h_1)?];
+        transcript.absorb(&first_commitments);
+        let beta_1 = transcript.challenge_outside(h);
+
+        // Second sumcheck, of r(alpha, X) sum_M eta_M M(X, beta_1), whose sum over H is t(beta_1)
+        let lagrange_1 = h.evaluate_all_lagrange_coefficients(beta_1);
+        let mut m_on_h = vec![Fr::zero(); n];
+        for (eta, matrix) in eta.iter().zip(self.index.matrices()) {
+            for (r, entries) in matrix.iter().enumerate() {
+                m_on_h[r] +=
+                    entries.iter().map(|(value, variable)| *eta * value * lagrange_1[positions[*variable]]).sum::<Fr>();
+            }
+        }
+        let e = domain(2 * n)?;
+        let q_2: Vec<Fr> = e.fft(&r_alpha).iter().zip(e.fft(&h.ifft(&m_on_h))).map(|(r, m)| *r * m).collect();
+        let (h_2, g_2) = split_sumcheck(e.ifft(&q_2), h, evaluate(&t, beta_1));
+        let g_2_shifted = shift(&g_2, max_degree - (n - 2));
+
+        let second_commitments = [commit(powers, &g_2)?, commit(powers, &g_2_shifted)?, commit(powers, &h_2)?];
+        transcript.absorb(&second_commitments);
+        let beta_2 = transcript.challenge_outside(h);
+
+        // Third sumcheck, over K, of the fraction
+        // f = sum_M eta_M v_H(beta_2) v_H(beta_1) val_M / ((beta_2 - row_M) (beta_1 - col_M)) = a / b, so a - b * f
+        // vanishes on K
+        let lagrange_2 = h.evaluate_all_lagrange_coefficients(beta_2);
+        let matrix_sum: Fr = eta
+            .iter()
+            .zip(self.index.matrices())
+            .flat_map(|(eta, matrix)| {
+                let lagrange_2 = &lagrange_2;
+                let lagrange_1 = &lagrange_1;
+                matrix.iter().enumerate().flat_map(move |(r, entries)| {
+                    entries
+                        .iter()
+                        .map(move |(value, variable)| *eta * value * lagrange_2[r] * lagrange_1[positions[*variable]])
+                })
+            })
+            .sum();
+
+        let vanishing = h.evaluate_vanishing_polynomial(beta_2) * h.evaluate_vanishing_polynomial(beta_1);
+        let evaluations = &self.index.evaluations;
+        let mut denominators: Vec<Fr> = (0..3 * k.size())
+            .map(|i| {
+                let (m, j) = (i / k.size(), i % k.size());
+                denominator(beta_1, beta_2, array::from_fn(|p| evaluations[4 * m + p][j]))
+            })
+            .collect();
+        batch_inversion(&mut denominators);
+        let f_on_k: Vec<Fr> = (0..k.size())
+            .map(|j| {
+                (0..3).map(|m| eta[m] * evaluations[4 * m + 3][j] * denominators[m * k.size() + j]).sum::<Fr>()
+                    * vanishing
+            })
+            .collect();
+        let f = k.ifft(&f_on_k);
+        debug_assert_eq!(f[0], matrix_sum * k.size_inv());
+        let g_3 = f[1..].to_vec();
+        let g_3_shifted = shift(&g_3, max_degree - (k.size() - 2));
+
+        let e = domain(4 * k.size())?;
+        let on_e: Vec<Vec<Fr>> = self.index.polynomials.iter().map(|p| e.fft(p)).collect();
+        let f_e = e.fft(&f);
+        let numerator: Vec<Fr> = (0..e.size())
+            .map(|i| {
+                let d: [Fr; 3] =
+                    array::from_fn(|m| denominator(beta_1, beta_2, array::from_fn(|p| on_e[4 * m + p][i])));
+                let a = vanishing
+                    * (eta[0] * on_e[3][i] * d[1] * d[2]
+                        + eta[1] * on_e[7][i] * d[0] * d[2]
+                        + eta[2] * on_e[11][i] * d[0] * d[1]);
+                a - d[0] * d[1] * d[2] * f_e[i]
+            })
+            .collect();
+        let (h_3, remainder) = divide_by_vanishing(e.ifft(&numerator), k);
+        debug_assert!(remainder.iter().all(Zero::is_zero));
+
+        transcript.absorb(&matrix_sum);
+        let third_commitments = [commit(powers, &g_3)?, commit(powers, &g_3_shifted)?, commit(powers, &h_3)?];
+        transcript.absorb(&third_commitments);
+        let beta_3 = transcript.challenge();
+
+        let first: [&[Fr]; 8] = [&w, &z_a, &z_b, &s, &t, &g_1, &g_1_shifted, &h_1];
+        let second: [&[Fr]; 3] = [&g_2, &g_2_shifted, &h_2];
+        let third: Vec<&[Fr]> =
+            self.index.polynomials.iter().map(Vec::as_slice).chain([g_3.as_slice(), &g_3_shifted, &h_3]).collect();
+        let first_evaluations = first.map(|p| evaluate(p, beta_1));
+        let second_evaluations = second.map(|p| evaluate(p, beta_2));
+        let third_evaluations: [Fr; 15] = array::from_fn(|i| evaluate(third[i], beta_3));
+        transcript.absorb(&first_evaluations);
+        transcript.absorb(&second_evaluations);
+        transcript.absorb(&third_evaluations);
+        let xi = transcript.challenge();
+
+        let openings =
+            [open(powers, &first, beta_1, xi)?, open(powers, &second, beta_2, xi)?, open(powers, &third, beta_3, xi)?];
+        Ok(MarlinProof {
+            witness_commitments,
+            mask_sum,
+            first_commitments,
+            second_commitments,
+            matrix_sum,
+            third_commitments,
+            first_evaluations,
+            second_evaluations,
+            third_evaluations,
+            openings,
+        })
+    }
+}
+
+fn verify_proof(vk: &MarlinVerifyingKey, public_inputs: &[Fr], proof: &MarlinProof) -> Result<bool, ZkpError> {
+    let (h, k, x) = key_domains(vk)?;
+    let mut transcript = Transcript::new(vk, public_inputs);
+    transcript.absorb(&proof.witness_commitments);
+    transcript.absorb(&proof.mask_sum);
+    let alpha = transcript.challenge_outside(&h);
+    let eta: [Fr; 3] = array::from_fn(|_| transcript.challenge());
+    transcript.absorb(&proof.first_commitments);
+    let beta_1 = transcript.challenge_outside(&h);
+    transcript.absorb(&proof.second_commitments);
+    let beta_2 = transcript.challenge_outside(&h);
+    transcript.absorb(&proof.matrix_sum);
+    transcript.absorb(&proof.third_commitments);
+    let beta_3 = transcript.challenge();
+    transcript.absorb(&proof.first_evaluations);
+    transcript.absorb(&proof.second_evaluations);
+    transcript.absorb(&proof.third_evaluations);
+    let xi = transcript.challenge();
+    transcript.absorb(&proof.openings);
+    let rho = transcript.challenge();
+    if alpha == beta_1 || alpha == beta_2 {
+        return Ok(false);
+    }
+
+    let [w, z_a, z_b, s, t, g_1, g_1_shifted, h_1] = proof.first_evaluations;
+    let [g_2, g_2_shifted, h_2] = proof.second_evaluations;
+    let [index @ .., g_3, g_3_shifted, h_3] = proof.third_evaluations;
+
+    // Only polynomials within the degree bound can be committed shifted up to the maximum degree
+    let shifted = |point: Fr, bound: usize| point.pow([vk.max_degree - bound as u64]);
+    let within_bounds = g_1_shifted == shifted(beta_1, h.size() - 2) * g_1
+        && g_2_shifted == shifted(beta_2, h.size() - 2) * g_2
+        && g_3_shifted == shifted(beta_3, k.size() - 2) * g_3;
+
+    let vanishing_alpha = h.evaluate_vanishing_polynomial(alpha);
+    let [vanishing_1, vanishing_2] = [beta_1, beta_2].map(|beta| h.evaluate_vanishing_polynomial(beta));
+    let r_alpha = |beta: Fr, vanishing: Fr| (vanishing_alpha - vanishing) / (alpha - beta);
+
+    let x_hat: Fr = x
+        .evaluate_all_lagrange_coefficients(beta_1)
+        .iter()
+        .zip(iter::once(&Fr::ONE).chain(public_inputs))
+        .map(|(lagrange, input)| *lagrange * input)
+        .sum();
+    let z = x_hat + w * x.evaluate_vanishing_polynomial(beta_1);
+    let q_1 = s + r_alpha(beta_1, vanishing_1) * (eta[0] * z_a + eta[1] * z_b + eta[2] * z_a * z_b) - t * z;
+    let first = q_1 == h_1 * vanishing_1 + beta_1 * g_1 + proof.mask_sum * h.size_inv();
+
+    let second = r_alpha(beta_2, vanishing_2) * proof.matrix_sum == h_2 * vanishing_2 + beta_2 * g_2 + t * h.size_inv();
+
+    let d: [Fr; 3] = array::from_fn(|m| denominator(beta_1, beta_2, array::from_fn(|p| index[4 * m + p])));
+    let a = vanishing_2
+        * vanishing_1
+        * (eta[0] * index[3] * d[1] * d[2] + eta[1] * index[7] * d[0] * d[2] + eta[2] * index[11] * d[0] * d[1]);
+    let f = beta_3 * g_3 + proof.matrix_sum * k.size_inv();
+    let third = a - d[0] * d[1] * d[2] * f == h_3 * k.evaluate_vanishing_polynomial(beta_3);
+    if !(within_bounds && first && second && third) {
+        return Ok(false);
+    }
+
+    // The three batched openings at once: e(sum rho^j (C_j - y_j G + beta_j pi_j), G2) = e(sum rho^j pi_j, tau G2)
+    let first_commitments: Vec<G1Affine> =
+        proof.witness_commitments.iter().chain(&proof.first_commitments).copied().collect();
+    let third_commitments: Vec<G1Affine> = vk.index.iter().chain(&proof.third_commitments).copied().collect();
+    let batches = [
+        (first_commitments.as_slice(), proof.first_evaluations.as_slice(), beta_1),
+        (proof.second_commitments.as_slice(), proof.second_evaluations.as_slice(), beta_2),
+        (third_commitments.as_slice(), proof.third_evaluations.as_slice(), beta_3),
+    ];
+    let (mut left, mut right) = (G1Projective::zero(), G1Projective::zero());
+    for ((commitments, evaluations, point), (opening, factor)) in
+        batches.into_iter().zip(proof.openings.iter().zip(powers_of(rho, 3)))
+    {
+        let xis = powers_of(xi, commitments.len());
+        let value: Fr = xis.iter().zip(evaluations).map(|(xi, y)| *xi * y).sum();
+        let combined =
+            G1Projective::msm_unchecked(commitments, &xis) - G1Affine::generator() * value + *opening * point;
+        left += combined * factor;
+        right += *opening * factor;
+    }
+    Ok(same_ratio((right.into_affine(), left.into_affine()), (G2Affine::generator(), vk.tau_g2)))
+}
+
+// Domains of a verifying key, which may come from an untrusted client
+fn key_domains(vk: &MarlinVerifyingKey) -> Result<(Domain, Domain, Domain), ZkpError> {
+    let malformed = || ZkpError::InvalidInput("malformed Marlin verifying key".to_string());
+    let subgroup = |size: u64| {
+        usize::try_from(size).ok().filter(|size| *size >= 2 && size.is_power_of_two()).and_then(Domain::new)
+    };
+    let h = subgroup(vk.constraint_domain_size).ok_or_else(malformed)?;
+    let k = subgroup(vk.matrix_domain_size).ok_or_else(malformed)?;
+    let x = usize::try_from(vk.num_public_inputs).ok().and_then(|n| domain(n + 1).ok()).ok_or_else(malformed)?;
+    if x.size() > h.size() || vk.max_degree < required_degree(h.size(), k.size()) as u64 {
+        return Err(malformed());
+    }
+    Ok((h, k, x))
+}
+
+// Fiat-Shamir transcript: every prover message is hashed into the state, and challenges are read from it
+struct Transcript {
+    state: [u8; 64],
+}
+
+impl Transcript {
+    fn new(vk: &MarlinVerifyingKey, public_inputs: &[Fr]) -> Self {
+        let mut transcript = Self { state: Sha512::digest(TRANSCRIPT_DOMAIN).into() };
+        transcript.absorb(vk);
+        transcript.absorb(public_inputs);
+        transcript
+    }
+
+    fn absorb<T: CanonicalSerialize + ?Sized>(&mut self, message: &T) {
+        let mut bytes = self.state.to_vec();
+        message.serialize_compressed(&mut bytes).expect("writing to a Vec cannot fail");
+        self.state = Sha512::digest(&bytes).into();
+    }
+
+    fn challenge(&mut self) -> Fr {
+        let challenge = Fr::from_le_bytes_mod_order(&self.state);
+        self.absorb(&challenge);
+        challenge
+    }
+
+    // A challenge outside `domain`, where its vanishing polynomial can be divided by
+    fn challenge_outside(&mut self, domain: &Domain) -> Fr {
+        loop {
+            let challenge = self.challenge();
+            if !domain.evaluate_vanishing_polynomial(challenge).is_zero() {
+                return challenge;
+            }
+        }
+    }
+}
+
+// Adds a random multiple of v_D of degree `MASK_DEGREE`, which leaves the polynomial unchanged on D
+fn mask<R: RngCore>(mut coeffs: Vec<Fr>, domain: &Domain, rng: &mut R) -> Vec<Fr> {
+    let size = domain.size();
+    coeffs.resize(size + MASK_DEGREE + 1, Fr::zero());
+    for i in 0..=MASK_DEGREE {
+        let c = Fr::rand(rng);
+        coeffs[size + i] += c;
+        coeffs[i] -= c;
+    }
+    coeffs
+}
+
+// Splits q into h * v_D + X * g + sum / |D|, which is possible exactly when q sums to `sum` over D
+fn split_sumcheck(q: Vec<Fr>, domain: &Domain, sum: Fr) -> (Vec<Fr>, Vec<Fr>) {
+    let (quotient, remainder) = divide_by_vanishing(q, domain);
+    debug_assert_eq!(remainder.first().copied().unwrap_or_default(), sum * domain.size_inv());
+    (quotient, remainder.get(1..).unwrap_or_default().to_vec())
+}
+
+fn divide_by_vanishing(coeffs: Vec<Fr>, domain: &Domain) -> (Vec<Fr>, Vec<Fr>) {
+    let (quotient, remainder) = DensePolynomial::from_coefficients_vec(coeffs)
+        .divide_by_vanishing_poly(*domain)
+        .expect("dividing by a vanishing polynomial cannot fail");
+    (quotient.coeffs, remainder.coeffs)
+}
+
+// g * X^shift, committed next to g so the verifier can tell g has degree at most max_degree - shift
+fn shift(coeffs: &[Fr], shift: usize) -> Vec<Fr> {
+    let mut shifted = vec![Fr::zero(); shift];
+    shifted.extend_from_slice(coeffs);
+    shifted
+}
+
+// (beta_2 - row) (beta_1 - col) from `row`, `col` and `row_col`, in which it is linear
+fn denominator(beta_1: Fr, beta_2: Fr, [row, col, row_col]: [Fr; 3]) -> Fr {
+    beta_1 * beta_2 - beta_1 * row - beta_2 * col + row_col
+}
+
+fn linear_combination(row: &[(Fr, usize)], assignment: &[Fr]) -> Fr {
+    row.iter().map(|(coefficient, index)| *coefficient * assignment[*index]).sum()
+}
+
+fn evaluate(coeffs: &[Fr], point: Fr) -> Fr {
+    coeffs.iter().rev().fold(Fr::zero(), |acc, c| acc * point + c)
+}
+
+fn powers_of(x: Fr, count: usize) -> Vec<Fr> {
+    iter::successors(Some(Fr::ONE), |p| Some(*p * x)).take(count).collect()
+}
+
+// KZG commitment: the polynomial evaluated at tau, in G1
+fn commit(powers: &[G1Affine], coeffs: &[Fr]) -> Result<G1Affine, ZkpError> {
+    if coeffs.len() > powers.len() {
+        return Err(ZkpError::InvalidInput(format!(
+            "a polynomial of degree {} is beyond the powers of tau",
+            coeffs.len() - 1
+        )));
+    }
+    Ok(G1Projective::msm_unchecked(&powers[..coeffs.len()], coeffs).into_affine())
+}
+
+// Batched KZG opening at `point`: a commitment to (p - p(point)) / (X - point) for p = sum xi^i p_i
+fn open(powers: &[G1Affine], polynomials: &[&[Fr]], point: Fr, xi: Fr) -> Result<G1Affine, ZkpError> {
+    let len = polynomials.iter().map(|p| p.len()).max().unwrap_or(0);
+    let mut combined = vec![Fr::zero(); len];
+    for (p, factor) in polynomials.iter().zip(powers_of(xi, polynomials.len())) {
+        combined.iter_mut().zip(p.iter()).for_each(|(c, coefficient)| *c += factor * coefficient);
+    }
+
+    // Synthetic division, dropping the remainder p(point)
+    let mut quotient = vec![Fr::zero(); len.saturating_sub(1)];
+    let mut carry = Fr::zero();
+    for i in (1..len).rev() {
+        carry = combined[i] + carry * point;
+        quotient[i - 1] = carry;
+    }
+    commit(powers, &quotient)
+}
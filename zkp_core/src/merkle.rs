@@ -0,0 +1,212 @@
+use ark_bn254::Fr;
+use ark_ff::{Field, Zero};
+use std::fmt;
+
+use crate::circuits::citizenship_verification::witness_calculator::MerklePath;
+use crate::poseidon::{PoseidonParameters, poseidon_hash};
+
+/// Value of an unoccupied leaf. The citizenship circuit refuses to prove membership of this value
+pub const EMPTY_LEAF: Fr = Fr::ZERO;
+
+/// Errors returned when modifying or querying a [`MerkleTree`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MerkleTreeError {
+    /// The leaf index does not fit in a tree of this depth
+    IndexOutOfRange { index: u64, capacity: u64 },
+    /// Every leaf slot of the tree is already taken
+    TreeFull { capacity: u64 },
+    /// The empty leaf value cannot be registered as a citizen
+    EmptyLeaf,
+}
+
+impl fmt::Display for MerkleTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MerkleTreeError::IndexOutOfRange { index, capacity } => {
+                write!(f, "leaf index {} is out of range for a tree of {} leaves", index, capacity)
+            }
+            MerkleTreeError::TreeFull { capacity } => {
+                write!(f, "tree is full ({} leaves)", capacity)
+            }
+            MerkleTreeError::EmptyLeaf => write!(f, "the empty leaf value cannot be inserted"),
+        }
+    }
+}
+
+impl std::error::Error for MerkleTreeError {}
+
+/// Fixed-depth Poseidon Merkle tree holding the hashed IDs of registered citizens
+///
+/// Leaves are stored densely up to the highest occupied index; every node beyond that is the hash of an
+/// empty subtree, precomputed once per level. Updates only rehash the nodes on the path to the root.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    depth: usize,
+    params: &'static PoseidonParameters<Fr>,
+    // empty_hashes[level] is the root of an empty subtree whose leaves sit `level` levels below it
+    empty_hashes: Vec<Fr>,
+    // levels[0] holds the leaves, levels[depth] holds the root
+    levels: Vec<Vec<Fr>>,
+}
+
+impl MerkleTree {
+    /// Creates an empty tree of the given depth
+    pub fn new(depth: usize) -> Self {
+        assert!(depth < 64, "Merkle tree depth must be below 64");
+
+        let params = PoseidonParameters::<Fr>::shared(2);
+
+        let mut empty_hashes = Vec::with_capacity(depth + 1);
+        empty_hashes.push(EMPTY_LEAF);
+        for level in 0..depth {
+            let below = empty_hashes[level];
+            empty_hashes.push(poseidon_hash(params, &[below, below]));
+        }
+
+        Self {
+            depth,
+            params,
+            empty_hashes,
+            levels: vec![Vec::new(); depth + 1],
+        }
+    }
+
+    /// Builds a tree whose first leaves are `leaves`, in order
+    pub fn from_leaves(depth: usize, leaves: &[Fr]) -> Result<Self, MerkleTreeError> {
+        let mut tree = Self::new(depth);
+        if leaves.len() as u64 > tree.capacity() {
+            return Err(MerkleTreeError::TreeFull { capacity: tree.capacity() });
+        }
+        if leaves.iter().any(|leaf| leaf.is_zero()) {
+            return Err(MerkleTreeError::EmptyLeaf);
+        }
+
+        // Hash level by level so each internal node is computed exactly once
+        tree.levels[0] = leaves.to_vec();
+        for level in 0..depth {
+            let parents = tree.levels[level].len().div_ceil(2);
+            tree.levels[level + 1] = (0..parents as u64)
+                .map(|i| tree.hash_children(level, i))
+                .collect();
+        }
+
+        Ok(tree)
+    }
+
+    /// Depth of the tree, i.e. the length of every authentication path
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Maximum number of leaves the tree can hold
+    pub fn capacity(&self) -> u64 {
+        1u64 << self.depth
+    }
+
+    /// Current Merkle root, the public input of the citizenship circuit
+    pub fn root(&self) -> Fr {
+        self.node(self.depth, 0)
+    }
+
+    /// Returns the leaf stored at `index`, or [`EMPTY_LEAF`] if the slot is unoccupied
+    pub fn leaf(&self, index: u64) -> Result<Fr, MerkleTreeError> {
+        self.check_index(index)?;
+        Ok(self.node(0, index))
+    }
+
+    /// Returns the index of the first slot holding `leaf`
+    pub fn index_of(&self, leaf: Fr) -> Option<u64> {
+        if leaf.is_zero() {
+            return None;
+        }
+        self.levels[0].iter().position(|l| *l == leaf).map(|i| i as u64)
+    }
+
+    /// Appends a leaf after the highest occupied slot and returns its index
+    pub fn insert(&mut self, leaf: Fr) -> Result<u64, MerkleTreeError> {
+        let index = self.levels[0].len() as u64;
+        if index >= self.capacity() {
+            return Err(MerkleTreeError::TreeFull { capacity: self.capacity() });
+        }
+        if leaf.is_zero() {
+            return Err(MerkleTreeError::EmptyLeaf);
+        }
+        self.set(index, leaf);
+        Ok(index)
+    }
+
+    /// Replaces the leaf at `index` and recomputes the nodes on its path to the root
+    pub fn update(&mut self, index: u64, leaf: Fr) -> Result<(), MerkleTreeError> {
+        self.check_index(index)?;
+        if leaf.is_zero() {
+            return Err(MerkleTreeError::EmptyLeaf);
+        }
+        self.set(index, leaf);
+        Ok(())
+    }
+
+    /// Clears the leaf at `index` so it can no longer be proven, keeping every other index stable
+    pub fn remove(&mut self, index: u64) -> Result<(), MerkleTreeError> {
+        self.check_index(index)?;
+        self.set(index, EMPTY_LEAF);
+        Ok(())
+    }
+
+    /// Authentication path for the leaf at `index`, in the shape consumed by the citizenship circuit
+    pub fn path(&self, index: u64) -> Result<MerklePath, MerkleTreeError> {
+        self.check_index(index)?;
+
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut directions = Vec::with_capacity(self.depth);
+        let mut position = index;
+        for level in 0..self.depth {
+            siblings.push(self.node(level, position ^ 1));
+            directions.push(position & 1 == 1);
+            position >>= 1;
+        }
+
+        Ok(MerklePath { siblings, directions })
+    }
+
+    fn check_index(&self, index: u64) -> Result<(), MerkleTreeError> {
+        if index >= self.capacity() {
+            return Err(MerkleTreeError::IndexOutOfRange { index, capacity: self.capacity() });
+        }
+        Ok(())
+    }
+
+    // Reads a node, falling back to the empty subtree hash beyond the stored prefix
+    fn node(&self, level: usize, index: u64) -> Fr {
+        self.levels[level]
+            .get(index as usize)
+            .copied()
+            .unwrap_or(self.empty_hashes[level])
+    }
+
+    fn hash_children(&self, level: usize, parent: u64) -> Fr {
+        let left = self.node(level, 2 * parent);
+        let right = self.node(level, 2 * parent + 1);
+        poseidon_hash(self.params, &[left, right])
+    }
+
+    // Writes a node, growing the stored prefix with empty subtree hashes if needed
+    fn write_node(&mut self, level: usize, index: u64, value: Fr) {
+        let empty = self.empty_hashes[level];
+        let nodes = &mut self.levels[level];
+        if nodes.len() <= index as usize {
+            nodes.resize(index as usize + 1, empty);
+        }
+        nodes[index as usize] = value;
+    }
+
+    fn set(&mut self, index: u64, leaf: Fr) {
+        self.write_node(0, index, leaf);
+
+        let mut position = index;
+        for level in 0..self.depth {
+            position >>= 1;
+            let parent = self.hash_children(level, position);
+            self.write_node(level + 1, position, parent);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use ark_bn254::Fr;
+use ark_ff::{PrimeField, UniformRand};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use rand::{CryptoRng, RngCore};
+
+// Every circuit takes a verifier-chosen nonce as its first public input. A proof only verifies against the nonce
+// it was generated for, so a verifier that issues a fresh nonce per request and accepts each nonce once cannot
+// be fooled by a proof replayed from an earlier session.
+
+/// Draws a fresh challenge nonce for a verifier to hand to a prover
+pub fn generate_nonce<R: RngCore + CryptoRng>(rng: &mut R) -> Fr {
+    Fr::rand(rng)
+}
+
+/// Allocates the nonce as a public input and ties it into the constraint system
+pub fn nonce_input_gadget<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    nonce: Option<F>,
+) -> Result<(), SynthesisError> {
+    let nonce_var = FpVar::<F>::new_input(cs, || nonce.ok_or(SynthesisError::AssignmentMissing))?;
+
+    // nonce * nonce gives the input a constraint of its own, so it cannot be left unconstrained by the reduction
+    let _nonce_squared = nonce_var.square()?;
+
+    Ok(())
+}
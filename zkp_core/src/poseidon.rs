@@ -0,0 +1,214 @@
+use ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::SynthesisError;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Number of full rounds used by the default parameter sets
+pub const DEFAULT_FULL_ROUNDS: usize = 8;
+
+/// S-box exponent used by the default parameter sets (x^5 is a permutation of the BN254 scalar field)
+pub const DEFAULT_ALPHA: u64 = 5;
+
+// Partial rounds for a 254-bit field at the 128-bit security level, indexed by width - 2.
+// Taken from Table 2 of the Poseidon paper (https://eprint.iacr.org/2019/458).
+const DEFAULT_PARTIAL_ROUNDS: [usize; 7] = [56, 57, 56, 60, 60, 63, 64];
+
+/// Parameters of a Poseidon permutation over the field `F`
+///
+/// The round constants and the MDS matrix are derived with the Grain LFSR described in the
+/// Poseidon paper, so two parameter sets built with the same width and round counts are identical.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoseidonParameters<F: PrimeField> {
+    /// Size of the permutation state (number of inputs + 1 capacity element)
+    pub width: usize,
+    /// Number of rounds applying the S-box to every state element
+    pub full_rounds: usize,
+    /// Number of rounds applying the S-box to the first state element only
+    pub partial_rounds: usize,
+    /// S-box exponent
+    pub alpha: u64,
+    /// Round constants, indexed by `ark[round][state_element]`
+    pub ark: Vec<Vec<F>>,
+    /// Maximum distance separable matrix mixing the state after every round
+    pub mds: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> PoseidonParameters<F> {
+    /// Derives a parameter set for the given width and round configuration
+    pub fn new(width: usize, full_rounds: usize, partial_rounds: usize, alpha: u64) -> Self {
+        assert!(width >= 2, "Poseidon width must be at least 2");
+        assert!(full_rounds.is_multiple_of(2), "Poseidon full rounds must be even");
+
+        let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+            F::MODULUS_BIT_SIZE as u64,
+            width - 1,
+            full_rounds as u64,
+            partial_rounds as u64,
+            0,
+        );
+
+        Self {
+            width,
+            full_rounds,
+            partial_rounds,
+            alpha,
+            ark,
+            mds,
+        }
+    }
+
+    /// Returns the recommended 128-bit secure parameters for hashing `width - 1` field elements
+    pub fn for_width(width: usize) -> Self {
+        assert!(
+            (2..DEFAULT_PARTIAL_ROUNDS.len() + 2).contains(&width),
+            "no default Poseidon parameters for width {}",
+            width
+        );
+        Self::new(
+            width,
+            DEFAULT_FULL_ROUNDS,
+            DEFAULT_PARTIAL_ROUNDS[width - 2],
+            DEFAULT_ALPHA,
+        )
+    }
+
+    /// Returns the recommended parameters for hashing `num_inputs` field elements
+    pub fn for_inputs(num_inputs: usize) -> Self {
+        Self::for_width(num_inputs + 1)
+    }
+
+    /// Returns the recommended parameters for hashing `num_inputs` field elements, derived once per process
+    ///
+    /// Deriving a parameter set runs the Grain LFSR over every round constant, so hashing code should take the
+    /// parameters from here rather than rebuilding them per hash.
+    pub fn shared(num_inputs: usize) -> &'static Self {
+        // (field, number of inputs) -> parameters. The sets are type-erased so every field shares one cache; only a
+        // handful of widths are ever used, so they are leaked rather than reference counted.
+        type ParameterCache = HashMap<(TypeId, usize), &'static (dyn Any + Send + Sync)>;
+        static CACHE: OnceLock<Mutex<ParameterCache>> = OnceLock::new();
+
+        let mut cache = CACHE.get_or_init(Default::default).lock().expect("Poseidon parameter cache lock poisoned");
+        let params = *cache
+            .entry((TypeId::of::<F>(), num_inputs))
+            .or_insert_with(|| Box::leak(Box::new(Self::for_inputs(num_inputs))));
+        params.downcast_ref::<Self>().expect("the cache key includes the field")
+    }
+
+    fn is_full_round(&self, round: usize) -> bool {
+        let half = self.full_rounds / 2;
+        round < half || round >= half + self.partial_rounds
+    }
+}
+
+/// Applies the Poseidon permutation to `state` in place
+pub fn poseidon_permute<F: PrimeField>(params: &PoseidonParameters<F>, state: &mut [F]) {
+    assert_eq!(state.len(), params.width, "state length must equal the Poseidon width");
+
+    for round in 0..(params.full_rounds + params.partial_rounds) {
+        // Add round constants
+        for (elem, c) in state.iter_mut().zip(&params.ark[round]) {
+            *elem += c;
+        }
+
+        // Apply the S-box to the whole state in full rounds, and to the first element otherwise
+        if params.is_full_round(round) {
+            for elem in state.iter_mut() {
+                *elem = elem.pow([params.alpha]);
+            }
+        } else {
+            state[0] = state[0].pow([params.alpha]);
+        }
+
+        // Mix the state with the MDS matrix
+        let mixed: Vec<F> = params
+            .mds
+            .iter()
+            .map(|row| row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum())
+            .collect();
+        state.copy_from_slice(&mixed);
+    }
+}
+
+/// Hashes `inputs` natively. The number of inputs must be `params.width - 1`
+pub fn poseidon_hash<F: PrimeField>(params: &PoseidonParameters<F>, inputs: &[F]) -> F {
+    assert_eq!(inputs.len() + 1, params.width, "Poseidon input count must be width - 1");
+
+    // The first state element is the capacity and starts at zero
+    let mut state = Vec::with_capacity(params.width);
+    state.push(F::zero());
+    state.extend_from_slice(inputs);
+
+    poseidon_permute(params, &mut state);
+    state[0]
+}
+
+/// Convenience helper hashing two field elements with the shared width-3 parameters
+pub fn poseidon_hash_two<F: PrimeField>(left: F, right: F) -> F {
+    poseidon_hash(PoseidonParameters::shared(2), &[left, right])
+}
+
+// Computes x^alpha in-circuit using square-and-multiply
+fn sbox_gadget<F: PrimeField>(x: &FpVar<F>, alpha: u64) -> Result<FpVar<F>, SynthesisError> {
+    x.pow_by_constant([alpha])
+}
+
+/// Applies the Poseidon permutation to `state` inside the circuit
+pub fn poseidon_permute_gadget<F: PrimeField>(
+    params: &PoseidonParameters<F>,
+    state: &mut [FpVar<F>],
+) -> Result<(), SynthesisError> {
+    if state.len() != params.width {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    for round in 0..(params.full_rounds + params.partial_rounds) {
+        // Add round constants
+        for (elem, c) in state.iter_mut().zip(&params.ark[round]) {
+            *elem += *c;
+        }
+
+        // Apply the S-box to the whole state in full rounds, and to the first element otherwise
+        if params.is_full_round(round) {
+            for elem in state.iter_mut() {
+                *elem = sbox_gadget(elem, params.alpha)?;
+            }
+        } else {
+            state[0] = sbox_gadget(&state[0], params.alpha)?;
+        }
+
+        // Mix the state with the MDS matrix (linear, so it costs no constraints)
+        let mixed: Vec<FpVar<F>> = params
+            .mds
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(state.iter())
+                    .fold(FpVar::zero(), |acc, (m, s)| acc + s * *m)
+            })
+            .collect();
+        state.clone_from_slice(&mixed);
+    }
+
+    Ok(())
+}
+
+/// In-circuit Poseidon hash of `inputs`, matching [`poseidon_hash`]
+pub fn poseidon_hash_gadget<F: PrimeField>(
+    params: &PoseidonParameters<F>,
+    inputs: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    if inputs.len() + 1 != params.width {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    // The first state element is the capacity and starts at zero
+    let mut state = Vec::with_capacity(params.width);
+    state.push(FpVar::zero());
+    state.extend_from_slice(inputs);
+
+    poseidon_permute_gadget(params, &mut state)?;
+    Ok(state[0].clone())
+}
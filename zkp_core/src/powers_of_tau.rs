@@ -0,0 +1,267 @@
+use ark_bn254::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{CryptoRng, RngCore, thread_rng};
+use sha2::{Digest, Sha256};
+
+use crate::ceremony::{same_ratio, transcript_point};
+use crate::error::ZkpError;
+
+// Multi-party phase-1 ("powers of tau") ceremony over BN254.
+//
+// Groth16 parameters are built from secrets tau, alpha and beta that no single party may know. Phase 1 publishes
+// `tau^i`, `alpha * tau^i` and `beta * tau^i` in the source groups, starting from the generators (all secrets
+// equal to one, known to everybody). Each participant multiplies tau, alpha and beta by fresh secrets and throws
+// them away, so the final secrets are unknown unless every participant colludes. The accumulator does not depend on
+// any circuit: `ceremony::Ceremony::new` derives the initial phase-2 parameters of a circuit from it.
+//
+// Like in phase 2, a contribution proves knowledge of each secret `x` with a random point `s`, `s_x = x * s` and
+// `r_x = x * r`, where `r` is hashed to G2 from the transcript. The contribution records tau, alpha and beta in G1
+// after the update, and the final accumulator must be made of powers of the last of them.
+
+/// Powers of the phase-1 secrets, enough for circuits whose evaluation domain has at most `size` elements
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Accumulator {
+    /// `tau^i` in G1 for `i < 2 * size - 1`
+    pub tau_g1: Vec<G1Affine>,
+    /// `tau^i` in G2 for `i < size`
+    pub tau_g2: Vec<G2Affine>,
+    /// `alpha * tau^i` in G1 for `i < size`
+    pub alpha_tau_g1: Vec<G1Affine>,
+    /// `beta * tau^i` in G1 for `i < size`
+    pub beta_tau_g1: Vec<G1Affine>,
+    /// `beta` in G2
+    pub beta_g2: G2Affine,
+}
+
+impl Accumulator {
+    /// Number of powers available in G2, i.e. the largest evaluation domain the accumulator supports
+    pub fn size(&self) -> usize {
+        self.tau_g2.len()
+    }
+}
+
+/// Proof that a participant knows the secret `x` they multiplied one of the phase-1 secrets by
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KnowledgeProof {
+    /// Random base of the proof
+    pub s: G1Affine,
+    /// `x * s`
+    pub s_x: G1Affine,
+    /// `x * r`, where `r` is derived from the previous value of the secret in G1, `s` and `s_x`
+    pub r_x: G2Affine,
+}
+
+/// One participant's update of tau, alpha and beta, with the proofs that it was applied by someone who knows the
+/// secrets
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TauContribution {
+    /// `tau` in G1 after this contribution
+    pub tau_g1: G1Affine,
+    /// `alpha` in G1 after this contribution
+    pub alpha_g1: G1Affine,
+    /// `beta` in G1 after this contribution
+    pub beta_g1: G1Affine,
+    pub tau_proof: KnowledgeProof,
+    pub alpha_proof: KnowledgeProof,
+    pub beta_proof: KnowledgeProof,
+}
+
+impl TauContribution {
+    /// Digest a participant can publish to let others check their contribution is part of the transcript
+    pub fn hash(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes).expect("writing to a Vec cannot fail");
+        Sha256::digest(&bytes).into()
+    }
+}
+
+/// Transcript of a powers-of-tau ceremony: every contribution in order and the current accumulator
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PowersOfTau {
+    pub contributions: Vec<TauContribution>,
+    pub current: Accumulator,
+}
+
+// Domain separators for the transcript points of the three proofs of knowledge
+const TAU_DOMAIN: &[u8] = b"zkp-powers-of-tau-tau";
+const ALPHA_DOMAIN: &[u8] = b"zkp-powers-of-tau-alpha";
+const BETA_DOMAIN: &[u8] = b"zkp-powers-of-tau-beta";
+
+impl PowersOfTau {
+    /// Starts a ceremony supporting evaluation domains of up to `size` elements, from the public generators
+    ///
+    /// `size` must be a power of two of at least 2.
+    pub fn new(size: usize) -> Result<Self, ZkpError> {
+        if size < 2 || !size.is_power_of_two() {
+            return Err(ZkpError::InvalidInput(format!("powers of tau size {} is not a power of two >= 2", size)));
+        }
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        let current = Accumulator {
+            tau_g1: vec![g1; 2 * size - 1],
+            tau_g2: vec![g2; size],
+            alpha_tau_g1: vec![g1; size],
+            beta_tau_g1: vec![g1; size],
+            beta_g2: g2,
+        };
+        Ok(Self { contributions: Vec::new(), current })
+    }
+
+    /// Applies a contribution with fresh randomness from `rng` and returns it. The secrets never leave this call
+    pub fn contribute<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> TauContribution {
+        let [tau, alpha, beta] = [(); 3].map(|_| nonzero_scalar(rng));
+        let before = &self.current;
+
+        let tau_proof = prove_knowledge(TAU_DOMAIN, &before.tau_g1[1], tau, rng);
+        let alpha_proof = prove_knowledge(ALPHA_DOMAIN, &before.alpha_tau_g1[0], alpha, rng);
+        let beta_proof = prove_knowledge(BETA_DOMAIN, &before.beta_tau_g1[0], beta, rng);
+
+        // Element i of every vector is multiplied by tau^i, and the alpha and beta vectors by alpha and beta too
+        let powers: Vec<Fr> = std::iter::successors(Some(Fr::from(1u64)), |power| Some(*power * tau))
+            .take(before.tau_g1.len())
+            .collect();
+        self.current = Accumulator {
+            tau_g1: scale(&before.tau_g1, &powers, Fr::from(1u64)),
+            tau_g2: scale(&before.tau_g2, &powers, Fr::from(1u64)),
+            alpha_tau_g1: scale(&before.alpha_tau_g1, &powers, alpha),
+            beta_tau_g1: scale(&before.beta_tau_g1, &powers, beta),
+            beta_g2: (before.beta_g2 * beta).into_affine(),
+        };
+
+        let contribution = TauContribution {
+            tau_g1: self.current.tau_g1[1],
+            alpha_g1: self.current.alpha_tau_g1[0],
+            beta_g1: self.current.beta_tau_g1[0],
+            tau_proof,
+            alpha_proof,
+            beta_proof,
+        };
+        self.contributions.push(contribution.clone());
+        contribution
+    }
+
+    /// Checks the whole transcript
+    ///
+    /// Every contribution must prove knowledge of its secrets and extend the previous tau, alpha and beta, and the
+    /// current accumulator must consist of the powers of the last ones.
+    pub fn verify(&self) -> Result<(), ZkpError> {
+        let generator = G1Affine::generator();
+        let mut previous = [generator; 3];
+        for (index, contribution) in self.contributions.iter().enumerate() {
+            check_contribution(&previous, contribution)
+                .map_err(|reason| ZkpError::InvalidContribution { index, reason })?;
+            previous = [contribution.tau_g1, contribution.alpha_g1, contribution.beta_g1];
+        }
+
+        let last = self.contributions.len().saturating_sub(1);
+        let invalid = |reason: String| ZkpError::InvalidContribution { index: last, reason };
+        check_shape(&self.current).map_err(invalid)?;
+        let current = &self.current;
+        if [current.tau_g1[1], current.alpha_tau_g1[0], current.beta_tau_g1[0]] != previous {
+            return Err(invalid("accumulator does not end with the last contribution's secrets".to_string()));
+        }
+        check_powers(current).map_err(invalid)
+    }
+}
+
+fn nonzero_scalar<R: RngCore + CryptoRng>(rng: &mut R) -> Fr {
+    loop {
+        let x = Fr::rand(rng);
+        if !x.is_zero() {
+            return x;
+        }
+    }
+}
+
+fn prove_knowledge<R: RngCore + CryptoRng>(domain: &[u8], previous: &G1Affine, x: Fr, rng: &mut R) -> KnowledgeProof {
+    let s = G1Projective::rand(rng).into_affine();
+    let s_x = (s * x).into_affine();
+    let r = transcript_point(domain, &[previous, &s, &s_x]);
+    KnowledgeProof { s, s_x, r_x: (r * x).into_affine() }
+}
+
+fn scale<G: AffineRepr<ScalarField = Fr>>(points: &[G], powers: &[Fr], factor: Fr) -> Vec<G> {
+    let scaled: Vec<G::Group> = points.iter().zip(powers).map(|(p, power)| *p * (*power * factor)).collect();
+    G::Group::normalize_batch(&scaled)
+}
+
+// Checks the three proofs of knowledge and that each secret in G1 was multiplied by the proven one
+fn check_contribution(previous: &[G1Affine; 3], contribution: &TauContribution) -> Result<(), String> {
+    let updates = [
+        ("tau", TAU_DOMAIN, &contribution.tau_g1, &contribution.tau_proof),
+        ("alpha", ALPHA_DOMAIN, &contribution.alpha_g1, &contribution.alpha_proof),
+        ("beta", BETA_DOMAIN, &contribution.beta_g1, &contribution.beta_proof),
+    ];
+    for ((name, domain, next, proof), previous) in updates.into_iter().zip(previous) {
+        let KnowledgeProof { s, s_x, r_x } = proof;
+        if s.is_zero() || s_x.is_zero() || next.is_zero() {
+            return Err(format!("{} update contains the identity", name));
+        }
+
+        let r = transcript_point(domain, &[previous, s, s_x]);
+        if !same_ratio((*s, *s_x), (r, *r_x)) {
+            return Err(format!("proof of knowledge for {} does not verify", name));
+        }
+        if !same_ratio((*previous, *next), (r, *r_x)) {
+            return Err(format!("{} was not updated with the proven secret", name));
+        }
+    }
+    Ok(())
+}
+
+fn check_shape(accumulator: &Accumulator) -> Result<(), String> {
+    let size = accumulator.size();
+    let consistent = size >= 2
+        && size.is_power_of_two()
+        && accumulator.tau_g1.len() == 2 * size - 1
+        && accumulator.alpha_tau_g1.len() == size
+        && accumulator.beta_tau_g1.len() == size;
+    if !consistent {
+        return Err("accumulator vectors have inconsistent lengths".to_string());
+    }
+    if accumulator.tau_g1[0] != G1Affine::generator() || accumulator.tau_g2[0] != G2Affine::generator() {
+        return Err("accumulator does not start at the generators".to_string());
+    }
+    Ok(())
+}
+
+// Checks that every vector holds consecutive powers of the same tau, with one random linear combination per vector:
+// sum(rho_i * v_{i+1}) == tau * sum(rho_i * v_i)
+fn check_powers(accumulator: &Accumulator) -> Result<(), String> {
+    let g1 = G1Affine::generator();
+    let g2 = G2Affine::generator();
+    let tau_g2 = accumulator.tau_g2[1];
+    let mut rng = thread_rng();
+
+    for (name, points) in [
+        ("tau", &accumulator.tau_g1),
+        ("alpha", &accumulator.alpha_tau_g1),
+        ("beta", &accumulator.beta_tau_g1),
+    ] {
+        let (lower, upper) = consecutive::<G1Projective>(points, &mut rng);
+        if !same_ratio((lower, upper), (g2, tau_g2)) {
+            return Err(format!("{} powers in G1 are not consecutive powers of tau", name));
+        }
+    }
+
+    let (lower, upper) = consecutive::<G2Projective>(&accumulator.tau_g2, &mut rng);
+    if !same_ratio((g1, accumulator.tau_g1[1]), (lower, upper)) {
+        return Err("tau^i in G2 are not consecutive powers of tau".to_string());
+    }
+
+    if !same_ratio((g1, accumulator.beta_tau_g1[0]), (g2, accumulator.beta_g2)) {
+        return Err("beta differs between G1 and G2".to_string());
+    }
+    Ok(())
+}
+
+// (sum(rho_i * points_i), sum(rho_i * points_{i+1})) for random rho
+fn consecutive<G: CurveGroup<ScalarField = Fr>>(points: &[G::Affine], rng: &mut impl RngCore) -> (G::Affine, G::Affine) {
+    let rho: Vec<Fr> = (1..points.len()).map(|_| Fr::rand(rng)).collect();
+    let lower = G::msm_unchecked(&points[..points.len() - 1], &rho);
+    let upper = G::msm_unchecked(&points[1..], &rho);
+    (lower.into_affine(), upper.into_affine())
+}
+
@@ -1,160 +1,332 @@
 use ark_bn254::{Bn254, Fr};
-use ark_groth16::{Groth16, Proof, VerifyingKey, ProvingKey, prepare_verifying_key};
-use ark_relations::r1cs::SynthesisError;
-use rand::thread_rng;
+use ark_ec::pairing::Pairing;
+use ark_groth16::{Groth16, Proof, VerifyingKey, prepare_verifying_key};
+use rand::{CryptoRng, RngCore, SeedableRng, thread_rng};
+use rand_chacha::ChaCha20Rng;
 
+use crate::attribute::AttributeCircuit;
 use crate::circuits::age_verification::witness_calculator::{
-    AgeVerificationCircuit, calculate_age_verification_witness,
+    AgeConfig, AgePrivateInputs, AgePublicInputs, AgeVerification,
 };
 use crate::circuits::citizenship_verification::witness_calculator::{
-    CitizenshipVerificationCircuit, calculate_citizenship_verification_witness,
+    CitizenshipPrivateInputs, CitizenshipPublicInputs, CitizenshipVerification, MerklePath,
 };
 use crate::circuits::college_credential_verification::witness_calculator::{
-    CollegeCredentialVerificationCircuit, calculate_college_credential_verification_witness,
+    CollegeCredentialPrivateInputs, CollegeCredentialPublicInputs, CollegeCredentialVerification,
 };
+use crate::circuits::date_of_birth_verification::witness_calculator::{
+    Date, DateOfBirthPrivateInputs, DateOfBirthPublicInputs, DateOfBirthVerification,
+};
+use crate::circuits::signed_age_verification::witness_calculator::{
+    SignedAgePrivateInputs, SignedAgePublicInputs, SignedAgeVerification,
+};
+use crate::eddsa::{PublicKey, Signature};
+use crate::error::ZkpError;
+use crate::keys::{CircuitProvingKey, CircuitVerifyingKey, check_fingerprint, circuit_fingerprint};
+use crate::satisfiability::{check_satisfied, first_unsatisfied};
+
+/// Performs a one-time trusted setup for any attribute circuit of the given shape on the pairing engine `E`
+pub fn setup<E: Pairing, C: AttributeCircuit<E::ScalarField>>(
+    config: &C::Config,
+) -> Result<(CircuitProvingKey<E>, CircuitVerifyingKey<E>), ZkpError> {
+    setup_with_rng::<E, C, _>(config, &mut thread_rng())
+}
+
+/// Performs the trusted setup drawing the toxic waste from a caller-supplied RNG
+pub fn setup_with_rng<E: Pairing, C: AttributeCircuit<E::ScalarField>, R: RngCore + CryptoRng>(
+    config: &C::Config,
+    rng: &mut R,
+) -> Result<(CircuitProvingKey<E>, CircuitVerifyingKey<E>), ZkpError> {
+    // Create a dummy circuit instance with arbitrary valid values, rejecting shapes the circuit cannot take
+    C::check_config(config)?;
+    let (public, private) = C::sample_inputs(config);
+    C::check_inputs(config, &public, &private)?;
+    let fingerprint = circuit_fingerprint::<E::ScalarField, C>(config)?;
+    let dummy_circuit = C::circuit(config, &public, &private);
 
-/// Performs a one-time trusted setup for the age verification circuit
-pub fn setup_age_verification_circuit() -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), SynthesisError> {
-    // Create a dummy circuit instance with arbitrary valid values
-    let dummy_circuit = AgeVerificationCircuit {
-        user_age: Some(Fr::from(20u64)),
-        min_age: Some(Fr::from(18u64)),
-    };
-
-    let mut rng = thread_rng();
-    
     // Generate parameters once to get the proving and verifying keys
-    let params = Groth16::<Bn254>::generate_random_parameters_with_reduction(dummy_circuit, &mut rng)?;
-    Ok((params.clone(), params.vk))
+    let params = Groth16::<E>::generate_random_parameters_with_reduction(dummy_circuit, rng)
+        .map_err(ZkpError::Setup)?;
+    let proving_key = CircuitProvingKey { fingerprint, key: params };
+    let verifying_key = proving_key.verifying_key();
+    Ok((proving_key, verifying_key))
+}
+
+/// Reproducible setup from a seed, for test fixtures and development keys only
+///
+/// INSECURE: anyone who knows the seed can recompute the toxic waste and forge proofs for these keys. Never use
+/// keys produced this way to verify real users.
+pub fn insecure_seeded_setup<E: Pairing, C: AttributeCircuit<E::ScalarField>>(
+    config: &C::Config,
+    seed: u64,
+) -> Result<(CircuitProvingKey<E>, CircuitVerifyingKey<E>), ZkpError> {
+    setup_with_rng::<E, C, _>(config, &mut ChaCha20Rng::seed_from_u64(seed))
+}
+
+/// Generates a proof for any attribute circuit. The shape must match the one the keys were set up with
+///
+/// Inputs that do not satisfy the circuit fail with [`ZkpError::UnsatisfiedConstraint`] naming the first violated
+/// constraint, instead of producing a proof that can never verify.
+pub fn prove<E: Pairing, C: AttributeCircuit<E::ScalarField>>(
+    proving_key: &CircuitProvingKey<E>,
+    config: &C::Config,
+    public: &C::PublicInputs,
+    private: &C::PrivateInputs,
+) -> Result<Proof<E>, ZkpError> {
+    prove_with_rng::<E, C, _>(proving_key, config, public, private, &mut thread_rng())
+}
+
+/// Generates a proof drawing its blinding factors from a caller-supplied RNG
+pub fn prove_with_rng<E: Pairing, C: AttributeCircuit<E::ScalarField>, R: RngCore + CryptoRng>(
+    proving_key: &CircuitProvingKey<E>,
+    config: &C::Config,
+    public: &C::PublicInputs,
+    private: &C::PrivateInputs,
+    rng: &mut R,
+) -> Result<Proof<E>, ZkpError> {
+    C::check_config(config)?;
+    C::check_inputs(config, public, private)?;
+    check_fingerprint::<E::ScalarField, C>(&proving_key.fingerprint, config)?;
+    check_public_input_count(&proving_key.key.vk, &C::public_inputs(public))?;
+
+    // The prover does not check the witness, so catch inputs that would give a proof that can never verify
+    if let Some(name) = first_unsatisfied(C::circuit(config, public, private))? {
+        return Err(ZkpError::UnsatisfiedConstraint { constraint: Some(name) });
+    }
+
+    // Create the circuit with public and private inputs
+    let circuit = C::circuit(config, public, private);
+
+    // Generate the Zero Knowledge Proof
+    let proof = Groth16::<E>::create_random_proof_with_reduction(circuit, &proving_key.key, rng)?;
+    Ok(proof)
+}
+
+/// Verifies a proof for any attribute circuit against its public inputs
+///
+/// Fails with [`ZkpError::KeyCircuitMismatch`] when the key was generated for another circuit, version or shape.
+pub fn verify<E: Pairing, C: AttributeCircuit<E::ScalarField>>(
+    vk: &CircuitVerifyingKey<E>,
+    config: &C::Config,
+    proof: &Proof<E>,
+    public: &C::PublicInputs,
+) -> Result<bool, ZkpError> {
+    check_fingerprint::<E::ScalarField, C>(&vk.fingerprint, config)?;
+    let public_inputs = C::public_inputs(public);
+    check_public_input_count(&vk.key, &public_inputs)?;
+
+    let pvk = prepare_verifying_key(&vk.key);
+    Ok(Groth16::<E>::verify_proof(&pvk, proof, &public_inputs)?)
+}
+
+// A key made for another circuit (or shape) usually expects a different number of public inputs
+pub(crate) fn check_public_input_count<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    public_inputs: &[E::ScalarField],
+) -> Result<(), ZkpError> {
+    let expected = vk.gamma_abc_g1.len() - 1;
+    if public_inputs.len() != expected {
+        return Err(ZkpError::KeyCircuitMismatch(format!(
+            "the key expects {} public inputs but the circuit has {}",
+            expected,
+            public_inputs.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Performs a one-time trusted setup for the age verification circuit
+///
+/// Ages are decomposed into `num_bits` bits; when `upper_bounded` is set the circuit also takes `max_age` as a
+/// public input and proves `min_age <= user_age <= max_age`. Proofs must be generated with the same choices.
+pub fn setup_age_verification_circuit(
+    num_bits: usize,
+    upper_bounded: bool,
+) -> Result<(CircuitProvingKey, CircuitVerifyingKey), ZkpError> {
+    setup::<Bn254, AgeVerification>(&AgeConfig { num_bits, upper_bounded })
 }
 
-/// Generates a proof for the circuit given a user's age and the minimum age
+/// Generates a proof for the circuit given a user's age, the minimum age and the optional maximum age
 pub fn prove_age(
-    proving_key: &ProvingKey<Bn254>,
+    proving_key: &CircuitProvingKey,
+    nonce: Fr,
+    num_bits: usize,
     user_age: u64,
     min_age: u64,
-) -> Result<Proof<Bn254>, SynthesisError> {
-    // Returns the age verification circuit
-    let circuit = calculate_age_verification_witness(user_age, min_age);
-    
-    let mut rng = thread_rng();
-
-    //Generate the Zero Knowledge Proof
-    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit, proving_key, &mut rng)?;
-    Ok(proof)
+    max_age: Option<u64>,
+) -> Result<Proof<Bn254>, ZkpError> {
+    let config = AgeConfig { num_bits, upper_bounded: max_age.is_some() };
+    let public = AgePublicInputs { nonce, min_age, max_age };
+    prove::<Bn254, AgeVerification>(proving_key, &config, &public, &AgePrivateInputs { user_age })
 }
 
-/// Verifies a given proof using the verifying key and the public input (min_age)
+/// Checks that a user's age satisfies the age circuit without proving, naming the failing constraint if it does not
+pub fn check_age_inputs(
+    nonce: Fr,
+    num_bits: usize,
+    user_age: u64,
+    min_age: u64,
+    max_age: Option<u64>,
+) -> Result<(), ZkpError> {
+    let config = AgeConfig { num_bits, upper_bounded: max_age.is_some() };
+    let public = AgePublicInputs { nonce, min_age, max_age };
+    check_satisfied::<Fr, AgeVerification>(&config, &public, &AgePrivateInputs { user_age })
+}
+
+/// Verifies a given proof using the verifying key and the public inputs (nonce, min_age, and max_age when bounded)
 pub fn verify_age(
-    vk: &VerifyingKey<Bn254>,
+    vk: &CircuitVerifyingKey,
     proof: &Proof<Bn254>,
+    nonce: Fr,
+    num_bits: usize,
     min_age: u64,
-) -> Result<bool, SynthesisError> {
-    let pvk = prepare_verifying_key(vk);
-    let public_input = Fr::from(min_age);
-    Ok(Groth16::<Bn254>::verify_proof(&pvk, proof, &[public_input]))?
+    max_age: Option<u64>,
+) -> Result<bool, ZkpError> {
+    let config = AgeConfig { num_bits, upper_bounded: max_age.is_some() };
+    verify::<Bn254, AgeVerification>(vk, &config, proof, &AgePublicInputs { nonce, min_age, max_age })
 }
 
-// Does a one time setup to generate proving and verifying keys for CitizenshipVerifictionCircuit
-pub fn setup_citizenship_verification_circuit() -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), SynthesisError> {
-    // Generate a sample Merkle root (public input)
-    let merkle_root = Some(Fr::from(123456u64));
+/// Performs a one-time trusted setup for the date of birth based age verification circuit
+pub fn setup_date_of_birth_verification_circuit() -> Result<(CircuitProvingKey, CircuitVerifyingKey), ZkpError> {
+    setup::<Bn254, DateOfBirthVerification>(&())
+}
 
-    // Generate a dummy leaf node (hashed user ID)
-    let leaf = Some(Fr::from(98765u64));
+/// Generates a proof that someone born on `birth_date` is at least `min_age` years old on `current_date`
+pub fn prove_date_of_birth(
+    proving_key: &CircuitProvingKey,
+    nonce: Fr,
+    birth_date: Date,
+    current_date: Date,
+    min_age: u64,
+) -> Result<Proof<Bn254>, ZkpError> {
+    let public = DateOfBirthPublicInputs { nonce, current_date, min_age };
+    prove::<Bn254, DateOfBirthVerification>(proving_key, &(), &public, &DateOfBirthPrivateInputs { birth_date })
+}
+
+/// Verifies a date of birth proof using the public inputs (nonce, current date, then min_age)
+pub fn verify_date_of_birth(
+    vk: &CircuitVerifyingKey,
+    proof: &Proof<Bn254>,
+    nonce: Fr,
+    current_date: Date,
+    min_age: u64,
+) -> Result<bool, ZkpError> {
+    verify::<Bn254, DateOfBirthVerification>(vk, &(), proof, &DateOfBirthPublicInputs { nonce, current_date, min_age })
+}
 
-    // Generate a dummy Merkle proof (Merkle path)
-    let path = Some(Fr::from(98765u64));
-    
-    // Sets up a dummy circuit
-    let dummy_circuit = CitizenshipVerificationCircuit{
-        merkle_root,
-        path,
-        leaf,
-    };
+/// Performs a one-time trusted setup for the issuer-signed age verification circuit
+pub fn setup_signed_age_verification_circuit() -> Result<(CircuitProvingKey, CircuitVerifyingKey), ZkpError> {
+    setup::<Bn254, SignedAgeVerification>(&())
+}
 
-    // Get random variable
-    let mut rng = thread_rng();
+/// Generates a proof that an issuer-signed date of birth makes the holder at least `min_age` on `current_date`
+///
+/// `credential` holds the date of birth, the holder secret and the issuer's signature over both.
+pub fn prove_signed_age(
+    proving_key: &CircuitProvingKey,
+    nonce: Fr,
+    issuer_public_key: &PublicKey,
+    credential: &SignedAgePrivateInputs,
+    current_date: Date,
+    min_age: u64,
+) -> Result<Proof<Bn254>, ZkpError> {
+    let public = SignedAgePublicInputs { nonce, issuer_public_key: *issuer_public_key, current_date, min_age };
+    prove::<Bn254, SignedAgeVerification>(proving_key, &(), &public, credential)
+}
 
-    // Generate proving and verifying keys
-    let params = Groth16::<Bn254>::generate_random_parameters_with_reduction(dummy_circuit, &mut rng)?;
-    Ok((params.clone(), params.vk))
+/// Verifies a signed age proof against the issuer public key, the current date and the age threshold
+pub fn verify_signed_age(
+    vk: &CircuitVerifyingKey,
+    proof: &Proof<Bn254>,
+    nonce: Fr,
+    issuer_public_key: &PublicKey,
+    current_date: Date,
+    min_age: u64,
+) -> Result<bool, ZkpError> {
+    let public = SignedAgePublicInputs { nonce, issuer_public_key: *issuer_public_key, current_date, min_age };
+    verify::<Bn254, SignedAgeVerification>(vk, &(), proof, &public)
 }
 
-// ZKP proof generator for citizenship status
+// Does a one time setup to generate proving and verifying keys for CitizenshipVerifictionCircuit of the given tree depth
+pub fn setup_citizenship_verification_circuit(depth: usize) -> Result<(CircuitProvingKey, CircuitVerifyingKey), ZkpError> {
+    setup::<Bn254, CitizenshipVerification>(&depth)
+}
+
+// ZKP proof generator for citizenship status. The depth must match the depth the keys were set up with
 pub fn prove_citizenship(
-    proving_key: &ProvingKey<Bn254>,
-    merkle_root: Option<Fr>,
-    path: Option<Fr>,
-    leaf: Option<Fr>,
-) -> Result<Proof<Bn254>, SynthesisError> {
-    // Create the circuit with public and private inputs
-    let circuit = calculate_citizenship_verification_witness(merkle_root, path, leaf);
-    
-    // Get random variable
-    let mut rng = thread_rng();
-    
-    // Generate the proof
-    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit, proving_key, &mut rng)?;
-    Ok(proof)
+    proving_key: &CircuitProvingKey,
+    nonce: Fr,
+    depth: usize,
+    merkle_root: Fr,
+    leaf: Fr,
+    path: MerklePath,
+) -> Result<Proof<Bn254>, ZkpError> {
+    let public = CitizenshipPublicInputs { nonce, merkle_root };
+    prove::<Bn254, CitizenshipVerification>(proving_key, &depth, &public, &CitizenshipPrivateInputs { leaf, path })
 }
 
-// Verify a given proof of a user's citizenship status
+// Checks that a leaf and path reach the root without proving, naming the failing constraint if they do not
+pub fn check_citizenship_inputs(
+    nonce: Fr,
+    depth: usize,
+    merkle_root: Fr,
+    leaf: Fr,
+    path: MerklePath,
+) -> Result<(), ZkpError> {
+    let public = CitizenshipPublicInputs { nonce, merkle_root };
+    check_satisfied::<Fr, CitizenshipVerification>(&depth, &public, &CitizenshipPrivateInputs { leaf, path })
+}
+
+// Verify a given proof of a user's citizenship status. The depth must match the depth the keys were set up with
 pub fn verify_citizenship(
-    vk: &VerifyingKey<Bn254>,
+    vk: &CircuitVerifyingKey,
     proof: &Proof<Bn254>,
-    merkle_root: Fr, 
-) -> Result<bool, SynthesisError> {
-    // Prepare the verifying key
-    let pvk = prepare_verifying_key(vk);
-
-    // Check the given proof with public parameters
-    Ok(Groth16::<Bn254>::verify_proof(&pvk, proof, &[merkle_root]))?
+    nonce: Fr,
+    depth: usize,
+    merkle_root: Fr,
+) -> Result<bool, ZkpError> {
+    verify::<Bn254, CitizenshipVerification>(vk, &depth, proof, &CitizenshipPublicInputs { nonce, merkle_root })
 }
 
 /// Performs a one-time trusted setup for the college credential verification circuit
-pub fn setup_credential_verification_circuit() -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), SynthesisError> {
-    // Create a dummy circuit instance with arbitrary valid values
-    let dummy_circuit = CollegeCredentialVerificationCircuit {
-        university_public_key: Some(Fr::from(20u64)),
-        credential: Some(Fr::from(18u64)),
-        signature: Some(Fr::from(2u64)),
-    };
-
-    let mut rng = thread_rng();
-    
-    // Generate parameters once to get the proving and verifying keys
-    let params = Groth16::<Bn254>::generate_random_parameters_with_reduction(dummy_circuit, &mut rng)?;
-    Ok((params.clone(), params.vk))
+pub fn setup_credential_verification_circuit() -> Result<(CircuitProvingKey, CircuitVerifyingKey), ZkpError> {
+    setup::<Bn254, CollegeCredentialVerification>(&())
 }
 
 // ZKP proof generator for college credential status
 pub fn prove_college_credential(
-    proving_key: &ProvingKey<Bn254>,
-    university_public_key: Option<Fr>,
-    credential: Option<Fr>,
-    signature: Option<Fr>,
-) -> Result<Proof<Bn254>, SynthesisError> {
-    // Create the circuit with public and private inputs
-    let circuit = calculate_college_credential_verification_witness(university_public_key, credential, signature);
-    
-    // Get random variable
-    let mut rng = thread_rng();
-    
-    // Generate the proof
-    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit, proving_key, &mut rng)?;
-    Ok(proof)
+    proving_key: &CircuitProvingKey,
+    nonce: Fr,
+    university_public_key: &PublicKey,
+    credential: Fr,
+    holder_secret: Fr,
+    signature: &Signature,
+) -> Result<Proof<Bn254>, ZkpError> {
+    let public = CollegeCredentialPublicInputs { nonce, university_public_key: *university_public_key };
+    let private = CollegeCredentialPrivateInputs { credential, holder_secret, signature: *signature };
+    prove::<Bn254, CollegeCredentialVerification>(proving_key, &(), &public, &private)
+}
+
+// Checks that a credential signature is valid without proving, naming the failing constraint if it is not
+pub fn check_college_credential_inputs(
+    nonce: Fr,
+    university_public_key: &PublicKey,
+    credential: Fr,
+    holder_secret: Fr,
+    signature: &Signature,
+) -> Result<(), ZkpError> {
+    let public = CollegeCredentialPublicInputs { nonce, university_public_key: *university_public_key };
+    let private = CollegeCredentialPrivateInputs { credential, holder_secret, signature: *signature };
+    check_satisfied::<Fr, CollegeCredentialVerification>(&(), &public, &private)
 }
 
 // Verify a given proof of a user's college credential status
 pub fn verify_college_credential(
-    vk: &VerifyingKey<Bn254>,
+    vk: &CircuitVerifyingKey,
     proof: &Proof<Bn254>,
-    university_public_key: Fr, 
-) -> Result<bool, SynthesisError> {
-    // Prepare the verifying key
-    let pvk = prepare_verifying_key(vk);
-
-    // Check the given proof with public parameters
-    Ok(Groth16::<Bn254>::verify_proof(&pvk, proof, &[university_public_key]))?
+    nonce: Fr,
+    university_public_key: &PublicKey,
+) -> Result<bool, ZkpError> {
+    let public = CollegeCredentialPublicInputs { nonce, university_public_key: *university_public_key };
+    verify::<Bn254, CollegeCredentialVerification>(vk, &(), proof, &public)
 }
@@ -0,0 +1,152 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+#[cfg(feature = "experimental-marlin")]
+use ark_bn254::Fr;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_groth16::Proof;
+use rand::{CryptoRng, RngCore};
+use std::marker::PhantomData;
+
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+use crate::keys::{CircuitProvingKey, CircuitVerifyingKey};
+#[cfg(feature = "experimental-marlin")]
+use crate::marlin::{self, MarlinProof, MarlinProvingKey, MarlinVerifyingKey};
+use crate::proof_system;
+
+// Proving systems an attribute circuit can be proven under. Code written against `ProvingSystem` works for any
+// of them, since every attribute circuit is a plain `ConstraintSynthesizer` and nothing about it is specific to
+// one backend.
+//
+// Groth16 needs a circuit-specific setup for every circuit and shape. Marlin derives keys for any circuit from one
+// universal powers of tau, so a new circuit or shape (e.g. another Merkle depth) needs no new ceremony, at the cost
+// of larger proofs and slower verification. The key file format records which of the two a file belongs to.
+// Marlin is implemented in this crate and unaudited, so it is only built with the `experimental-marlin` feature.
+
+/// A zkSNARK that attribute circuits over `Self::Field` can be set up, proven and verified with
+pub trait ProvingSystem {
+    /// Stable name of the proving system and curve
+    const NAME: &'static str;
+
+    /// Scalar field the circuits are expressed over
+    type Field: PrimeField;
+    type ProvingKey;
+    type VerifyingKey;
+    type Proof;
+
+    /// Generates keys for circuit `C` of the given shape
+    fn setup<C: AttributeCircuit<Self::Field>, R: RngCore + CryptoRng>(
+        config: &C::Config,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), ZkpError>;
+
+    /// Proves that the private inputs satisfy circuit `C` for the public ones
+    fn prove<C: AttributeCircuit<Self::Field>, R: RngCore + CryptoRng>(
+        pk: &Self::ProvingKey,
+        config: &C::Config,
+        public: &C::PublicInputs,
+        private: &C::PrivateInputs,
+        rng: &mut R,
+    ) -> Result<Self::Proof, ZkpError>;
+
+    /// Checks a proof for circuit `C` against its public inputs
+    fn verify<C: AttributeCircuit<Self::Field>>(
+        vk: &Self::VerifyingKey,
+        config: &C::Config,
+        proof: &Self::Proof,
+        public: &C::PublicInputs,
+    ) -> Result<bool, ZkpError>;
+}
+
+/// Groth16 over the pairing engine `E`: smallest proofs and fastest verification, but every circuit shape needs its
+/// own setup
+pub struct Groth16System<E: Pairing>(PhantomData<E>);
+
+/// Groth16 over BN254, the curve with precompiles on Ethereum
+pub type Groth16Bn254 = Groth16System<Bn254>;
+
+/// Groth16 over BLS12-381, for a higher security margin than BN254 at the cost of larger keys and proofs
+pub type Groth16Bls12_381 = Groth16System<Bls12_381>;
+
+macro_rules! impl_groth16 {
+    ($curve:ty, $name:literal) => {
+        impl ProvingSystem for Groth16System<$curve> {
+            const NAME: &'static str = $name;
+
+            type Field = <$curve as Pairing>::ScalarField;
+            type ProvingKey = CircuitProvingKey<$curve>;
+            type VerifyingKey = CircuitVerifyingKey<$curve>;
+            type Proof = Proof<$curve>;
+
+            fn setup<C: AttributeCircuit<Self::Field>, R: RngCore + CryptoRng>(
+                config: &C::Config,
+                rng: &mut R,
+            ) -> Result<(Self::ProvingKey, Self::VerifyingKey), ZkpError> {
+                proof_system::setup_with_rng::<$curve, C, R>(config, rng)
+            }
+
+            fn prove<C: AttributeCircuit<Self::Field>, R: RngCore + CryptoRng>(
+                pk: &Self::ProvingKey,
+                config: &C::Config,
+                public: &C::PublicInputs,
+                private: &C::PrivateInputs,
+                rng: &mut R,
+            ) -> Result<Self::Proof, ZkpError> {
+                proof_system::prove_with_rng::<$curve, C, R>(pk, config, public, private, rng)
+            }
+
+            fn verify<C: AttributeCircuit<Self::Field>>(
+                vk: &Self::VerifyingKey,
+                config: &C::Config,
+                proof: &Self::Proof,
+                public: &C::PublicInputs,
+            ) -> Result<bool, ZkpError> {
+                proof_system::verify::<$curve, C>(vk, config, proof, public)
+            }
+        }
+    };
+}
+
+impl_groth16!(Bn254, "groth16-bn254");
+impl_groth16!(Bls12_381, "groth16-bls12-381");
+
+/// Marlin over BN254 with KZG commitments: keys for every circuit are derived from one universal powers of tau
+#[cfg(feature = "experimental-marlin")]
+pub struct MarlinBn254;
+
+#[cfg(feature = "experimental-marlin")]
+impl ProvingSystem for MarlinBn254 {
+    const NAME: &'static str = "marlin-bn254";
+
+    type Field = Fr;
+    type ProvingKey = MarlinProvingKey;
+    type VerifyingKey = MarlinVerifyingKey;
+    type Proof = MarlinProof;
+
+    fn setup<C: AttributeCircuit<Fr>, R: RngCore + CryptoRng>(
+        config: &C::Config,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), ZkpError> {
+        marlin::setup_with_rng::<C, R>(config, rng)
+    }
+
+    fn prove<C: AttributeCircuit<Fr>, R: RngCore + CryptoRng>(
+        pk: &Self::ProvingKey,
+        config: &C::Config,
+        public: &C::PublicInputs,
+        private: &C::PrivateInputs,
+        rng: &mut R,
+    ) -> Result<Self::Proof, ZkpError> {
+        marlin::prove_with_rng::<C, R>(pk, config, public, private, rng)
+    }
+
+    fn verify<C: AttributeCircuit<Fr>>(
+        vk: &Self::VerifyingKey,
+        config: &C::Config,
+        proof: &Self::Proof,
+        public: &C::PublicInputs,
+    ) -> Result<bool, ZkpError> {
+        marlin::verify::<C>(vk, config, proof, public)
+    }
+}
@@ -0,0 +1,60 @@
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::SynthesisError;
+
+// Range checks by bit decomposition. Values are split into `num_bits` boolean witnesses whose weighted sum
+// must equal the original value, which is only satisfiable when the value lies in [0, 2^num_bits).
+// Comparisons rely on this: when a and b are both below 2^num_bits, b - a fits in num_bits bits exactly
+// when a <= b, because a negative difference wraps around to a value close to the field modulus.
+
+/// Largest supported bit width, keeping every range far below the field modulus
+pub const MAX_RANGE_BITS: usize = 64;
+
+/// Decomposes `value` into `num_bits` little-endian bits, enforcing `0 <= value < 2^num_bits`
+pub fn enforce_in_range_gadget<F: PrimeField>(
+    value: &FpVar<F>,
+    num_bits: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    if num_bits == 0 || num_bits > MAX_RANGE_BITS {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    // Constants need no witnesses, but must still be in range
+    if let FpVar::Constant(c) = value {
+        let bits = c.into_bigint().to_bits_le();
+        if bits[num_bits..].iter().any(|b| *b) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        return Ok(bits[..num_bits].iter().map(|b| Boolean::constant(*b)).collect());
+    }
+
+    let cs = value.cs();
+    let value_bits = value.value().map(|v| v.into_bigint().to_bits_le());
+
+    let bits = (0..num_bits)
+        .map(|i| {
+            Boolean::new_witness(cs.clone(), || {
+                value_bits
+                    .as_ref()
+                    .map(|b| b[i])
+                    .map_err(|_| SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // sum(bit_i * 2^i) == value
+    let recomposed = Boolean::le_bits_to_fp_var(&bits)?;
+    recomposed.enforce_equal(value)?;
+
+    Ok(bits)
+}
+
+/// Enforces `lower <= upper`, assuming both are already known to be below `2^num_bits`
+pub fn enforce_less_or_equal_gadget<F: PrimeField>(
+    lower: &FpVar<F>,
+    upper: &FpVar<F>,
+    num_bits: usize,
+) -> Result<(), SynthesisError> {
+    enforce_in_range_gadget(&(upper - lower), num_bits)?;
+    Ok(())
+}
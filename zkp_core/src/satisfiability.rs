@@ -0,0 +1,90 @@
+use ark_ff::PrimeField;
+use ark_relations::r1cs::{
+    ConstraintLayer, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, ConstraintTrace, TracingMode,
+};
+use tracing_subscriber::Registry;
+use tracing_subscriber::layer::SubscriberExt;
+
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+
+// Satisfiability pre-check. The Groth16 prover never checks the witness: debug builds panic on an unsatisfied
+// constraint and release builds return a proof that can never verify. Proving therefore synthesizes the circuit on
+// its own first and evaluates every constraint, turning bad inputs into an error that names the first failing one.
+//
+// A constraint is named after the `ns!` namespaces open when it was created, joined with `/`, e.g.
+// `user_age_at_least_min_age/range`. arkworks records these as constraint traces whenever the tracing subscriber
+// has a `ConstraintLayer`. Applications that want their own subscriber to see synthesis can add the layer to it;
+// otherwise a registry with just the layer is installed for the duration of the synthesis.
+
+/// Checks that the inputs satisfy circuit `C` without proving anything
+///
+/// Fails with [`ZkpError::UnsatisfiedConstraint`] naming the first constraint the inputs violate.
+pub fn check_satisfied<F: PrimeField, C: AttributeCircuit<F>>(
+    config: &C::Config,
+    public: &C::PublicInputs,
+    private: &C::PrivateInputs,
+) -> Result<(), ZkpError> {
+    C::check_config(config)?;
+    C::check_inputs(config, public, private)?;
+    match first_unsatisfied(C::circuit(config, public, private))? {
+        Some(name) => Err(ZkpError::UnsatisfiedConstraint { constraint: Some(name) }),
+        None => Ok(()),
+    }
+}
+
+/// Synthesizes a circuit with its assignment and returns the name of the first constraint it violates, if any
+///
+/// Constraints created outside any namespace are named by their index, e.g. `#12`.
+pub fn first_unsatisfied<F: PrimeField, S: ConstraintSynthesizer<F>>(circuit: S) -> Result<Option<String>, ZkpError> {
+    let cs = ConstraintSystem::<F>::new_ref();
+    if records_constraint_traces() {
+        circuit.generate_constraints(cs.clone())?;
+    } else {
+        let subscriber = Registry::default().with(ConstraintLayer::new(TracingMode::OnlyConstraints));
+        tracing::subscriber::with_default(subscriber, || circuit.generate_constraints(cs.clone()))?;
+    }
+
+    let Some(index) = first_violated(&cs)? else {
+        return Ok(None);
+    };
+    let path = cs.which_is_unsatisfied()?.and_then(|trace| namespace_path(&trace));
+    Ok(Some(path.unwrap_or_else(|| format!("#{}", index))))
+}
+
+// Whether the current subscriber has a `ConstraintLayer`, probed with a throwaway namespace
+fn records_constraint_traces() -> bool {
+    let _probe = tracing::info_span!(target: "r1cs", "probe").entered();
+    ConstraintTrace::capture().is_some_and(|trace| !trace.path().is_empty())
+}
+
+// `which_is_unsatisfied` lists the spans open when the constraint was created, innermost first, as `N: module::name`
+// lines each followed by an `at file:line` line. Only namespaces opened outside arkworks' own gadgets count.
+fn namespace_path(trace: &str) -> Option<String> {
+    let mut names: Vec<&str> = trace
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("at "))
+        .filter_map(|line| line.split_once(": ")?.1.rsplit_once("::"))
+        .filter(|(module, _)| !module.starts_with("ark_"))
+        .map(|(_, name)| name)
+        .collect();
+    names.reverse();
+    (!names.is_empty()).then(|| names.join("/"))
+}
+
+// Index of the first constraint the assignment violates
+fn first_violated<F: PrimeField>(cs: &ConstraintSystemRef<F>) -> Result<Option<usize>, ZkpError> {
+    cs.finalize();
+    let matrices = cs
+        .to_matrices()
+        .ok_or_else(|| ZkpError::InvalidInput("constraint matrices are unavailable".to_string()))?;
+    let assignment: Vec<F> = {
+        let inner = cs.borrow().ok_or_else(|| ZkpError::InvalidInput("the constraint system is empty".to_string()))?;
+        inner.instance_assignment.iter().chain(&inner.witness_assignment).copied().collect()
+    };
+
+    let evaluate = |row: &[(F, usize)]| -> F { row.iter().map(|(coefficient, index)| *coefficient * assignment[*index]).sum() };
+    Ok((0..matrices.num_constraints)
+        .find(|i| evaluate(&matrices.a[*i]) * evaluate(&matrices.b[*i]) != evaluate(&matrices.c[*i])))
+}
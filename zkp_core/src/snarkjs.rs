@@ -0,0 +1,179 @@
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_groth16::{Proof, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZkpError;
+
+// snarkjs interop. snarkjs and circom tooling exchange Groth16 objects as JSON: `verification_key.json`,
+// `proof.json` and `public.json`. Every number is a decimal string and points are projective, affine ones with
+// z = 1 and the point at infinity as (0, 1, 0). G2 coordinates are written real part first, the reverse of the
+// order the EVM precompiles take. snarkjs calls BN254 "bn128".
+//
+// Importing checks that every coordinate is a canonical field element and that every point is on the curve and in
+// the prime order subgroup, like the `key_file` decoders do. The `vk_alphabeta_12` precomputation snarkjs adds to
+// verifying keys is neither written nor read; it is derived from `vk_alpha_1` and `vk_beta_2`.
+
+const PROTOCOL: &str = "groth16";
+const CURVE: &str = "bn128";
+
+/// A G1 point in snarkjs form: `[x, y, z]`
+pub type G1Json = [String; 3];
+/// A G2 point in snarkjs form: `[[x.c0, x.c1], [y.c0, y.c1], [z.c0, z.c1]]`
+pub type G2Json = [[String; 2]; 3];
+
+/// Contents of a snarkjs `verification_key.json`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnarkjsVerifyingKey {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: usize,
+    pub vk_alpha_1: G1Json,
+    pub vk_beta_2: G2Json,
+    pub vk_gamma_2: G2Json,
+    pub vk_delta_2: G2Json,
+    #[serde(rename = "IC")]
+    pub ic: Vec<G1Json>,
+}
+
+/// Contents of a snarkjs `proof.json`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnarkjsProof {
+    pub pi_a: G1Json,
+    pub pi_b: G2Json,
+    pub pi_c: G1Json,
+    pub protocol: String,
+    pub curve: String,
+}
+
+/// Converts a verifying key to its snarkjs form
+pub fn export_verifying_key(vk: &VerifyingKey<Bn254>) -> SnarkjsVerifyingKey {
+    SnarkjsVerifyingKey {
+        protocol: PROTOCOL.to_string(),
+        curve: CURVE.to_string(),
+        n_public: vk.gamma_abc_g1.len().saturating_sub(1),
+        vk_alpha_1: export_g1(&vk.alpha_g1),
+        vk_beta_2: export_g2(&vk.beta_g2),
+        vk_gamma_2: export_g2(&vk.gamma_g2),
+        vk_delta_2: export_g2(&vk.delta_g2),
+        ic: vk.gamma_abc_g1.iter().map(export_g1).collect(),
+    }
+}
+
+/// Reads a verifying key from its snarkjs form
+pub fn import_verifying_key(json: &SnarkjsVerifyingKey) -> Result<VerifyingKey<Bn254>, ZkpError> {
+    check_header(&json.protocol, &json.curve)?;
+    if json.ic.len() != json.n_public + 1 {
+        return Err(ZkpError::InvalidFile(format!(
+            "the key declares {} public inputs but has {} IC points",
+            json.n_public,
+            json.ic.len()
+        )));
+    }
+
+    Ok(VerifyingKey {
+        alpha_g1: import_g1(&json.vk_alpha_1)?,
+        beta_g2: import_g2(&json.vk_beta_2)?,
+        gamma_g2: import_g2(&json.vk_gamma_2)?,
+        delta_g2: import_g2(&json.vk_delta_2)?,
+        gamma_abc_g1: json.ic.iter().map(import_g1).collect::<Result<_, _>>()?,
+    })
+}
+
+/// Converts a proof to its snarkjs form
+pub fn export_proof(proof: &Proof<Bn254>) -> SnarkjsProof {
+    SnarkjsProof {
+        pi_a: export_g1(&proof.a),
+        pi_b: export_g2(&proof.b),
+        pi_c: export_g1(&proof.c),
+        protocol: PROTOCOL.to_string(),
+        curve: CURVE.to_string(),
+    }
+}
+
+/// Reads a proof from its snarkjs form
+pub fn import_proof(json: &SnarkjsProof) -> Result<Proof<Bn254>, ZkpError> {
+    check_header(&json.protocol, &json.curve)?;
+    Ok(Proof { a: import_g1(&json.pi_a)?, b: import_g2(&json.pi_b)?, c: import_g1(&json.pi_c)? })
+}
+
+/// Converts public inputs to the contents of a snarkjs `public.json`
+pub fn export_public_inputs(public_inputs: &[Fr]) -> Vec<String> {
+    public_inputs.iter().map(|x| x.into_bigint().to_string()).collect()
+}
+
+/// Reads public inputs from the contents of a snarkjs `public.json`
+pub fn import_public_inputs(json: &[String]) -> Result<Vec<Fr>, ZkpError> {
+    json.iter()
+        .map(|value| field(value).ok_or_else(|| ZkpError::InvalidInput(format!("`{}` is not a field element", value))))
+        .collect()
+}
+
+fn check_header(protocol: &str, curve: &str) -> Result<(), ZkpError> {
+    if protocol != PROTOCOL || curve != CURVE {
+        return Err(ZkpError::InvalidFile(format!(
+            "expected a {} object on {}, got {} on {}",
+            PROTOCOL, CURVE, protocol, curve
+        )));
+    }
+    Ok(())
+}
+
+// Only canonical decimals: `from_str` would silently reduce a value past the modulus
+fn field<F: PrimeField>(value: &str) -> Option<F> {
+    let x = F::from_str(value).ok()?;
+    (x.into_bigint().to_string() == value).then_some(x)
+}
+
+fn coordinate(value: &str) -> Result<Fq, ZkpError> {
+    field(value).ok_or_else(|| ZkpError::InvalidFile(format!("`{}` is not a base field element", value)))
+}
+
+fn export_g1(p: &G1Affine) -> G1Json {
+    match p.xy() {
+        Some((x, y)) => [x.into_bigint().to_string(), y.into_bigint().to_string(), "1".to_string()],
+        None => ["0", "1", "0"].map(String::from),
+    }
+}
+
+fn export_g2(p: &G2Affine) -> G2Json {
+    let pair = |x: &Fq2| [x.c0.into_bigint().to_string(), x.c1.into_bigint().to_string()];
+    match p.xy() {
+        Some((x, y)) => [pair(x), pair(y), ["1", "0"].map(String::from)],
+        None => [["0", "0"], ["1", "0"], ["0", "0"]].map(|c| c.map(String::from)),
+    }
+}
+
+// The point at infinity has a single encoding, the one `export_g1` writes, so no other bytes decode to it
+fn import_g1(json: &G1Json) -> Result<G1Affine, ZkpError> {
+    if *json == export_g1(&G1Affine::zero()) {
+        return Ok(G1Affine::zero());
+    }
+    let [x, y, z] = json;
+    if z != "1" {
+        return Err(ZkpError::InvalidFile("G1 points must be affine, with z = 1, or the point at infinity".to_string()));
+    }
+    let p = G1Affine::new_unchecked(coordinate(x)?, coordinate(y)?);
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(ZkpError::InvalidFile("a G1 point is not in the prime order subgroup".to_string()));
+    }
+    Ok(p)
+}
+
+fn import_g2(json: &G2Json) -> Result<G2Affine, ZkpError> {
+    if *json == export_g2(&G2Affine::zero()) {
+        return Ok(G2Affine::zero());
+    }
+    let pair = |c: &[String; 2]| Ok::<_, ZkpError>(Fq2::new(coordinate(&c[0])?, coordinate(&c[1])?));
+    let [x, y, z] = json;
+    if *z != ["1", "0"] {
+        return Err(ZkpError::InvalidFile("G2 points must be affine, with z = 1, or the point at infinity".to_string()));
+    }
+    let p = G2Affine::new_unchecked(pair(x)?, pair(y)?);
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(ZkpError::InvalidFile("a G2 point is not in the prime order subgroup".to_string()));
+    }
+    Ok(p)
+}
@@ -0,0 +1,191 @@
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use sha3::{Digest, Keccak256};
+use std::fmt::Write;
+
+use crate::error::ZkpError;
+
+// On-chain verification. A Groth16 verifying key is rendered into a standalone Solidity contract that checks proofs
+// with the BN254 precompiles every EVM chain provides: ecAdd (0x06) and ecMul (0x07) combine the public inputs with
+// the key's IC points, and the pairing precompile (0x08) checks
+//
+//   e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+//
+// The contract exposes `verifyProof(uint256[2] a, uint256[2][2] b, uint256[2] c, uint256[n] input)`, the layout
+// snarkjs verifiers use, so existing tooling can call it. G2 coordinates are written imaginary part first, as the
+// precompile expects, and points at infinity as zeros. Only static arrays are involved, so the ABI encoding of a
+// call is the selector followed by every word in order, which is what `calldata` produces.
+
+/// Renders a Solidity contract named `contract_name` verifying proofs against `vk`
+pub fn verifier_contract(contract_name: &str, vk: &VerifyingKey<Bn254>) -> Result<String, ZkpError> {
+    if !is_identifier(contract_name) {
+        return Err(ZkpError::InvalidInput(format!("`{}` is not a valid contract name", contract_name)));
+    }
+    // Solidity has no zero-length arrays, and every attribute circuit takes at least its nonce
+    let num_inputs = vk.gamma_abc_g1.len().saturating_sub(1);
+    if num_inputs == 0 {
+        return Err(ZkpError::InvalidInput("the verifying key has no public inputs".to_string()));
+    }
+
+    let mut constants = String::new();
+    g1_constants(&mut constants, "ALPHA", &vk.alpha_g1);
+    g2_constants(&mut constants, "BETA", &vk.beta_g2);
+    g2_constants(&mut constants, "GAMMA", &vk.gamma_g2);
+    g2_constants(&mut constants, "DELTA", &vk.delta_g2);
+    for (i, ic) in vk.gamma_abc_g1.iter().enumerate() {
+        g1_constants(&mut constants, &format!("IC{}", i), ic);
+    }
+
+    let mut accumulate = String::new();
+    for i in 0..num_inputs {
+        let ic = i + 1;
+        let _ = writeln!(accumulate, "        if (input[{i}] >= R) return false;");
+        let _ = writeln!(accumulate, "        vkX = ecAdd(vkX, ecMul([IC{ic}_X, IC{ic}_Y], input[{i}]));");
+    }
+
+    Ok(format!(
+        r#"// SPDX-License-Identifier: MIT
+// Groth16 verifier generated by zkp_core::solidity from a BN254 verifying key; regenerate it instead of editing.
+pragma solidity ^0.8.0;
+
+contract {contract_name} {{
+    // BN254 base field and scalar field moduli
+    uint256 constant Q = {q};
+    uint256 constant R = {r};
+
+{constants}
+    /// Checks a Groth16 proof against the {num_inputs} public inputs, in the order the circuit allocates them
+    function verifyProof(
+        uint256[2] calldata a,
+        uint256[2][2] calldata b,
+        uint256[2] calldata c,
+        uint256[{num_inputs}] calldata input
+    ) external view returns (bool) {{
+        // A's y coordinate is negated below, so it must be canonical for the proof not to be malleable
+        if (a[1] >= Q) return false;
+
+        uint256[2] memory vkX = [IC0_X, IC0_Y];
+{accumulate}
+        uint256[24] memory pairing;
+        pairing[0] = a[0];
+        pairing[1] = (Q - a[1]) % Q;
+        pairing[2] = b[0][0];
+        pairing[3] = b[0][1];
+        pairing[4] = b[1][0];
+        pairing[5] = b[1][1];
+        pairing[6] = ALPHA_X;
+        pairing[7] = ALPHA_Y;
+        pairing[8] = BETA_X1;
+        pairing[9] = BETA_X0;
+        pairing[10] = BETA_Y1;
+        pairing[11] = BETA_Y0;
+        pairing[12] = vkX[0];
+        pairing[13] = vkX[1];
+        pairing[14] = GAMMA_X1;
+        pairing[15] = GAMMA_X0;
+        pairing[16] = GAMMA_Y1;
+        pairing[17] = GAMMA_Y0;
+        pairing[18] = c[0];
+        pairing[19] = c[1];
+        pairing[20] = DELTA_X1;
+        pairing[21] = DELTA_X0;
+        pairing[22] = DELTA_Y1;
+        pairing[23] = DELTA_Y0;
+
+        // The precompile fails on points off the curve, which is an invalid proof rather than an error
+        uint256[1] memory result;
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x08, pairing, 0x300, result, 0x20)
+        }}
+        return success && result[0] == 1;
+    }}
+
+    function ecAdd(uint256[2] memory p, uint256[2] memory q) internal view returns (uint256[2] memory sum) {{
+        uint256[4] memory operands = [p[0], p[1], q[0], q[1]];
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x06, operands, 0x80, sum, 0x40)
+        }}
+        require(success, "ecAdd failed");
+    }}
+
+    function ecMul(uint256[2] memory p, uint256 scalar) internal view returns (uint256[2] memory product) {{
+        uint256[3] memory operands = [p[0], p[1], scalar];
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x07, operands, 0x60, product, 0x40)
+        }}
+        require(success, "ecMul failed");
+    }}
+}}
+"#,
+        q = Fq::MODULUS,
+        r = Fr::MODULUS,
+    ))
+}
+
+/// ABI-encoded calldata calling `verifyProof` on a generated verifier with `proof` and its public inputs
+///
+/// The public inputs are the field elements the circuit allocates, as returned by
+/// [`AttributeCircuit::public_inputs`](crate::attribute::AttributeCircuit::public_inputs), and there must be as many
+/// as the key the contract was made from expects.
+pub fn calldata(proof: &Proof<Bn254>, public_inputs: &[Fr]) -> Vec<u8> {
+    let mut words: Vec<[u8; 32]> = Vec::with_capacity(8 + public_inputs.len());
+    words.extend(g1_coordinates(&proof.a).map(word));
+    words.extend(g2_coordinates(&proof.b).map(word));
+    words.extend(g1_coordinates(&proof.c).map(word));
+    words.extend(public_inputs.iter().map(|x| word(*x)));
+
+    let mut data = selector(public_inputs.len()).to_vec();
+    data.extend(words.iter().flatten());
+    data
+}
+
+/// Solidity signature of `verifyProof` for a circuit with `num_inputs` public inputs
+pub fn verify_proof_signature(num_inputs: usize) -> String {
+    format!("verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])", num_inputs)
+}
+
+fn selector(num_inputs: usize) -> [u8; 4] {
+    let hash = Keccak256::digest(verify_proof_signature(num_inputs).as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+// Big-endian 32 byte word holding a field element
+fn word<F: PrimeField>(x: F) -> [u8; 32] {
+    let bytes = x.into_bigint().to_bytes_be();
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+fn g1_coordinates(p: &G1Affine) -> [Fq; 2] {
+    p.xy().map_or([Fq::from(0u64); 2], |(x, y)| [*x, *y])
+}
+
+// (x imaginary, x real, y imaginary, y real)
+fn g2_coordinates(p: &G2Affine) -> [Fq; 4] {
+    p.xy().map_or([Fq::from(0u64); 4], |(x, y)| [x.c1, x.c0, y.c1, y.c0])
+}
+
+fn g1_constants(out: &mut String, name: &str, p: &G1Affine) {
+    let [x, y] = g1_coordinates(p);
+    let _ = writeln!(out, "    uint256 constant {}_X = {};", name, x.into_bigint());
+    let _ = writeln!(out, "    uint256 constant {}_Y = {};", name, y.into_bigint());
+}
+
+fn g2_constants(out: &mut String, name: &str, p: &G2Affine) {
+    let [x1, x0, y1, y0] = g2_coordinates(p);
+    for (suffix, value) in [("X1", x1), ("X0", x0), ("Y1", y1), ("Y0", y0)] {
+        let _ = writeln!(out, "    uint256 constant {}_{} = {};", name, suffix, value.into_bigint());
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
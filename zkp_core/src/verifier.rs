@@ -0,0 +1,147 @@
+use ark_bn254::Bn254;
+use ark_ec::{VariableBaseMSM, pairing::Pairing};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, prepare_verifying_key};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::attribute::AttributeCircuit;
+use crate::error::ZkpError;
+use crate::keys::{CircuitFingerprint, CircuitVerifyingKey, check_fingerprint, circuit_fingerprint};
+use crate::proof_system::check_public_input_count;
+
+// Long-lived verification. `proof_system::verify` prepares the key (a pairing precomputation) on every call. A
+// `PreparedKey` does it once, when it is created, and a `Verifier` keeps one prepared key per circuit fingerprint
+// for services verifying many proofs. Both still compare the key's fingerprint with the circuit in use on each
+// call, as `proof_system::verify` does; fingerprints are cached per shape, so that is a lookup.
+//
+// Verifying also needs the public inputs combined into a single point, sum(x_j * IC_j), which costs a scalar
+// multiplication per input. Inputs that only take a few values, like an age threshold, can have their terms
+// precomputed so those multiplications are skipped; fresh values such as nonces are always computed.
+
+// (public input position, value) -> value * IC_{position + 1}
+type InputTerms<E> = HashMap<(usize, <E as Pairing>::ScalarField), <E as Pairing>::G1>;
+
+/// A verifying key with its pairing precomputation done, bound to the circuit it was checked against
+pub struct PreparedKey<E: Pairing = Bn254> {
+    fingerprint: CircuitFingerprint,
+    pvk: PreparedVerifyingKey<E>,
+    input_terms: RwLock<InputTerms<E>>,
+}
+
+impl<E: Pairing> PreparedKey<E> {
+    /// Prepares `vk` after checking it belongs to circuit `C` of the given shape
+    pub fn new<C: AttributeCircuit<E::ScalarField>>(vk: &CircuitVerifyingKey<E>, config: &C::Config) -> Result<Self, ZkpError> {
+        check_fingerprint::<E::ScalarField, C>(&vk.fingerprint, config)?;
+        Ok(Self {
+            fingerprint: vk.fingerprint,
+            pvk: prepare_verifying_key(&vk.key),
+            input_terms: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Fingerprint of the circuit this key verifies
+    pub fn fingerprint(&self) -> CircuitFingerprint {
+        self.fingerprint
+    }
+
+    /// Precomputes the input terms for the values a public input is expected to take
+    ///
+    /// `position` indexes the circuit's public inputs, e.g. 1 for `min_age` in the age circuit (after the nonce).
+    pub fn precompute_input(&self, position: usize, values: &[E::ScalarField]) -> Result<(), ZkpError> {
+        let base = self.pvk.vk.gamma_abc_g1.get(position + 1).ok_or_else(|| {
+            ZkpError::InvalidInput(format!("the key has no public input at position {}", position))
+        })?;
+        let mut terms = self.input_terms.write().expect("input cache lock poisoned");
+        for value in values {
+            terms.insert((position, *value), *base * value);
+        }
+        Ok(())
+    }
+
+    /// Verifies a proof for circuit `C` of the given shape against this key
+    pub fn verify<C: AttributeCircuit<E::ScalarField>>(
+        &self,
+        config: &C::Config,
+        proof: &Proof<E>,
+        public: &C::PublicInputs,
+    ) -> Result<bool, ZkpError> {
+        check_fingerprint::<E::ScalarField, C>(&self.fingerprint, config)?;
+        self.verify_inputs(proof, &C::public_inputs(public))
+    }
+
+    /// Verifies a proof against raw public inputs, in the order the circuit allocates them
+    pub fn verify_inputs(&self, proof: &Proof<E>, public_inputs: &[E::ScalarField]) -> Result<bool, ZkpError> {
+        check_public_input_count(&self.pvk.vk, public_inputs)?;
+        let prepared_inputs = self.prepare_inputs(public_inputs);
+        Ok(Groth16::<E>::verify_proof_with_prepared_inputs(&self.pvk, proof, &prepared_inputs)?)
+    }
+
+    // IC_0 + sum(x_j * IC_{j + 1}), taking cached terms where available and one MSM for the rest
+    fn prepare_inputs(&self, public_inputs: &[E::ScalarField]) -> E::G1 {
+        let bases = &self.pvk.vk.gamma_abc_g1;
+        let terms = self.input_terms.read().expect("input cache lock poisoned");
+
+        let mut acc = E::G1::from(bases[0]);
+        let mut missing_bases: Vec<E::G1Affine> = Vec::new();
+        let mut missing_scalars: Vec<E::ScalarField> = Vec::new();
+        for (position, value) in public_inputs.iter().enumerate() {
+            match terms.get(&(position, *value)) {
+                Some(term) => acc += term,
+                None => {
+                    missing_bases.push(bases[position + 1]);
+                    missing_scalars.push(*value);
+                }
+            }
+        }
+        acc + E::G1::msm_unchecked(&missing_bases, &missing_scalars)
+    }
+}
+
+/// Registry of prepared keys, one per circuit fingerprint
+pub struct Verifier<E: Pairing = Bn254> {
+    keys: RwLock<HashMap<CircuitFingerprint, Arc<PreparedKey<E>>>>,
+}
+
+impl<E: Pairing> Default for Verifier<E> {
+    fn default() -> Self {
+        Self { keys: RwLock::new(HashMap::new()) }
+    }
+}
+
+impl<E: Pairing> Verifier<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prepares and stores `vk` for circuit `C`, replacing any key previously registered for the same circuit
+    ///
+    /// Every setup of a circuit yields keys with the same fingerprint, so registering new keys rotates them.
+    pub fn register<C: AttributeCircuit<E::ScalarField>>(
+        &self,
+        vk: &CircuitVerifyingKey<E>,
+        config: &C::Config,
+    ) -> Result<Arc<PreparedKey<E>>, ZkpError> {
+        let key = Arc::new(PreparedKey::new::<C>(vk, config)?);
+        self.keys.write().expect("key registry lock poisoned").insert(key.fingerprint, key.clone());
+        Ok(key)
+    }
+
+    /// The prepared key registered for a circuit fingerprint, if any
+    pub fn get(&self, fingerprint: &CircuitFingerprint) -> Option<Arc<PreparedKey<E>>> {
+        self.keys.read().expect("key registry lock poisoned").get(fingerprint).cloned()
+    }
+
+    /// Verifies a proof for circuit `C` of the given shape with the key registered for it
+    pub fn verify<C: AttributeCircuit<E::ScalarField>>(
+        &self,
+        config: &C::Config,
+        proof: &Proof<E>,
+        public: &C::PublicInputs,
+    ) -> Result<bool, ZkpError> {
+        let fingerprint = circuit_fingerprint::<E::ScalarField, C>(config)?;
+        let key = self.get(&fingerprint).ok_or_else(|| {
+            ZkpError::KeyCircuitMismatch(format!("no verifying key is registered for circuit {}", fingerprint))
+        })?;
+        key.verify::<C>(config, proof, public)
+    }
+}
@@ -0,0 +1,88 @@
+use ark_bn254::{Bn254, Fr};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use zkp_core::attribute::AttributeCircuit;
+use zkp_core::circuits::age_verification::witness_calculator::{
+    AgeConfig, AgePrivateInputs, AgePublicInputs, AgeVerification,
+};
+use zkp_core::nonce::nonce_input_gadget;
+use zkp_core::poseidon::{PoseidonParameters, poseidon_hash, poseidon_hash_gadget};
+use zkp_core::proof_system::{prove, setup, verify};
+
+// ----------------------------
+// Attribute Circuit Tests
+// ----------------------------
+
+// A new attribute defined outside the crate: knowledge of a membership secret whose Poseidon hash is public
+struct MembershipSecret;
+
+struct MembershipCircuit {
+    nonce: Fr,
+    commitment: Fr,
+    secret: Fr,
+}
+
+impl ConstraintSynthesizer<Fr> for MembershipCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        nonce_input_gadget(cs.clone(), Some(self.nonce))?;
+        let commitment_var = FpVar::new_input(cs.clone(), || Ok(self.commitment))?;
+        let secret_var = FpVar::new_witness(cs.clone(), || Ok(self.secret))?;
+
+        let params = PoseidonParameters::<Fr>::for_inputs(1);
+        poseidon_hash_gadget(&params, &[secret_var])?.enforce_equal(&commitment_var)
+    }
+}
+
+impl AttributeCircuit for MembershipSecret {
+    const NAME: &'static str = "membership_secret";
+    const VERSION: u32 = 1;
+
+    type Config = ();
+    type PublicInputs = (Fr, Fr);
+    type PrivateInputs = Fr;
+    type Circuit = MembershipCircuit;
+
+    fn sample_inputs(_: &()) -> ((Fr, Fr), Fr) {
+        let secret = Fr::from(1u64);
+        ((Fr::from(0u64), commit(secret)), secret)
+    }
+
+    fn circuit(_: &(), public: &(Fr, Fr), secret: &Fr) -> MembershipCircuit {
+        MembershipCircuit { nonce: public.0, commitment: public.1, secret: *secret }
+    }
+
+    fn public_inputs(public: &(Fr, Fr)) -> Vec<Fr> {
+        vec![public.0, public.1]
+    }
+}
+
+fn commit(secret: Fr) -> Fr {
+    poseidon_hash(&PoseidonParameters::<Fr>::for_inputs(1), &[secret])
+}
+
+#[test]
+fn test_generic_functions_prove_built_in_attribute() {
+    let config = AgeConfig::default();
+    let (pk, vk) = setup::<Bn254, AgeVerification>(&config).expect("Setup failed");
+
+    let public = AgePublicInputs { nonce: Fr::from(7u64), min_age: 18, max_age: None };
+    let proof = prove::<Bn254, AgeVerification>(&pk, &config, &public, &AgePrivateInputs { user_age: 30 })
+        .expect("Proof generation failed");
+
+    assert!(verify::<Bn254, AgeVerification>(&vk, &config, &proof, &public).expect("Verification failed"));
+    assert!(!verify::<Bn254, AgeVerification>(&vk, &config, &proof, &AgePublicInputs { min_age: 21, ..public })
+        .expect("Verification failed"));
+}
+
+#[test]
+fn test_new_attribute_only_implements_the_trait() {
+    let (pk, vk) = setup::<Bn254, MembershipSecret>(&()).expect("Setup failed");
+
+    let secret = Fr::from(31337u64);
+    let public = (Fr::from(7u64), commit(secret));
+    let proof = prove::<Bn254, MembershipSecret>(&pk, &(), &public, &secret).expect("Proof generation failed");
+
+    assert!(verify::<Bn254, MembershipSecret>(&vk, &(), &proof, &public).expect("Verification failed"));
+    assert!(!verify::<Bn254, MembershipSecret>(&vk, &(), &proof, &(public.0, commit(Fr::from(1u64))))
+        .expect("Verification failed"));
+}
@@ -0,0 +1,45 @@
+use ark_bn254::Fr;
+use zkp_core::batch::batch_verify_age;
+use zkp_core::error::ZkpError;
+use zkp_core::proof_system::{prove_age, setup_age_verification_circuit};
+
+// ----------------------------
+// Batch Verification Tests
+// ----------------------------
+
+#[test]
+fn test_batch_verify_age_proofs() {
+    let (pk, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let items: Vec<_> = (0..4u64)
+        .map(|i| {
+            let nonce = Fr::from(1000 + i);
+            let proof = prove_age(&pk, nonce, 8, 20 + i, 18, None).unwrap();
+            (proof, nonce, 18, None)
+        })
+        .collect();
+    assert_eq!(batch_verify_age(&vk, 8, &items).unwrap(), vec![true; 4]);
+    assert_eq!(batch_verify_age(&vk, 8, &[]).unwrap(), Vec::<bool>::new());
+
+    // A proof replayed against another nonce fails the batch, and the fallback singles it out
+    let mut tampered = items.clone();
+    tampered[2].1 = Fr::from(7u64);
+    assert_eq!(batch_verify_age(&vk, 8, &tampered).unwrap(), vec![true, true, false, true]);
+
+    // Swapping proofs between statements is caught too
+    let mut swapped = items.clone();
+    swapped[0].0 = items[1].0.clone();
+    assert_eq!(batch_verify_age(&vk, 8, &swapped).unwrap(), vec![false, true, true, true]);
+}
+
+#[test]
+fn test_batch_verify_rejects_mixed_shapes() {
+    let (pk, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let nonce = Fr::from(1u64);
+    let proof = prove_age(&pk, nonce, 8, 20, 18, None).unwrap();
+    let items = vec![(proof.clone(), nonce, 18, None), (proof, nonce, 18, Some(30))];
+    assert!(matches!(batch_verify_age(&vk, 8, &items), Err(ZkpError::InvalidInput(_))));
+
+    // Keys of another bit width do not match the circuit
+    let items = vec![items[0].clone()];
+    assert!(matches!(batch_verify_age(&vk, 16, &items), Err(ZkpError::KeyCircuitMismatch(_))));
+}
@@ -0,0 +1,157 @@
+use ark_bn254::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::thread_rng;
+use zkp_core::ceremony::{Ceremony, contribute, verify_contribution};
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgeVerification};
+use zkp_core::error::ZkpError;
+use zkp_core::keys::CircuitProvingKey;
+use zkp_core::powers_of_tau::PowersOfTau;
+use zkp_core::proof_system::{prove_age, setup_age_verification_circuit, verify_age};
+
+const AGE_CONFIG: AgeConfig = AgeConfig { num_bits: 8, upper_bounded: false };
+
+fn powers_of_tau(contributions: usize) -> PowersOfTau {
+    let mut powers = PowersOfTau::new(64).unwrap();
+    for _ in 0..contributions {
+        powers.contribute(&mut thread_rng());
+    }
+    powers
+}
+
+// ----------------------------
+// Powers of Tau Tests
+// ----------------------------
+
+#[test]
+fn test_powers_of_tau_verify() {
+    let powers = powers_of_tau(2);
+
+    // The transcript survives a round trip through its file encoding
+    let mut bytes = Vec::new();
+    powers.serialize_uncompressed(&mut bytes).unwrap();
+    let powers = PowersOfTau::deserialize_uncompressed(bytes.as_slice()).unwrap();
+    assert!(powers.verify().is_ok());
+    assert_ne!(powers.current.tau_g1[1], ark_bn254::G1Affine::generator(), "tau should have changed");
+
+    assert!(PowersOfTau::new(48).is_err(), "sizes must be powers of two");
+}
+
+#[test]
+fn test_powers_of_tau_rejects_tampering() {
+    let powers = powers_of_tau(2);
+
+    // Claim a tau the contributor cannot prove knowledge of
+    let mut forged = powers.clone();
+    let tau = (forged.contributions[1].tau_g1 * Fr::from(2u64)).into_affine();
+    forged.contributions[1].tau_g1 = tau;
+    assert!(matches!(forged.verify(), Err(ZkpError::InvalidContribution { index: 1, .. })));
+
+    // Replace one power with something that is not a power of the same tau
+    let mut forged = powers.clone();
+    forged.current.tau_g1[5] = (forged.current.tau_g1[5] + ark_bn254::G1Affine::generator()).into_affine();
+    assert!(matches!(forged.verify(), Err(ZkpError::InvalidContribution { .. })));
+
+    let mut forged = powers.clone();
+    forged.current.alpha_tau_g1[3] = forged.current.beta_tau_g1[3];
+    assert!(matches!(forged.verify(), Err(ZkpError::InvalidContribution { .. })));
+
+    let mut forged = powers;
+    forged.current.beta_g2 = forged.current.tau_g2[1];
+    assert!(matches!(forged.verify(), Err(ZkpError::InvalidContribution { .. })));
+}
+
+// ----------------------------
+// Ceremony Tests
+// ----------------------------
+
+#[test]
+fn test_ceremony_keys_prove_and_verify() {
+    let powers = powers_of_tau(2);
+    let mut ceremony = Ceremony::new::<AgeVerification>(&powers, &AGE_CONFIG).unwrap();
+    let initial = CircuitProvingKey { fingerprint: ceremony.fingerprint, key: ceremony.initial.clone() };
+    let mut rng = thread_rng();
+    for _ in 0..3 {
+        ceremony.contribute(&mut rng);
+    }
+
+    // The transcript survives a round trip through its file encoding
+    let mut bytes = Vec::new();
+    ceremony.serialize_uncompressed(&mut bytes).unwrap();
+    let ceremony = Ceremony::deserialize_uncompressed(bytes.as_slice()).unwrap();
+
+    let (pk, vk) = ceremony.verify::<AgeVerification>(&powers, &AGE_CONFIG).expect("honest transcript should verify");
+    assert_ne!(pk.key.delta_g1, initial.key.delta_g1, "delta should have changed");
+
+    let nonce = Fr::from(424242u64);
+    let proof = prove_age(&pk, nonce, 8, 25, 18, None).unwrap();
+    assert!(verify_age(&vk, &proof, nonce, 8, 18, None).unwrap());
+    assert!(!verify_age(&vk, &proof, nonce, 8, 30, None).unwrap());
+
+    // Keys from before phase 2 no longer verify its proofs
+    assert!(!verify_age(&initial.verifying_key(), &proof, nonce, 8, 18, None).unwrap());
+}
+
+#[test]
+fn test_ceremony_requires_contributed_powers_of_tau() {
+    // Without a contribution tau, alpha and beta are all one
+    assert!(matches!(
+        Ceremony::new::<AgeVerification>(&powers_of_tau(0), &AGE_CONFIG),
+        Err(ZkpError::InvalidInput(_))
+    ));
+
+    // The circuit does not fit in a domain of 4 elements
+    let mut small = PowersOfTau::new(4).unwrap();
+    small.contribute(&mut thread_rng());
+    assert!(matches!(Ceremony::new::<AgeVerification>(&small, &AGE_CONFIG), Err(ZkpError::InvalidInput(_))));
+}
+
+#[test]
+fn test_ceremony_rejects_initial_parameters_not_from_powers_of_tau() {
+    let powers = powers_of_tau(1);
+
+    // Parameters from a local setup, whose secrets the initiator knows
+    let (local, _) = setup_age_verification_circuit(8, false).unwrap();
+    let mut forged = Ceremony::new::<AgeVerification>(&powers, &AGE_CONFIG).unwrap();
+    forged.initial = local.key.clone();
+    forged.current = local.key;
+    forged.contribute(&mut thread_rng());
+    assert!(forged.verify_contributions().is_ok());
+    assert!(forged.verify::<AgeVerification>(&powers, &AGE_CONFIG).is_err());
+
+    // The right parameters checked against another circuit shape
+    let ceremony = Ceremony::new::<AgeVerification>(&powers, &AGE_CONFIG).unwrap();
+    let other = AgeConfig { num_bits: 8, upper_bounded: true };
+    assert!(ceremony.verify::<AgeVerification>(&powers, &other).is_err());
+}
+
+#[test]
+fn test_ceremony_rejects_tampered_contribution() {
+    let mut ceremony = Ceremony::new::<AgeVerification>(&powers_of_tau(1), &AGE_CONFIG).unwrap();
+    let mut rng = thread_rng();
+    ceremony.contribute(&mut rng);
+    ceremony.contribute(&mut rng);
+
+    // Claim a delta the contributor cannot prove knowledge of
+    let mut forged = ceremony.clone();
+    let delta = (forged.contributions[1].delta_g1 * Fr::from(2u64)).into_affine();
+    forged.contributions[1].delta_g1 = delta;
+    forged.current.delta_g1 = delta;
+    assert!(matches!(forged.verify_contributions(), Err(ZkpError::InvalidContribution { index: 1, .. })));
+
+    // Swap in a query element that was not divided by delta
+    let mut forged = ceremony.clone();
+    forged.current.h_query[0] = (forged.current.h_query[0] + ark_bn254::G1Affine::generator()).into_affine();
+    assert!(matches!(forged.verify_contributions(), Err(ZkpError::InvalidContribution { .. })));
+}
+
+#[test]
+fn test_verify_single_contribution() {
+    let (before, _) = setup_age_verification_circuit(8, false).unwrap();
+    let (after, contribution) = contribute(&before.key, &mut thread_rng());
+    assert!(verify_contribution(&before.key, &after, &contribution).is_ok());
+
+    // Another participant's output does not match this contribution
+    let (other, _) = contribute(&before.key, &mut thread_rng());
+    assert!(verify_contribution(&before.key, &other, &contribution).is_err());
+}
@@ -0,0 +1,98 @@
+use ark_bn254::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::ConstraintSystem;
+use zkp_core::babyjubjub::{EdwardsAffine, EdwardsVar, Fs};
+use zkp_core::eddsa::{
+    PublicKey, PublicKeyVar, SCALAR_BITS, SecretKey, Signature, SignatureVar, enforce_signature_gadget,
+};
+
+// Runs the signature gadget on the given values and reports whether the constraints are satisfied
+fn gadget_accepts(public_key: &PublicKey, message: Fr, signature: &Signature) -> bool {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let pk_var = PublicKeyVar::new_input(cs.clone(), || Ok(*public_key)).unwrap();
+    let message_var = FpVar::new_witness(cs.clone(), || Ok(message)).unwrap();
+    let signature_var = SignatureVar::new_witness(cs.clone(), || Ok(*signature)).unwrap();
+    enforce_signature_gadget(&pk_var, &message_var, &signature_var).unwrap();
+    cs.is_satisfied().unwrap()
+}
+
+// ----------------------------
+// Baby Jubjub EdDSA Tests
+// ----------------------------
+
+#[test]
+fn test_generator_has_prime_order() {
+    let generator = EdwardsAffine::generator();
+    assert!(generator.is_on_curve());
+    assert!(!generator.is_zero());
+    assert!(generator.mul_bigint(Fs::MODULUS).is_zero());
+}
+
+#[test]
+fn test_native_and_gadget_verification_agree() {
+    let secret_key = SecretKey(Fs::from(42u64));
+    let public_key = secret_key.public_key();
+    let message = Fr::from(2024u64);
+    let signature = secret_key.sign(message);
+
+    // A genuine signature passes both verifiers
+    assert!(public_key.verify(message, &signature));
+    assert!(gadget_accepts(&public_key, message, &signature));
+
+    // A different message fails both verifiers
+    assert!(!public_key.verify(message + Fr::from(1u64), &signature));
+    assert!(!gadget_accepts(&public_key, message + Fr::from(1u64), &signature));
+
+    // A tampered scalar fails both verifiers
+    let tampered = Signature { r: signature.r, s: signature.s + Fs::from(1u64) };
+    assert!(!public_key.verify(message, &tampered));
+    assert!(!gadget_accepts(&public_key, message, &tampered));
+}
+
+#[test]
+fn test_gadget_rejects_unreduced_scalar() {
+    let secret_key = SecretKey(Fs::from(42u64));
+    let public_key = secret_key.public_key();
+
+    // s + l satisfies s * G == R + h * A as well; find a message whose s + l still fits the scalar bits
+    let (message, signature, unreduced) = (0u64..)
+        .map(|m| {
+            let signature = secret_key.sign(Fr::from(m));
+            let mut unreduced = signature.s.into_bigint();
+            unreduced.add_with_carry(&Fs::MODULUS);
+            (Fr::from(m), signature, unreduced)
+        })
+        .find(|(_, _, unreduced)| unreduced.num_bits() as usize <= SCALAR_BITS)
+        .unwrap();
+    assert!(gadget_accepts(&public_key, message, &signature));
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let pk_var = PublicKeyVar::new_input(cs.clone(), || Ok(public_key)).unwrap();
+    let message_var = FpVar::new_witness(cs.clone(), || Ok(message)).unwrap();
+    let signature_var = SignatureVar {
+        r: EdwardsVar::new_witness(cs.clone(), || Ok(signature.r)).unwrap(),
+        s_bits: unreduced.to_bits_le()[..SCALAR_BITS]
+            .iter()
+            .map(|bit| Boolean::new_witness(cs.clone(), || Ok(*bit)).unwrap())
+            .collect(),
+    };
+    enforce_signature_gadget(&pk_var, &message_var, &signature_var).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_public_key_coordinates_are_validated() {
+    let public_key = SecretKey(Fs::from(7u64)).public_key();
+    assert_eq!(PublicKey::from_coordinates(public_key.0.x, public_key.0.y), Some(public_key));
+
+    // Points off the curve are rejected
+    assert_eq!(PublicKey::from_coordinates(Fr::from(1u64), Fr::from(2u64)), None);
+
+    // Points outside the prime-order subgroup are rejected (the point of order 2 is (0, -1))
+    let low_order = EdwardsAffine::new_unchecked(Fr::from(0u64), -Fr::from(1u64));
+    assert!(low_order.is_on_curve());
+    let mixed = (public_key.0 + low_order).into_affine();
+    assert_eq!(PublicKey::from_coordinates(mixed.x, mixed.y), None);
+}
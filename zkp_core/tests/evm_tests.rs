@@ -0,0 +1,350 @@
+use ark_bn254::{Bn254, Fq, Fr, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use revm::Evm;
+use revm::db::{CacheDB, EmptyDB};
+use revm::primitives::{Address, Bytes, ExecutionResult, Output, TxKind, U256};
+use semver::Version;
+use std::collections::HashMap;
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use zkp_core::babyjubjub::Fs;
+use zkp_core::circuits::citizenship_verification::witness_calculator::MerklePath;
+use zkp_core::circuits::college_credential_verification::witness_calculator::college_credential_digest;
+use zkp_core::eddsa::SecretKey;
+use zkp_core::issuer::holder_commitment;
+use zkp_core::proof_system::{
+    prove_age, prove_citizenship, prove_college_credential, setup_age_verification_circuit,
+    setup_citizenship_verification_circuit, setup_credential_verification_circuit, verify_age, verify_citizenship,
+    verify_college_credential,
+};
+use zkp_core::solidity::{calldata, verifier_contract};
+
+// ----------------------------
+// EVM Verifier Tests
+// ----------------------------
+//
+// Generated verifiers run on revm, an EVM interpreter embedded in the test, with no chain behind it. Every case is
+// checked against the Rust verifier twice: once by replaying the contract's steps on the EVM's own BN254
+// precompiles with the constants rendered into its source, which pins the calldata and pairing input layout, and
+// once by deploying the contract itself.
+//
+// Contracts are compiled with a pinned solc release, so the bytecode under test does not depend on whichever
+// compiler is installed. It is installed with svm (which checks the release's checksum) on first use, unless `SOLC`
+// points to a binary of that version. That may need the network, so the deployed-contract tests are ignored by
+// default and run with `cargo test --test evm_tests -- --ignored`; without solc they fail rather than skip.
+
+// Gas a transaction may use, a full block
+const GAS_LIMIT: u64 = 30_000_000;
+
+// solc release the generated verifiers are compiled with
+const SOLC_VERSION: Version = Version::new(0, 8, 26);
+
+struct Chain {
+    evm: Evm<'static, (), CacheDB<EmptyDB>>,
+}
+
+impl Chain {
+    fn new() -> Self {
+        Self { evm: Evm::builder().with_db(CacheDB::new(EmptyDB::default())).build() }
+    }
+
+    // Output of a transaction, or None if it reverted or halted
+    fn execute(&mut self, to: TxKind, data: Vec<u8>) -> Option<Output> {
+        let tx = self.evm.tx_mut();
+        tx.transact_to = to;
+        tx.data = Bytes::from(data);
+        tx.gas_limit = GAS_LIMIT;
+        match self.evm.transact_commit().expect("the transaction is invalid") {
+            ExecutionResult::Success { output, .. } => Some(output),
+            _ => None,
+        }
+    }
+
+    fn deploy(&mut self, creation_code: Vec<u8>) -> Address {
+        match self.execute(TxKind::Create, creation_code) {
+            Some(Output::Create(_, Some(address))) => address,
+            other => panic!("deployment failed: {:?}", other),
+        }
+    }
+
+    fn call(&mut self, to: Address, data: Vec<u8>) -> Option<Vec<u8>> {
+        match self.execute(TxKind::Call(to), data)? {
+            Output::Call(bytes) => Some(bytes.to_vec()),
+            Output::Create(..) => None,
+        }
+    }
+}
+
+fn precompile(index: u8) -> Address {
+    Address::with_last_byte(index)
+}
+
+fn modulus<F: PrimeField>() -> U256 {
+    U256::from_be_slice(&F::MODULUS.to_bytes_be())
+}
+
+// The `uint256 constant NAME = value;` declarations of a rendered contract
+struct Constants(HashMap<String, U256>);
+
+impl Constants {
+    fn parse(source: &str) -> Self {
+        let declarations = source
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("uint256 constant ")?.strip_suffix(';')?.split_once(" = "));
+        Self(
+            declarations
+                .map(|(name, value)| (name.to_string(), U256::from_str_radix(value, 10).expect("decimal constant")))
+                .collect(),
+        )
+    }
+
+    fn get(&self, name: &str) -> U256 {
+        *self.0.get(name).unwrap_or_else(|| panic!("the contract declares no constant {}", name))
+    }
+
+    fn words(&self, names: &[String]) -> Vec<u8> {
+        names.iter().flat_map(|name| self.get(name).to_be_bytes::<32>()).collect()
+    }
+
+    fn g1(&self, point: &str) -> Vec<u8> {
+        self.words(&[format!("{}_X", point), format!("{}_Y", point)])
+    }
+
+    // Imaginary part first, as the pairing precompile expects
+    fn g2(&self, point: &str) -> Vec<u8> {
+        self.words(&["X1", "X0", "Y1", "Y0"].map(|suffix| format!("{}_{}", point, suffix)))
+    }
+}
+
+// The generated contract's `verifyProof`, step by step on the precompiles, with the constants rendered into it
+fn precompiles_accept(chain: &mut Chain, source: &str, data: &[u8]) -> bool {
+    let constants = Constants::parse(source);
+    let (q, r) = (constants.get("Q"), constants.get("R"));
+    assert_eq!((q, r), (modulus::<Fq>(), modulus::<Fr>()), "the contract declares the wrong moduli");
+
+    let words: Vec<U256> = data[4..].chunks(32).map(U256::from_be_slice).collect();
+    let inputs = &words[8..];
+    assert!(
+        source.contains(&format!("uint256[{}] calldata input", inputs.len())),
+        "the contract does not take {} public inputs",
+        inputs.len()
+    );
+    if words[1] >= q || inputs.iter().any(|x| *x >= r) {
+        return false;
+    }
+
+    let mut vk_x = constants.g1("IC0");
+    for (i, x) in inputs.iter().enumerate() {
+        let ic = constants.g1(&format!("IC{}", i + 1));
+        let product = chain.call(precompile(7), [ic, x.to_be_bytes::<32>().to_vec()].concat()).unwrap();
+        vk_x = chain.call(precompile(6), [vk_x, product].concat()).unwrap();
+    }
+
+    let negated_a_y = (q - words[1]) % q;
+    let pairing = [
+        data[4..36].to_vec(),
+        negated_a_y.to_be_bytes::<32>().to_vec(),
+        data[68..196].to_vec(),
+        constants.g1("ALPHA"),
+        constants.g2("BETA"),
+        vk_x,
+        constants.g2("GAMMA"),
+        data[196..260].to_vec(),
+        constants.g2("DELTA"),
+    ]
+    .concat();
+    chain.call(precompile(8), pairing).is_some_and(|result| result.last() == Some(&1))
+}
+
+// The pinned solc, installed once per test run
+fn solc() -> &'static PathBuf {
+    static SOLC: OnceLock<PathBuf> = OnceLock::new();
+    SOLC.get_or_init(|| {
+        let version = SOLC_VERSION.to_string();
+        let path = match env::var_os("SOLC") {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let installed = svm_lib::version_path(&version).join(format!("solc-{}", version));
+                if installed.exists() {
+                    installed
+                } else {
+                    svm_lib::blocking_install(&SOLC_VERSION).unwrap_or_else(|e| {
+                        panic!("cannot install solc {}: {}; point SOLC to a solc {} binary", version, e, version)
+                    })
+                }
+            }
+        };
+
+        let output = Command::new(&path)
+            .arg("--version")
+            .output()
+            .unwrap_or_else(|e| panic!("cannot run solc at {}: {}", path.display(), e));
+        let reported = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            reported.contains(&format!("Version: {}+", version)),
+            "{} is not solc {}:\n{}",
+            path.display(),
+            version,
+            reported
+        );
+        path
+    })
+}
+
+// Compiles the generated contract with the pinned solc
+fn compile(source: &str, contract_name: &str) -> Vec<u8> {
+    let mut child = Command::new(solc())
+        .args(["--bin", "--optimize", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("solc could not be started");
+    child.stdin.take().unwrap().write_all(source.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "solc rejected the generated {} contract", contract_name);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let binary = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("Binary"))
+        .nth(1)
+        .unwrap_or_else(|| panic!("solc printed no bytecode for {}", contract_name));
+    (0..binary.len()).step_by(2).map(|i| u8::from_str_radix(&binary[i..i + 2], 16).unwrap()).collect()
+}
+
+// A verifier contract with the cases the Rust verifier decided: the first one is a valid proof, the rest must be
+// rejected. Calldata corrupted past decoding is rejected as well.
+struct EvmCases {
+    contract_name: &'static str,
+    source: String,
+    data: Vec<(Vec<u8>, bool)>,
+}
+
+impl EvmCases {
+    fn new(contract_name: &'static str, vk: &VerifyingKey<Bn254>, cases: &[(Proof<Bn254>, Vec<Fr>, bool)]) -> Self {
+        assert!(cases[0].2 && cases[1..].iter().all(|(_, _, valid)| !valid), "unexpected Rust verifier results");
+        let mut data: Vec<(Vec<u8>, bool)> =
+            cases.iter().map(|(proof, inputs, valid)| (calldata(proof, inputs), *valid)).collect();
+        let mut corrupted = data[0].0.clone();
+        corrupted[4 + 6 * 32 + 31] ^= 1;
+        data.push((corrupted, false));
+        EvmCases { contract_name, source: verifier_contract(contract_name, vk).unwrap(), data }
+    }
+
+    fn assert_precompiles_agree(&self) {
+        let mut chain = Chain::new();
+        for (i, (call, valid)) in self.data.iter().enumerate() {
+            let accepted = precompiles_accept(&mut chain, &self.source, call);
+            assert_eq!(accepted, *valid, "{} precompiles, case {}", self.contract_name, i);
+        }
+    }
+
+    fn assert_contract_agrees(&self) {
+        let mut chain = Chain::new();
+        let verifier = chain.deploy(compile(&self.source, self.contract_name));
+        for (i, (call, valid)) in self.data.iter().enumerate() {
+            let accepted = chain.call(verifier, call.clone()).is_some_and(|result| result.last() == Some(&1));
+            assert_eq!(accepted, *valid, "{} contract, case {}", self.contract_name, i);
+        }
+    }
+}
+
+// A proof made of valid points that does not verify: A moved by the generator
+fn tampered(proof: &Proof<Bn254>) -> Proof<Bn254> {
+    Proof { a: (proof.a + G1Affine::generator()).into_affine(), ..proof.clone() }
+}
+
+fn age_cases() -> EvmCases {
+    let (pk, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let nonce = Fr::from(424242u64);
+    let proof = prove_age(&pk, nonce, 8, 25, 18, None).unwrap();
+    let verify = |proof: &Proof<Bn254>, min_age: u64| verify_age(&vk, proof, nonce, 8, min_age, None).unwrap();
+    let inputs = |min_age: u64| vec![nonce, Fr::from(min_age)];
+
+    EvmCases::new("AgeVerifier", &vk.key, &[
+        (proof.clone(), inputs(18), verify(&proof, 18)),
+        (tampered(&proof), inputs(18), verify(&tampered(&proof), 18)),
+        (proof.clone(), inputs(21), verify(&proof, 21)),
+    ])
+}
+
+fn citizenship_cases() -> EvmCases {
+    const DEPTH: usize = 4;
+    let (pk, vk) = setup_citizenship_verification_circuit(DEPTH).unwrap();
+    let nonce = Fr::from(424242u64);
+    let leaf = Fr::from(6u64);
+    let path = MerklePath {
+        siblings: (1..=DEPTH as u64).map(Fr::from).collect(),
+        directions: vec![true, false, false, true],
+    };
+    let root = path.compute_root(leaf);
+    let proof = prove_citizenship(&pk, nonce, DEPTH, root, leaf, path).unwrap();
+    let verify = |proof: &Proof<Bn254>, root: Fr| verify_citizenship(&vk, proof, nonce, DEPTH, root).unwrap();
+    let other_root = root + Fr::from(1u64);
+
+    EvmCases::new("CitizenshipVerifier", &vk.key, &[
+        (proof.clone(), vec![nonce, root], verify(&proof, root)),
+        (tampered(&proof), vec![nonce, root], verify(&tampered(&proof), root)),
+        (proof.clone(), vec![nonce, other_root], verify(&proof, other_root)),
+    ])
+}
+
+fn college_credential_cases() -> EvmCases {
+    let (pk, vk) = setup_credential_verification_circuit().unwrap();
+    let nonce = Fr::from(424242u64);
+    let university_key = SecretKey(Fs::from(123456789u64));
+    let other_university_key = SecretKey(Fs::from(987654321u64));
+    let credential = Fr::from(10u64);
+    let holder_secret = Fr::from(31337u64);
+    let signature = university_key.sign(college_credential_digest(credential, holder_commitment(holder_secret)));
+    let public_key = university_key.public_key();
+    let proof = prove_college_credential(&pk, nonce, &public_key, credential, holder_secret, &signature).unwrap();
+    let verify = |proof: &Proof<Bn254>, key: &SecretKey| {
+        verify_college_credential(&vk, proof, nonce, &key.public_key()).unwrap()
+    };
+    let inputs = |key: &SecretKey| [vec![nonce], key.public_key().to_public_inputs()].concat();
+
+    EvmCases::new("CollegeDegreeCredentialVerifier", &vk.key, &[
+        (proof.clone(), inputs(&university_key), verify(&proof, &university_key)),
+        (tampered(&proof), inputs(&university_key), verify(&tampered(&proof), &university_key)),
+        (proof.clone(), inputs(&other_university_key), verify(&proof, &other_university_key)),
+    ])
+}
+
+#[test]
+fn test_evm_age_verifier() {
+    age_cases().assert_precompiles_agree();
+}
+
+#[test]
+fn test_evm_citizenship_verifier() {
+    citizenship_cases().assert_precompiles_agree();
+}
+
+#[test]
+fn test_evm_college_credential_verifier() {
+    college_credential_cases().assert_precompiles_agree();
+}
+
+#[test]
+#[ignore = "needs solc, which is downloaded unless SOLC points to one"]
+fn test_evm_age_verifier_contract() {
+    age_cases().assert_contract_agrees();
+}
+
+#[test]
+#[ignore = "needs solc, which is downloaded unless SOLC points to one"]
+fn test_evm_citizenship_verifier_contract() {
+    citizenship_cases().assert_contract_agrees();
+}
+
+#[test]
+#[ignore = "needs solc, which is downloaded unless SOLC points to one"]
+fn test_evm_college_credential_verifier_contract() {
+    college_credential_cases().assert_contract_agrees();
+}
@@ -0,0 +1,62 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::{Bn254, Fr};
+use ark_serialize::Compress;
+use zkp_core::attribute::AttributeCircuit;
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgeVerification};
+use zkp_core::circuits::citizenship_verification::witness_calculator::CitizenshipVerification;
+use zkp_core::inspect::inspect;
+use zkp_core::key_file::{encode_proof, encode_proving_key, encode_verifying_key};
+use zkp_core::keys::{Curve, circuit_fingerprint};
+use zkp_core::proof_system::{insecure_seeded_setup, prove_age};
+
+// ----------------------------
+// Circuit Inspection Tests
+// ----------------------------
+
+// The analytic key sizes must match what a real setup encodes to
+fn assert_sizes_match_setup<E: Curve, C: AttributeCircuit<E::ScalarField>>(config: &C::Config) {
+    let info = inspect::<E, C>(config).unwrap();
+    let (pk, vk) = insecure_seeded_setup::<E, C>(config, 7).unwrap();
+
+    assert_eq!(info.fingerprint, pk.fingerprint);
+    assert_eq!(info.num_public_inputs + 1, vk.key.gamma_abc_g1.len());
+    assert_eq!(info.num_witness_variables, pk.key.l_query.len());
+    for compress in [Compress::Yes, Compress::No] {
+        let (pk_size, vk_size) = match compress {
+            Compress::Yes => (info.proving_key_size.compressed, info.verifying_key_size.compressed),
+            Compress::No => (info.proving_key_size.uncompressed, info.verifying_key_size.uncompressed),
+        };
+        assert_eq!(encode_proving_key(&pk, compress).unwrap().len(), pk_size);
+        assert_eq!(encode_verifying_key(&vk, compress).unwrap().len(), vk_size);
+    }
+}
+
+#[test]
+fn test_inspect_sizes_match_setup() {
+    assert_sizes_match_setup::<Bn254, AgeVerification>(&AgeConfig { num_bits: 8, upper_bounded: true });
+    assert_sizes_match_setup::<Bn254, CitizenshipVerification>(&2);
+    assert_sizes_match_setup::<Bls12_381, AgeVerification>(&AgeConfig::default());
+}
+
+#[test]
+fn test_inspect_reports_circuit_dimensions() {
+    let config = AgeConfig { num_bits: 8, upper_bounded: false };
+    let info = inspect::<Bn254, AgeVerification>(&config).unwrap();
+    assert_eq!((info.name, info.curve), ("age_verification", "bn254"));
+    assert_eq!(info.fingerprint, circuit_fingerprint::<Fr, AgeVerification>(&config).unwrap());
+
+    // nonce and min_age; an upper bound adds max_age and more constraints
+    assert_eq!(info.num_public_inputs, 2);
+    let bracket = inspect::<Bn254, AgeVerification>(&AgeConfig { upper_bounded: true, ..config }).unwrap();
+    assert_eq!(bracket.num_public_inputs, 3);
+    assert!(bracket.num_constraints > info.num_constraints);
+
+    // Wider ranges cost more constraints
+    let wide = inspect::<Bn254, AgeVerification>(&AgeConfig { num_bits: 16, ..config }).unwrap();
+    assert!(wide.num_constraints > info.num_constraints);
+
+    let (pk, _) = insecure_seeded_setup::<Bn254, AgeVerification>(&config, 7).unwrap();
+    let proof = prove_age(&pk, Fr::from(424242u64), 8, 25, 18, None).unwrap();
+    assert_eq!(encode_proof(&proof, pk.fingerprint, Compress::Yes).unwrap().len(), info.proof_size.compressed);
+    assert_eq!(encode_proof(&proof, pk.fingerprint, Compress::No).unwrap().len(), info.proof_size.uncompressed);
+}
@@ -0,0 +1,75 @@
+use ark_bn254::Fr;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::ConstraintSystem;
+use rand::thread_rng;
+use zkp_core::issuer::{
+    IssuerKeypair, credential_digest, credential_digest_gadget, verify_credential,
+    public_key_from_bytes, public_key_to_bytes, signature_from_bytes, signature_to_bytes,
+};
+
+// ----------------------------
+// Issuer Tests
+// ----------------------------
+
+#[test]
+fn test_issued_credential_verifies() {
+    let issuer = IssuerKeypair::generate(&mut thread_rng());
+
+    // Sign a credential made of (student id, degree code, graduation year)
+    let digest = credential_digest(&[Fr::from(1001u64), Fr::from(7u64), Fr::from(2024u64)]);
+    let signature = issuer.sign_credential(digest);
+    assert!(verify_credential(issuer.public_key(), digest, &signature));
+
+    // Changing any attribute changes the digest and invalidates the signature
+    let forged = credential_digest(&[Fr::from(1001u64), Fr::from(8u64), Fr::from(2024u64)]);
+    assert!(!verify_credential(issuer.public_key(), forged, &signature));
+
+    // Another issuer's key does not accept the signature
+    let other = IssuerKeypair::generate(&mut thread_rng());
+    assert!(!verify_credential(other.public_key(), digest, &signature));
+}
+
+#[test]
+fn test_keys_and_signatures_round_trip() {
+    let issuer = IssuerKeypair::generate(&mut thread_rng());
+    let digest = credential_digest(&[Fr::from(42u64)]);
+    let signature = issuer.sign_credential(digest);
+
+    assert_eq!(IssuerKeypair::from_bytes(&issuer.to_bytes()).unwrap(), issuer);
+    assert_eq!(public_key_from_bytes(&public_key_to_bytes(issuer.public_key())).unwrap(), *issuer.public_key());
+    assert_eq!(signature_from_bytes(&signature_to_bytes(&signature)).unwrap(), signature);
+
+    // Debug output never carries the secret key
+    let secret = issuer.secret_key().0.to_string();
+    assert!(!format!("{:?}", issuer).contains(&secret));
+    assert!(!format!("{:?}", issuer.secret_key()).contains(&secret));
+}
+
+#[test]
+fn test_malformed_encodings_are_rejected() {
+    let issuer = IssuerKeypair::generate(&mut thread_rng());
+    let signature = issuer.sign_credential(Fr::from(1u64));
+
+    // Truncated inputs
+    let pk_bytes = public_key_to_bytes(issuer.public_key());
+    assert!(public_key_from_bytes(&pk_bytes[..31]).is_err());
+    assert!(signature_from_bytes(&signature_to_bytes(&signature)[..63]).is_err());
+
+    // Scalars outside the field
+    assert!(IssuerKeypair::from_bytes(&[0xff; 32]).is_err());
+}
+
+#[test]
+fn test_digest_gadget_matches_native_digest() {
+    let attributes = [Fr::from(2008u64), Fr::from(10u64), Fr::from(18u64)];
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let attribute_vars = attributes
+        .iter()
+        .map(|a| FpVar::new_witness(cs.clone(), || Ok(*a)).unwrap())
+        .collect::<Vec<_>>();
+    let digest_var = credential_digest_gadget(&attribute_vars).unwrap();
+
+    assert_eq!(digest_var.value().unwrap(), credential_digest(&attributes));
+    assert!(cs.is_satisfied().unwrap());
+}
@@ -0,0 +1,121 @@
+use ark_bls12_381::{Bls12_381, Fr as BlsFr};
+use ark_bn254::{Bn254, Fr};
+use ark_serialize::{CanonicalSerialize, Compress};
+use zkp_core::error::ZkpError;
+use zkp_core::key_file::{
+    HEADER_LEN, decode_proof, decode_proof_auto, decode_proving_key, decode_verifying_key, decode_verifying_key_auto,
+    encode_proof, encode_proving_key, encode_verifying_key, load_proof, load_verifying_key, save_proof,
+    save_verifying_key,
+};
+use zkp_core::circuits::age_verification::witness_calculator::{
+    AgeConfig, AgePrivateInputs, AgePublicInputs, AgeVerification,
+};
+use zkp_core::proof_system::{insecure_seeded_setup, prove, prove_age, setup_age_verification_circuit};
+
+// ----------------------------
+// Key File Tests
+// ----------------------------
+
+#[test]
+fn test_key_file_round_trip() {
+    let (pk, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let proof = prove_age(&pk, Fr::from(424242u64), 8, 25, 18, None).unwrap();
+
+    for compress in [Compress::No, Compress::Yes] {
+        assert_eq!(decode_proving_key(&encode_proving_key(&pk, compress).unwrap()).unwrap(), pk);
+        assert_eq!(decode_verifying_key(&encode_verifying_key(&vk, compress).unwrap()).unwrap(), vk);
+        let bytes = encode_proof(&proof, pk.fingerprint, compress).unwrap();
+        assert_eq!(decode_proof::<Bn254>(&bytes).unwrap(), (pk.fingerprint, proof.clone()));
+    }
+
+    // Compressed points take roughly half the space
+    let uncompressed = encode_verifying_key(&vk, Compress::No).unwrap().len();
+    let compressed = encode_verifying_key(&vk, Compress::Yes).unwrap().len();
+    assert!(compressed < uncompressed);
+
+    let dir = std::env::temp_dir();
+    let vk_path = dir.join(format!("zkp_key_file_test_{}.vk", std::process::id()));
+    let proof_path = dir.join(format!("zkp_key_file_test_{}.proof", std::process::id()));
+    save_verifying_key(&vk_path, &vk, Compress::Yes).unwrap();
+    save_proof(&proof_path, &proof, vk.fingerprint, Compress::No).unwrap();
+    assert_eq!(load_verifying_key(&vk_path).unwrap(), vk);
+    assert_eq!(load_proof(&proof_path).unwrap(), (vk.fingerprint, proof));
+    std::fs::remove_file(vk_path).unwrap();
+    std::fs::remove_file(proof_path).unwrap();
+}
+
+#[test]
+fn test_key_file_rejects_bad_files() {
+    let (_, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let bytes = encode_verifying_key(&vk, Compress::No).unwrap();
+
+    // Truncated in the header and in the payload
+    assert!(matches!(decode_verifying_key::<Bn254>(&bytes[..HEADER_LEN - 1]), Err(ZkpError::InvalidFile(_))));
+    assert!(matches!(decode_verifying_key::<Bn254>(&bytes[..bytes.len() - 1]), Err(ZkpError::InvalidFile(_))));
+
+    // A flipped payload bit breaks the digest
+    let mut corrupted = bytes.clone();
+    *corrupted.last_mut().unwrap() ^= 1;
+    assert!(matches!(decode_verifying_key::<Bn254>(&corrupted), Err(ZkpError::InvalidFile(_))));
+
+    // Unknown format version
+    let mut future = bytes.clone();
+    future[4] = 2;
+    assert!(matches!(decode_verifying_key::<Bn254>(&future), Err(ZkpError::InvalidFile(_))));
+
+    // A verifying key is not a proving key or a proof
+    assert!(matches!(decode_proving_key::<Bn254>(&bytes), Err(ZkpError::InvalidFile(_))));
+    assert!(matches!(decode_proof::<Bn254>(&bytes), Err(ZkpError::InvalidFile(_))));
+
+    // The header must agree with the fingerprint the key carries
+    let mut relabelled = bytes.clone();
+    relabelled[8] ^= 1;
+    assert!(matches!(decode_verifying_key::<Bn254>(&relabelled), Err(ZkpError::InvalidFile(_))));
+}
+
+#[test]
+fn test_auto_decoding_detects_encoding() {
+    let (pk, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let proof = prove_age(&pk, Fr::from(424242u64), 8, 25, 18, None).unwrap();
+
+    for compress in [Compress::No, Compress::Yes] {
+        let mut bare_vk = Vec::new();
+        vk.serialize_with_mode(&mut bare_vk, compress).unwrap();
+        assert_eq!(decode_verifying_key_auto(&bare_vk).unwrap(), vk);
+
+        let mut bare_proof = Vec::new();
+        proof.serialize_with_mode(&mut bare_proof, compress).unwrap();
+        assert_eq!(decode_proof_auto(&bare_proof).unwrap(), (None, proof.clone()));
+
+        let container = encode_proof(&proof, pk.fingerprint, compress).unwrap();
+        assert_eq!(decode_proof_auto(&container).unwrap(), (Some(pk.fingerprint), proof.clone()));
+    }
+
+    // A point moved off the curve is rejected rather than accepted unchecked
+    let mut off_curve = Vec::new();
+    proof.serialize_uncompressed(&mut off_curve).unwrap();
+    off_curve[0] ^= 1;
+    assert!(matches!(decode_proof_auto::<Bn254>(&off_curve), Err(ZkpError::InvalidFile(_))));
+}
+
+#[test]
+fn test_key_file_records_the_curve() {
+    let config = AgeConfig { num_bits: 8, upper_bounded: false };
+    let (pk, vk) = insecure_seeded_setup::<Bls12_381, AgeVerification>(&config, 7).unwrap();
+    let public = AgePublicInputs { nonce: BlsFr::from(424242u64), min_age: 18, max_age: None };
+    let proof = prove::<Bls12_381, AgeVerification>(&pk, &config, &public, &AgePrivateInputs { user_age: 25 }).unwrap();
+
+    let vk_bytes = encode_verifying_key(&vk, Compress::Yes).unwrap();
+    let proof_bytes = encode_proof(&proof, vk.fingerprint, Compress::Yes).unwrap();
+    assert_eq!(decode_verifying_key::<Bls12_381>(&vk_bytes).unwrap(), vk);
+    assert_eq!(decode_proof::<Bls12_381>(&proof_bytes).unwrap(), (vk.fingerprint, proof));
+
+    // Reading a BLS12-381 file as BN254 fails on the header rather than on the points
+    assert!(matches!(decode_verifying_key::<Bn254>(&vk_bytes), Err(ZkpError::InvalidFile(_))));
+    assert!(matches!(decode_proof::<Bn254>(&proof_bytes), Err(ZkpError::InvalidFile(_))));
+
+    // Unknown curve
+    let mut unknown = vk_bytes.clone();
+    unknown[7] = 0xff;
+    assert!(matches!(decode_verifying_key::<Bls12_381>(&unknown), Err(ZkpError::InvalidFile(_))));
+}
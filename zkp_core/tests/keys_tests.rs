@@ -0,0 +1,99 @@
+use ark_bn254::Fr;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use zkp_core::attribute::AttributeCircuit;
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgeVerification};
+use zkp_core::circuits::citizenship_verification::witness_calculator::CitizenshipVerification;
+use zkp_core::error::ZkpError;
+use zkp_core::keys::{check_fingerprint, circuit_fingerprint};
+use zkp_core::proof_system::{prove_age, setup_age_verification_circuit, setup_citizenship_verification_circuit, verify_age};
+
+// ----------------------------
+// Circuit Fingerprint Tests
+// ----------------------------
+
+#[test]
+fn test_fingerprint_is_stable_per_shape() {
+    let config = AgeConfig::default();
+    let fingerprint = circuit_fingerprint::<Fr, AgeVerification>(&config).unwrap();
+    assert_eq!(fingerprint, circuit_fingerprint::<Fr, AgeVerification>(&config).unwrap());
+
+    // Another shape or another circuit gets its own fingerprint
+    let bracket = AgeConfig { upper_bounded: true, ..config };
+    assert_ne!(fingerprint, circuit_fingerprint::<Fr, AgeVerification>(&bracket).unwrap());
+    assert_ne!(fingerprint, circuit_fingerprint::<Fr, CitizenshipVerification>(&4).unwrap());
+
+    // Keys carry the fingerprint of the circuit they were generated for
+    let (pk, vk) = setup_age_verification_circuit(config.num_bits, false).unwrap();
+    assert_eq!(pk.fingerprint, fingerprint);
+    assert_eq!(vk.fingerprint, fingerprint);
+}
+
+#[test]
+fn test_verify_rejects_key_of_other_circuit() {
+    let (pk, _) = setup_age_verification_circuit(8, false).unwrap();
+    let (_, citizenship_vk) = setup_citizenship_verification_circuit(1).unwrap();
+    let nonce = Fr::from(424242u64);
+    let proof = prove_age(&pk, nonce, 8, 25, 18, None).unwrap();
+
+    // The citizenship key happens to expect as many public inputs as the age circuit, only the fingerprint differs
+    let result = verify_age(&citizenship_vk, &proof, nonce, 8, 18, None);
+    assert!(matches!(result, Err(ZkpError::KeyCircuitMismatch(_))));
+}
+
+// A circuit of `n` squarings that counts how often it is synthesized
+struct CountedCircuit;
+
+static SYNTHESES: AtomicUsize = AtomicUsize::new(0);
+
+struct Squarings(usize);
+
+impl ConstraintSynthesizer<Fr> for Squarings {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        SYNTHESES.fetch_add(1, Ordering::SeqCst);
+        let mut x = FpVar::new_witness(cs, || Ok(Fr::from(2u64)))?;
+        for _ in 0..self.0 {
+            x = x.square()?;
+        }
+        Ok(())
+    }
+}
+
+impl AttributeCircuit for CountedCircuit {
+    const NAME: &'static str = "counted_circuit";
+    const VERSION: u32 = 1;
+
+    type Config = usize;
+    type PublicInputs = ();
+    type PrivateInputs = ();
+    type Circuit = Squarings;
+
+    fn sample_inputs(_: &usize) -> ((), ()) {
+        ((), ())
+    }
+
+    fn circuit(n: &usize, _: &(), _: &()) -> Squarings {
+        Squarings(*n)
+    }
+
+    fn public_inputs(_: &()) -> Vec<Fr> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn test_fingerprint_is_computed_once_per_shape() {
+    let fingerprint = circuit_fingerprint::<Fr, CountedCircuit>(&3).unwrap();
+    let syntheses = SYNTHESES.load(Ordering::SeqCst);
+    for _ in 0..3 {
+        check_fingerprint::<Fr, CountedCircuit>(&fingerprint, &3).unwrap();
+    }
+    assert_eq!(SYNTHESES.load(Ordering::SeqCst), syntheses);
+
+    // A new shape is synthesized, and only once
+    let other = circuit_fingerprint::<Fr, CountedCircuit>(&4).unwrap();
+    assert_ne!(other, fingerprint);
+    assert_eq!(circuit_fingerprint::<Fr, CountedCircuit>(&4).unwrap(), other);
+    assert_eq!(SYNTHESES.load(Ordering::SeqCst), syntheses + 1);
+}
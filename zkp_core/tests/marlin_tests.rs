@@ -0,0 +1,174 @@
+#![cfg(feature = "experimental-marlin")]
+
+use ark_bn254::Fr;
+use ark_serialize::{CanonicalSerialize, Compress};
+use rand::{SeedableRng, thread_rng};
+use rand_chacha::ChaCha20Rng;
+use zkp_core::circuits::age_verification::witness_calculator::{
+    AgeConfig, AgePrivateInputs, AgePublicInputs, AgeVerification,
+};
+use zkp_core::error::ZkpError;
+use zkp_core::key_file::{
+    ProvingSystemId, decode_marlin_proof, decode_marlin_proving_key, decode_marlin_verifying_key,
+    decode_verifying_key, decode_verifying_key_auto, encode_marlin_proof, encode_marlin_proving_key,
+    encode_marlin_verifying_key, encode_verifying_key, proving_system,
+};
+use zkp_core::marlin;
+use zkp_core::powers_of_tau::PowersOfTau;
+use zkp_core::proof_system::setup_age_verification_circuit;
+
+// Keys and a proof for the 8 bit age circuit, as compressed key files. They were made with `setup_with_rng` and
+// `prove_with_rng` seeded as below, verified, and checked in; they must keep verifying and must be reproduced
+// byte for byte, so a change to the transcript, the polynomial layout or the encoding cannot go unnoticed.
+const VECTOR_VK: &[u8] = include_bytes!("fixtures/marlin_age_8.vk");
+const VECTOR_PROOF: &[u8] = include_bytes!("fixtures/marlin_age_8.proof");
+const VECTOR_SETUP_SEED: u64 = 7;
+const VECTOR_PROVE_SEED: u64 = 8;
+const AGE_CONFIG: AgeConfig = AgeConfig { num_bits: 8, upper_bounded: false };
+
+fn age_inputs(min_age: u64) -> (AgePublicInputs, AgePrivateInputs) {
+    (AgePublicInputs { nonce: Fr::from(424242u64), min_age, max_age: None }, AgePrivateInputs { user_age: 30 })
+}
+
+// ----------------------------
+// Known Vector Tests
+// ----------------------------
+
+#[test]
+fn test_marlin_verifies_known_vectors() {
+    let vk = decode_marlin_verifying_key(VECTOR_VK).unwrap();
+    let (fingerprint, proof) = decode_marlin_proof(VECTOR_PROOF).unwrap();
+    assert_eq!(fingerprint, vk.fingerprint);
+
+    let (public, _) = age_inputs(18);
+    assert!(marlin::verify::<AgeVerification>(&vk, &AGE_CONFIG, &proof, &public).unwrap());
+    assert!(!marlin::verify::<AgeVerification>(&vk, &AGE_CONFIG, &proof, &age_inputs(21).0).unwrap());
+}
+
+#[test]
+fn test_marlin_reproduces_known_vectors() {
+    let mut rng = ChaCha20Rng::seed_from_u64(VECTOR_SETUP_SEED);
+    let (pk, vk) = marlin::setup_with_rng::<AgeVerification, _>(&AGE_CONFIG, &mut rng).unwrap();
+    assert_eq!(encode_marlin_verifying_key(&vk, Compress::Yes).unwrap(), VECTOR_VK);
+
+    let (public, private) = age_inputs(18);
+    let mut rng = ChaCha20Rng::seed_from_u64(VECTOR_PROVE_SEED);
+    let proof = marlin::prove_with_rng::<AgeVerification, _>(&pk, &AGE_CONFIG, &public, &private, &mut rng).unwrap();
+    assert_eq!(encode_marlin_proof(&proof, vk.fingerprint, Compress::Yes).unwrap(), VECTOR_PROOF);
+}
+
+// ----------------------------
+// Marlin Tests
+// ----------------------------
+
+#[test]
+fn test_marlin_rejects_wrong_public_inputs_and_tampered_proofs() {
+    let (pk, vk) = marlin::setup_with_rng::<AgeVerification, _>(&AGE_CONFIG, &mut thread_rng()).unwrap();
+    let (public, private) = age_inputs(18);
+    let proof = marlin::prove::<AgeVerification>(&pk, &AGE_CONFIG, &public, &private).unwrap();
+    assert!(marlin::verify::<AgeVerification>(&vk, &AGE_CONFIG, &proof, &public).unwrap());
+
+    // The same proof claimed for another minimum age or nonce
+    assert!(!marlin::verify::<AgeVerification>(&vk, &AGE_CONFIG, &proof, &age_inputs(21).0).unwrap());
+    let other_nonce = AgePublicInputs { nonce: Fr::from(1u64), ..public };
+    assert!(!marlin::verify::<AgeVerification>(&vk, &AGE_CONFIG, &proof, &other_nonce).unwrap());
+
+    let mut forged = proof.clone();
+    forged.mask_sum += Fr::from(1u64);
+    assert!(!marlin::verify::<AgeVerification>(&vk, &AGE_CONFIG, &forged, &public).unwrap());
+
+    let mut forged = proof;
+    forged.first_evaluations[0] += Fr::from(1u64);
+    assert!(!marlin::verify::<AgeVerification>(&vk, &AGE_CONFIG, &forged, &public).unwrap());
+
+    // Inputs that do not satisfy the circuit are caught before proving
+    assert!(matches!(
+        marlin::prove::<AgeVerification>(&pk, &AGE_CONFIG, &age_inputs(40).0, &private),
+        Err(ZkpError::UnsatisfiedConstraint { .. })
+    ));
+}
+
+#[test]
+fn test_marlin_indexes_every_shape_from_one_powers_of_tau() {
+    let shapes = [AGE_CONFIG, AgeConfig { num_bits: 16, upper_bounded: true }];
+    let size = shapes.iter().map(|config| marlin::powers_of_tau_size::<AgeVerification>(config).unwrap()).max();
+    let mut powers = PowersOfTau::new(size.unwrap()).unwrap();
+    powers.contribute(&mut thread_rng());
+
+    let public = AgePublicInputs { nonce: Fr::from(424242u64), min_age: 18, max_age: Some(65) };
+    let private = AgePrivateInputs { user_age: 30 };
+    let keys: Vec<_> = shapes.iter().map(|config| marlin::index::<AgeVerification>(&powers, config).unwrap()).collect();
+    let (pk, vk) = &keys[1];
+    let proof = marlin::prove::<AgeVerification>(pk, &shapes[1], &public, &private).unwrap();
+    assert!(marlin::verify::<AgeVerification>(vk, &shapes[1], &proof, &public).unwrap());
+
+    // The keys commit with exactly the ceremony's powers
+    assert_eq!(pk.powers, powers.current.tau_g1);
+    assert_eq!(vk.tau_g2, powers.current.tau_g2[1]);
+
+    // Keys for one shape do not accept the other
+    assert!(matches!(
+        marlin::verify::<AgeVerification>(&keys[0].1, &shapes[1], &proof, &public),
+        Err(ZkpError::KeyCircuitMismatch(_))
+    ));
+
+    // Without a contribution tau is public
+    let uncontributed = PowersOfTau::new(size.unwrap()).unwrap();
+    assert!(matches!(marlin::index::<AgeVerification>(&uncontributed, &shapes[0]), Err(ZkpError::InvalidInput(_))));
+}
+
+#[test]
+fn test_marlin_key_sizes_match_encoding() {
+    let (pk, vk) = marlin::setup_with_rng::<AgeVerification, _>(&AGE_CONFIG, &mut thread_rng()).unwrap();
+    let (pk_size, vk_size) = marlin::key_sizes::<AgeVerification>(&AGE_CONFIG).unwrap();
+    let sizes = [
+        (Compress::Yes, pk_size.compressed, vk_size.compressed),
+        (Compress::No, pk_size.uncompressed, vk_size.uncompressed),
+    ];
+    for (compress, pk_len, vk_len) in sizes {
+        assert_eq!(encode_marlin_proving_key(&pk, compress).unwrap().len(), pk_len);
+        assert_eq!(encode_marlin_verifying_key(&vk, compress).unwrap().len(), vk_len);
+    }
+}
+
+// ----------------------------
+// Key File Tests
+// ----------------------------
+
+#[test]
+fn test_key_file_records_the_proving_system() {
+    let (pk, vk) = marlin::setup_with_rng::<AgeVerification, _>(&AGE_CONFIG, &mut thread_rng()).unwrap();
+    let (public, private) = age_inputs(18);
+    let proof = marlin::prove::<AgeVerification>(&pk, &AGE_CONFIG, &public, &private).unwrap();
+
+    for compress in [Compress::No, Compress::Yes] {
+        let pk_bytes = encode_marlin_proving_key(&pk, compress).unwrap();
+        let vk_bytes = encode_marlin_verifying_key(&vk, compress).unwrap();
+        let proof_bytes = encode_marlin_proof(&proof, vk.fingerprint, compress).unwrap();
+        assert_eq!(pk_bytes[41], ProvingSystemId::Marlin as u8);
+        assert_eq!(proving_system(&vk_bytes).unwrap(), ProvingSystemId::Marlin);
+        assert_eq!(decode_marlin_proving_key(&pk_bytes).unwrap(), pk);
+        assert_eq!(decode_marlin_verifying_key(&vk_bytes).unwrap(), vk);
+        assert_eq!(decode_marlin_proof(&proof_bytes).unwrap(), (vk.fingerprint, proof.clone()));
+    }
+
+    // Groth16 readers refuse Marlin files and the other way round, on the header rather than on the points
+    let vk_bytes = encode_marlin_verifying_key(&vk, Compress::No).unwrap();
+    assert!(matches!(decode_verifying_key::<ark_bn254::Bn254>(&vk_bytes), Err(ZkpError::InvalidFile(_))));
+    assert!(matches!(decode_verifying_key_auto::<ark_bn254::Bn254>(&vk_bytes), Err(ZkpError::InvalidFile(_))));
+    let (_, groth16_vk) = setup_age_verification_circuit(8, false).unwrap();
+    let groth16_bytes = encode_verifying_key(&groth16_vk, Compress::No).unwrap();
+    assert_eq!(proving_system(&groth16_bytes).unwrap(), ProvingSystemId::Groth16);
+    assert!(matches!(decode_marlin_verifying_key(&groth16_bytes), Err(ZkpError::InvalidFile(_))));
+
+    // Bare encodings predate the byte and are Groth16
+    let mut bare = Vec::new();
+    groth16_vk.serialize_compressed(&mut bare).unwrap();
+    assert_eq!(proving_system(&bare).unwrap(), ProvingSystemId::Groth16);
+
+    // Unknown proving system
+    let mut unknown = vk_bytes;
+    unknown[41] = 0xff;
+    assert!(matches!(proving_system(&unknown), Err(ZkpError::InvalidFile(_))));
+    assert!(matches!(decode_marlin_verifying_key(&unknown), Err(ZkpError::InvalidFile(_))));
+}
@@ -0,0 +1,88 @@
+use ark_bn254::Fr;
+use zkp_core::error::ZkpError;
+use zkp_core::merkle::{EMPTY_LEAF, MerkleTree, MerkleTreeError};
+use zkp_core::proof_system::{
+    setup_citizenship_verification_circuit, prove_citizenship, verify_citizenship,
+};
+
+const TEST_TREE_DEPTH: usize = 4;
+
+// Hashed citizen IDs used to populate the test registry
+fn sample_leaves() -> Vec<Fr> {
+    (101..=105u64).map(Fr::from).collect()
+}
+
+// ----------------------------
+// Merkle Tree Tests
+// ----------------------------
+
+#[test]
+fn test_paths_recompute_root() {
+    let tree = MerkleTree::from_leaves(TEST_TREE_DEPTH, &sample_leaves()).expect("Tree build failed");
+
+    // Every occupied and unoccupied slot has a path leading to the root
+    for index in 0..tree.capacity() {
+        let leaf = tree.leaf(index).unwrap();
+        let path = tree.path(index).unwrap();
+        assert_eq!(path.depth(), TEST_TREE_DEPTH);
+        assert_eq!(path.compute_root(leaf), tree.root());
+    }
+}
+
+#[test]
+fn test_incremental_updates_match_rebuild() {
+    let mut leaves = sample_leaves();
+    let mut tree = MerkleTree::new(TEST_TREE_DEPTH);
+    for leaf in &leaves {
+        tree.insert(*leaf).unwrap();
+    }
+    assert_eq!(tree.root(), MerkleTree::from_leaves(TEST_TREE_DEPTH, &leaves).unwrap().root());
+
+    // Replace one citizen and compare with a tree built from scratch
+    tree.update(2, Fr::from(999u64)).unwrap();
+    leaves[2] = Fr::from(999u64);
+    assert_eq!(tree.root(), MerkleTree::from_leaves(TEST_TREE_DEPTH, &leaves).unwrap().root());
+
+    // Removing the last citizen yields the same root as never inserting it
+    tree.remove(4).unwrap();
+    leaves.pop();
+    assert_eq!(tree.root(), MerkleTree::from_leaves(TEST_TREE_DEPTH, &leaves).unwrap().root());
+    assert_eq!(tree.leaf(4).unwrap(), EMPTY_LEAF);
+    assert_eq!(tree.index_of(Fr::from(999u64)), Some(2));
+}
+
+#[test]
+fn test_invalid_operations_are_rejected() {
+    let mut tree = MerkleTree::new(1);
+    assert_eq!(tree.insert(EMPTY_LEAF), Err(MerkleTreeError::EmptyLeaf));
+    tree.insert(Fr::from(1u64)).unwrap();
+    tree.insert(Fr::from(2u64)).unwrap();
+    assert_eq!(tree.insert(Fr::from(3u64)), Err(MerkleTreeError::TreeFull { capacity: 2 }));
+    assert_eq!(
+        tree.path(2),
+        Err(MerkleTreeError::IndexOutOfRange { index: 2, capacity: 2 })
+    );
+}
+
+#[test]
+fn test_registry_proof_end_to_end() {
+    let (pk, vk) = setup_citizenship_verification_circuit(TEST_TREE_DEPTH).expect("Setup failed");
+    let mut tree = MerkleTree::from_leaves(TEST_TREE_DEPTH, &sample_leaves()).expect("Tree build failed");
+
+    // A registered citizen can prove membership against the current root
+    let leaf = Fr::from(103u64);
+    let index = tree.index_of(leaf).unwrap();
+    let proof = prove_citizenship(&pk, Fr::from(1u64), TEST_TREE_DEPTH, tree.root(), leaf, tree.path(index).unwrap())
+        .expect("Proof generation failed");
+    assert!(verify_citizenship(&vk, &proof, Fr::from(1u64), TEST_TREE_DEPTH, tree.root()).expect("Verification failed"));
+
+    // Once removed, the citizen's slot is empty and cannot be proven
+    tree.remove(index).unwrap();
+    let root = tree.root();
+    let path = tree.path(index).unwrap();
+    let proof = prove_citizenship(&pk, Fr::from(1u64), TEST_TREE_DEPTH, root, EMPTY_LEAF, path);
+    assert!(matches!(
+        proof,
+        Err(ZkpError::UnsatisfiedConstraint { constraint: Some(name) }) if name.starts_with("leaf_not_empty")
+    ));
+}
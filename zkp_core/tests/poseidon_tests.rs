@@ -0,0 +1,56 @@
+use ark_bn254::Fr;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::ConstraintSystem;
+use zkp_core::poseidon::{PoseidonParameters, poseidon_hash, poseidon_hash_gadget, poseidon_hash_two};
+
+// ----------------------------
+// Poseidon Hash Tests
+// ----------------------------
+
+#[test]
+fn test_gadget_matches_native_hash() {
+    for num_inputs in 1..=5u64 {
+        let params = PoseidonParameters::<Fr>::for_inputs(num_inputs as usize);
+        let inputs: Vec<Fr> = (0..num_inputs).map(|i| Fr::from(i * 7 + 3)).collect();
+
+        // Hash the inputs natively
+        let expected = poseidon_hash(&params, &inputs);
+
+        // Hash the same inputs in-circuit
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let input_vars: Vec<FpVar<Fr>> = inputs
+            .iter()
+            .map(|x| FpVar::new_witness(cs.clone(), || Ok(*x)).unwrap())
+            .collect();
+        let hash_var = poseidon_hash_gadget(&params, &input_vars).expect("Gadget failed");
+
+        assert_eq!(hash_var.value().unwrap(), expected);
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
+
+#[test]
+fn test_hash_is_not_a_sum() {
+    // The old dummy gadget accepted any pair summing to the root
+    let root = poseidon_hash_two(Fr::from(4u64), Fr::from(6u64));
+    assert_ne!(root, Fr::from(10u64));
+    assert_ne!(root, poseidon_hash_two(Fr::from(5u64), Fr::from(5u64)));
+    assert_ne!(root, poseidon_hash_two(Fr::from(6u64), Fr::from(4u64)));
+}
+
+#[test]
+fn test_parameters_are_deterministic() {
+    let a = PoseidonParameters::<Fr>::new(3, 8, 57, 5);
+    let b = PoseidonParameters::<Fr>::for_width(3);
+    assert_eq!(a, b);
+    assert_eq!(a.ark.len(), 8 + 57);
+    assert_eq!(a.mds.len(), 3);
+}
+
+#[test]
+fn test_shared_parameters_are_derived_once() {
+    let shared = PoseidonParameters::<Fr>::shared(2);
+    assert_eq!(*shared, PoseidonParameters::<Fr>::for_inputs(2));
+    assert!(std::ptr::eq(shared, PoseidonParameters::<Fr>::shared(2)));
+    assert_eq!(PoseidonParameters::<Fr>::shared(5).width, 6);
+}
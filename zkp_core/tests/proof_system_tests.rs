@@ -5,8 +5,51 @@ use zkp_core::proof_system::{
     setup_citizenship_verification_circuit,
     prove_citizenship,
     verify_citizenship,
+    setup_credential_verification_circuit,
+    prove_college_credential,
+    verify_college_credential,
+    setup_date_of_birth_verification_circuit,
+    prove_date_of_birth,
+    verify_date_of_birth,
+    setup_signed_age_verification_circuit,
+    prove_signed_age,
+    verify_signed_age,
+    insecure_seeded_setup,
+    prove,
+    prove_with_rng,
+    setup,
+    verify,
 };
-use ark_bn254::Fr;
+use zkp_core::circuits::age_verification::witness_calculator::{
+    AgeConfig, AgePrivateInputs, AgePublicInputs, AgeVerification, DEFAULT_AGE_BITS,
+};
+use zkp_core::circuits::citizenship_verification::witness_calculator::{
+    CitizenshipPrivateInputs, CitizenshipPublicInputs, CitizenshipVerification, MAX_TREE_DEPTH, MerklePath,
+};
+use zkp_core::circuits::college_credential_verification::witness_calculator::college_credential_digest;
+use zkp_core::circuits::date_of_birth_verification::witness_calculator::Date;
+use zkp_core::circuits::signed_age_verification::witness_calculator::{
+    SignedAgePrivateInputs, birth_date_credential_digest,
+};
+use zkp_core::babyjubjub::Fs;
+use zkp_core::eddsa::SecretKey;
+use zkp_core::error::ZkpError;
+use zkp_core::issuer::holder_commitment;
+use zkp_core::keys::circuit_fingerprint;
+use ark_bls12_381::{Bls12_381, Fr as BlsFr};
+use ark_bn254::{Bn254, Fr};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+// Verifier challenge the proofs in these tests are bound to
+fn nonce() -> Fr {
+    Fr::from(424242u64)
+}
+
+// Whether proving failed on an unsatisfied constraint in the given namespace
+fn unsatisfied_at<T>(result: Result<T, ZkpError>, namespace: &str) -> bool {
+    matches!(result, Err(ZkpError::UnsatisfiedConstraint { constraint: Some(name) }) if name.starts_with(namespace))
+}
 
 // ----------------------------
 // Age Verification Tests
@@ -15,99 +58,384 @@ use ark_bn254::Fr;
 #[test]
 fn test_full_proof_verification() {
     // Generate verifying and proving keys for age circuit
-    let (pk, vk) = setup_age_verification_circuit().expect("Setup failed");
+    let (pk, vk) = setup_age_verification_circuit(DEFAULT_AGE_BITS, false).expect("Setup failed");
 
     // Generate the proof (user_age = 25, min_age = 18) should return valid proof
-    let proof = prove_age(&pk, 25, 18).expect("Proof generation failed");
+    let proof = prove_age(&pk, nonce(), DEFAULT_AGE_BITS, 25, 18, None).expect("Proof generation failed");
     
     // Assert that the generated zkp proof has the user's age >= 18
-    assert!(verify_age(&vk, &proof, 18).expect("Verification failed"));
+    assert!(verify_age(&vk, &proof, nonce(), DEFAULT_AGE_BITS, 18, None).expect("Verification failed"));
 }
 
 #[test]
 fn test_fails_when_age_is_not_greater() {
     // Generate verifying and proving keys for age circuit
-    let (pk, _vk) = setup_age_verification_circuit().expect("Setup failed");
+    let (pk, _vk) = setup_age_verification_circuit(DEFAULT_AGE_BITS, false).expect("Setup failed");
 
     // Generate proof (should fail) because user_age < min_age
-    let proof = std::panic::catch_unwind(|| prove_age(&pk, 17, 18));
-    
-    // Verify that the proof creation actually failed
-    assert!(proof.is_err());
+    let proof = prove_age(&pk, nonce(), DEFAULT_AGE_BITS, 17, 18, None);
+
+    // Proof creation fails and names the violated comparison
+    assert!(unsatisfied_at(proof, "user_age_at_least_min_age"));
+}
+
+#[test]
+fn test_fails_when_age_exceeds_bit_width() {
+    // Generate verifying and proving keys for an 8-bit age circuit
+    let (pk, _vk) = setup_age_verification_circuit(DEFAULT_AGE_BITS, false).expect("Setup failed");
+
+    // Generate proof (should fail) because 300 does not fit in 8 bits, even though 300 >= 18
+    let proof = prove_age(&pk, nonce(), DEFAULT_AGE_BITS, 300, 18, None);
+
+    // Proof creation fails on the range check rather than the comparison
+    assert!(unsatisfied_at(proof, "user_age_in_range"));
+}
+
+#[test]
+fn test_age_bracket_proof_verification() {
+    // Generate verifying and proving keys for a 16-bit age circuit with an upper bound
+    let (pk, vk) = setup_age_verification_circuit(16, true).expect("Setup failed");
+
+    // Generate the proof (18 <= 21 <= 25) should return valid proof
+    let proof = prove_age(&pk, nonce(), 16, 21, 18, Some(25)).expect("Proof generation failed");
+
+    // The proof verifies against the bracket it was generated for only
+    assert!(verify_age(&vk, &proof, nonce(), 16, 18, Some(25)).expect("Verification failed"));
+    assert!(!verify_age(&vk, &proof, nonce(), 16, 18, Some(30)).expect("Verification failed"));
+
+    // Generate proof (should fail) because user_age > max_age
+    let proof = prove_age(&pk, nonce(), 16, 26, 18, Some(25));
+    assert!(unsatisfied_at(proof, "user_age_at_most_max_age"));
+}
+
+#[test]
+fn test_age_proof_rejects_key_of_other_shape() {
+    // Keys for the bracket circuit expect one more public input than the plain threshold circuit
+    let (pk, vk) = setup_age_verification_circuit(DEFAULT_AGE_BITS, true).expect("Setup failed");
+
+    let result = prove_age(&pk, nonce(), DEFAULT_AGE_BITS, 25, 18, None);
+    assert!(matches!(result, Err(ZkpError::KeyCircuitMismatch(_))));
+
+    let proof = prove_age(&pk, nonce(), DEFAULT_AGE_BITS, 21, 18, Some(25)).expect("Proof generation failed");
+    let result = verify_age(&vk, &proof, nonce(), DEFAULT_AGE_BITS, 18, None);
+    assert!(matches!(result, Err(ZkpError::KeyCircuitMismatch(_))));
+}
+
+#[test]
+fn test_seeded_setup_and_proving_are_reproducible() {
+    let config = AgeConfig::default();
+
+    // The same seed yields the same keys, a different one does not
+    let (pk, vk) = insecure_seeded_setup::<Bn254, AgeVerification>(&config, 42).expect("Setup failed");
+    let (_, same_vk) = insecure_seeded_setup::<Bn254, AgeVerification>(&config, 42).expect("Setup failed");
+    let (_, other_vk) = insecure_seeded_setup::<Bn254, AgeVerification>(&config, 43).expect("Setup failed");
+    assert_eq!(vk, same_vk);
+    assert_ne!(vk, other_vk);
+
+    // Proving with a seeded RNG is deterministic too
+    let public = AgePublicInputs { nonce: nonce(), min_age: 18, max_age: None };
+    let private = AgePrivateInputs { user_age: 30 };
+    let proof = prove_with_rng::<Bn254, AgeVerification, _>(&pk, &config, &public, &private, &mut ChaCha20Rng::seed_from_u64(7))
+        .expect("Proof generation failed");
+    let same_proof = prove_with_rng::<Bn254, AgeVerification, _>(&pk, &config, &public, &private, &mut ChaCha20Rng::seed_from_u64(7))
+        .expect("Proof generation failed");
+    assert_eq!(proof, same_proof);
+    assert!(verify::<Bn254, AgeVerification>(&vk, &config, &proof, &public).expect("Verification failed"));
+}
+
+// ----------------------------
+// Date of Birth Verification Tests
+// ----------------------------
+
+fn date(year: u32, month: u32, day: u32) -> Date {
+    Date::new(year, month, day).expect("Invalid date")
+}
+
+#[test]
+fn test_date_of_birth_proof_on_birthday() {
+    let (pk, vk) = setup_date_of_birth_verification_circuit().expect("Setup failed");
+
+    // Born 2008-10-18, so exactly 18 on 2026-10-18
+    let today = date(2026, 10, 18);
+    let proof = prove_date_of_birth(&pk, nonce(), date(2008, 10, 18), today, 18).expect("Proof generation failed");
+
+    assert!(verify_date_of_birth(&vk, &proof, nonce(), today, 18).expect("Verification failed"));
+    // The proof is bound to the reference date and threshold it was generated for
+    assert!(!verify_date_of_birth(&vk, &proof, nonce(), date(2026, 10, 19), 18).expect("Verification failed"));
+    assert!(!verify_date_of_birth(&vk, &proof, nonce(), today, 21).expect("Verification failed"));
+}
+
+#[test]
+fn test_date_of_birth_fails_day_before_birthday() {
+    let (pk, _vk) = setup_date_of_birth_verification_circuit().expect("Setup failed");
+
+    // Born 2008-10-19, so still 17 on 2026-10-18
+    let proof = prove_date_of_birth(&pk, nonce(), date(2008, 10, 19), date(2026, 10, 18), 18);
+    assert!(unsatisfied_at(proof, "old_enough_on_current_date"));
+}
+
+#[test]
+fn test_date_of_birth_leap_day() {
+    let (pk, vk) = setup_date_of_birth_verification_circuit().expect("Setup failed");
+    let birth_date = date(2008, 2, 29);
+    assert!(Date::new(2026, 2, 29).is_none());
+
+    // Someone born on February 29 comes of age on March 1 in a non-leap year
+    let proof = prove_date_of_birth(&pk, nonce(), birth_date, date(2026, 2, 28), 18);
+    assert!(unsatisfied_at(proof, "old_enough_on_current_date"));
+
+    let proof = prove_date_of_birth(&pk, nonce(), birth_date, date(2026, 3, 1), 18).expect("Proof generation failed");
+    assert!(verify_date_of_birth(&vk, &proof, nonce(), date(2026, 3, 1), 18).expect("Verification failed"));
+}
+
+// ----------------------------
+// Signed Age Verification Tests
+// ----------------------------
+
+#[test]
+fn test_signed_age_proof_verification() {
+    let (pk, vk) = setup_signed_age_verification_circuit().expect("Setup failed");
+
+    // The issuer signs the holder's date of birth and the commitment to their secret
+    let issuer = SecretKey(Fs::from(77u64));
+    let birth_date = date(2000, 5, 17);
+    let holder_secret = Fr::from(31337u64);
+    let signature = issuer.sign(birth_date_credential_digest(&birth_date, holder_commitment(holder_secret)));
+    let credential = SignedAgePrivateInputs { birth_date, holder_secret, signature };
+
+    let today = date(2026, 10, 18);
+    let proof = prove_signed_age(&pk, nonce(), &issuer.public_key(), &credential, today, 18)
+        .expect("Proof generation failed");
+    assert!(verify_signed_age(&vk, &proof, nonce(), &issuer.public_key(), today, 18).expect("Verification failed"));
+
+    // A different issuer key does not accept the proof
+    let other = SecretKey(Fs::from(78u64)).public_key();
+    assert!(!verify_signed_age(&vk, &proof, nonce(), &other, today, 18).expect("Verification failed"));
+}
+
+#[test]
+fn test_signed_age_fails_for_unsigned_birth_date() {
+    let (pk, _vk) = setup_signed_age_verification_circuit().expect("Setup failed");
+
+    // The issuer signed a 2010 birth date, but the holder claims an earlier one to pass the threshold
+    let issuer = SecretKey(Fs::from(77u64));
+    let holder_secret = Fr::from(31337u64);
+    let signature = issuer.sign(birth_date_credential_digest(&date(2010, 5, 17), holder_commitment(holder_secret)));
+    let credential = SignedAgePrivateInputs { birth_date: date(2000, 5, 17), holder_secret, signature };
+
+    let proof = prove_signed_age(&pk, nonce(), &issuer.public_key(), &credential, date(2026, 10, 18), 18);
+    assert!(unsatisfied_at(proof, "signature_valid"));
+}
+
+#[test]
+fn test_signed_age_is_bound_to_the_holder() {
+    let (pk, _vk) = setup_signed_age_verification_circuit().expect("Setup failed");
+
+    // Someone who obtained another holder's signed credential, but not their secret, cannot prove with it
+    let issuer = SecretKey(Fs::from(77u64));
+    let birth_date = date(2000, 5, 17);
+    let signature = issuer.sign(birth_date_credential_digest(&birth_date, holder_commitment(Fr::from(31337u64))));
+    let credential = SignedAgePrivateInputs { birth_date, holder_secret: Fr::from(31338u64), signature };
+
+    let proof = prove_signed_age(&pk, nonce(), &issuer.public_key(), &credential, date(2026, 10, 18), 18);
+    assert!(unsatisfied_at(proof, "signature_valid"));
 }
 
 // ----------------------------
 // Citizenship Verification Tests
 // ----------------------------
 
+// Small tree depth to keep the citizenship tests fast
+const TEST_TREE_DEPTH: usize = 4;
+
+// Builds an arbitrary authentication path of the test depth
+fn sample_merkle_path() -> MerklePath {
+    MerklePath {
+        siblings: (1..=TEST_TREE_DEPTH as u64).map(Fr::from).collect(),
+        directions: vec![true, false, false, true],
+    }
+}
+
 #[test]
 fn test_citizenship_proof_passes() {
     // Generate verifying and proving keys for the citizenship circuit
-    let (pk, vk) = setup_citizenship_verification_circuit().expect("Setup failed");
+    let (pk, vk) = setup_citizenship_verification_circuit(TEST_TREE_DEPTH).expect("Setup failed");
 
-    // Set up "path + leaf" == root
-    let root = Fr::from(10u64);
-    let path = Fr::from(4u64);
+    // Compute the root reached from the leaf along the path
     let leaf = Fr::from(6u64);
+    let path = sample_merkle_path();
+    let root = path.compute_root(leaf);
 
     // Generate a proof
-    let proof = prove_citizenship(&pk, Some(root), Some(path), Some(leaf)).expect("Proof generation failed");
+    let proof = prove_citizenship(&pk, nonce(), TEST_TREE_DEPTH, root, leaf, path).expect("Proof generation failed");
+
+    // This should verify, because hashing the leaf up along the path yields the root
+    assert!(verify_citizenship(&vk, &proof, nonce(), TEST_TREE_DEPTH, root).expect("Verification failed"));
 
-    // This should verify, because 4 + 6 = 10 (our dummy "hash" is just sum)
-    assert!(verify_citizenship(&vk, &proof, root).expect("Verification failed"));
+    // Replaying the proof against another verifier challenge fails
+    assert!(!verify_citizenship(&vk, &proof, Fr::from(1u64), TEST_TREE_DEPTH, root).expect("Verification failed"));
 }
 
 #[test]
 fn test_citizenship_proof_fails() {
     // Generate verifying and proving keys for the citizenship circuit
-    let (pk, _vk) = setup_citizenship_verification_circuit().expect("Setup failed");
+    let (pk, _vk) = setup_citizenship_verification_circuit(TEST_TREE_DEPTH).expect("Setup failed");
+
+    // Now the leaf is not the one the root was computed from
+    let path = sample_merkle_path();
+    let root = path.compute_root(Fr::from(6u64));
+    let leaf = Fr::from(7u64);
+
+    // Proof generation fails, because hashing the leaf up along the path does not reach the root
+    let proof = prove_citizenship(&pk, nonce(), TEST_TREE_DEPTH, root, leaf, path);
+    assert!(unsatisfied_at(proof, "root_matches"));
+}
+
+#[test]
+fn test_citizenship_proof_rejects_wrong_depth() {
+    // Generate verifying and proving keys for the citizenship circuit
+    let (pk, _vk) = setup_citizenship_verification_circuit(TEST_TREE_DEPTH).expect("Setup failed");
 
-    // Now path + leaf != root
-    let root = Fr::from(10u64);
-    let path = Fr::from(5u64);
+    // A path one level too short cannot be proven against these keys
     let leaf = Fr::from(6u64);
+    let mut path = sample_merkle_path();
+    path.siblings.pop();
+    path.directions.pop();
+    let root = path.compute_root(leaf);
 
-    // Generate the proof (the proof creation itself doesn't fail—it's just wrong)
-    let proof = std::panic::catch_unwind(|| prove_citizenship(&pk, Some(root), Some(path), Some(leaf)).expect("Proof generation failed"));
+    let result = prove_citizenship(&pk, nonce(), TEST_TREE_DEPTH, root, leaf, path);
+    assert!(matches!(result, Err(ZkpError::InvalidInput(_))));
+}
 
-    // Verification should return false
-    assert!(proof.is_err());
+#[test]
+fn test_citizenship_rejects_depth_past_maximum() {
+    // Rejected before a path of that depth is ever sampled, instead of exhausting memory
+    let depth = 10_000_000_000;
+    assert!(matches!(setup_citizenship_verification_circuit(depth), Err(ZkpError::InvalidInput(_))));
+    assert!(matches!(circuit_fingerprint::<Fr, CitizenshipVerification>(&depth), Err(ZkpError::InvalidInput(_))));
+    assert!(matches!(
+        circuit_fingerprint::<Fr, CitizenshipVerification>(&(MAX_TREE_DEPTH + 1)),
+        Err(ZkpError::InvalidInput(_))
+    ));
 }
 
 // ----------------------------
 // College Credential Verification Tests
 // ----------------------------
 
+// University signing key used by the college credential tests
+fn sample_university_key() -> SecretKey {
+    SecretKey(Fs::from(123456789u64))
+}
+
 #[test]
 fn test_college_credential_proof_passes() {
-    // Generate verifying and proving keys for the citizenship circuit
-    let (pk, vk) = setup_citizenship_verification_circuit().expect("Setup failed");
+    // Generate verifying and proving keys for the college credential circuit
+    let (pk, vk) = setup_credential_verification_circuit().expect("Setup failed");
 
-    // Set up "credential + signature" == university_public_key
-    let university_public_key = Fr::from(25u64);
+    // The university signs the digest of the user's credential and the commitment to their secret
+    let university_key = sample_university_key();
+    let university_public_key = university_key.public_key();
     let credential = Fr::from(10u64);
-    let signature = Fr::from(15u64);
+    let holder_secret = Fr::from(31337u64);
+    let signature = university_key.sign(college_credential_digest(credential, holder_commitment(holder_secret)));
 
     // Generate a proof
-    let proof = prove_citizenship(&pk, Some(university_public_key), Some(credential), Some(signature)).expect("Proof generation failed");
+    let proof = prove_college_credential(&pk, nonce(), &university_public_key, credential, holder_secret, &signature)
+        .expect("Proof generation failed");
 
     // This should verify
-    assert!(verify_citizenship(&vk, &proof, university_public_key).expect("Verification failed"));
+    assert!(verify_college_credential(&vk, &proof, nonce(), &university_public_key).expect("Verification failed"));
 }
 
 #[test]
 fn test_college_credential_proof_fails() {
-    // Generate verifying and proving keys for the citizenship circuit
-    let (pk, _vk) = setup_citizenship_verification_circuit().expect("Setup failed");
+    // Generate verifying and proving keys for the college credential circuit
+    let (pk, _vk) = setup_credential_verification_circuit().expect("Setup failed");
 
-    // Now credential + signature != univeristy_public_key
-    let university_public_key = Fr::from(25u64);
+    // Now the signature was made over a different credential
+    let university_key = sample_university_key();
+    let university_public_key = university_key.public_key();
     let credential = Fr::from(10u64);
-    let signature = Fr::from(10u64);
+    let holder_secret = Fr::from(31337u64);
+    let signature = university_key.sign(college_credential_digest(Fr::from(11u64), holder_commitment(holder_secret)));
 
-    // Generate the proof (the proof creation itself doesn't fail—it's just wrong)
-    let proof = std::panic::catch_unwind(|| prove_citizenship(&pk, Some(university_public_key), Some(credential), Some(signature)).expect("Proof generation failed"));
+    // Proof generation fails, because the signature does not cover this credential
+    let proof = prove_college_credential(&pk, nonce(), &university_public_key, credential, holder_secret, &signature);
+    assert!(unsatisfied_at(proof, "signature_valid"));
+}
+
+#[test]
+fn test_college_credential_is_bound_to_the_holder() {
+    let (pk, _vk) = setup_credential_verification_circuit().expect("Setup failed");
+
+    // Someone who obtained another graduate's signed credential, but not their secret, cannot prove with it
+    let university_key = sample_university_key();
+    let credential = Fr::from(10u64);
+    let signature = university_key.sign(college_credential_digest(credential, holder_commitment(Fr::from(31337u64))));
+
+    let public_key = university_key.public_key();
+    let proof = prove_college_credential(&pk, nonce(), &public_key, credential, Fr::from(31338u64), &signature);
+    assert!(unsatisfied_at(proof, "signature_valid"));
+}
 
-    // Verification should return false
-    assert!(proof.is_err());
-}
\ No newline at end of file
+#[test]
+fn test_college_credential_proof_rejects_other_university() {
+    // Generate verifying and proving keys for the college credential circuit
+    let (pk, vk) = setup_credential_verification_circuit().expect("Setup failed");
+
+    // A valid proof for one university must not verify against another university's key
+    let university_key = sample_university_key();
+    let credential = Fr::from(10u64);
+    let holder_secret = Fr::from(31337u64);
+    let signature = university_key.sign(college_credential_digest(credential, holder_commitment(holder_secret)));
+    let university_public_key = university_key.public_key();
+    let proof = prove_college_credential(&pk, nonce(), &university_public_key, credential, holder_secret, &signature)
+        .expect("Proof generation failed");
+
+    let other_university = SecretKey(Fs::from(987654321u64)).public_key();
+    assert!(!verify_college_credential(&vk, &proof, nonce(), &other_university).expect("Verification failed"));
+}
+
+// ----------------------------
+// BLS12-381 Tests
+// ----------------------------
+
+#[test]
+fn test_age_proof_on_bls12_381() {
+    let config = AgeConfig { num_bits: DEFAULT_AGE_BITS, upper_bounded: true };
+    let (pk, vk) = setup::<Bls12_381, AgeVerification>(&config).expect("Setup failed");
+
+    let public = AgePublicInputs { nonce: BlsFr::from(424242u64), min_age: 18, max_age: Some(65) };
+    let proof = prove::<Bls12_381, AgeVerification>(&pk, &config, &public, &AgePrivateInputs { user_age: 30 })
+        .expect("Proof generation failed");
+    assert!(verify::<Bls12_381, AgeVerification>(&vk, &config, &proof, &public).expect("Verification failed"));
+
+    let wrong = AgePublicInputs { min_age: 40, ..public };
+    assert!(!verify::<Bls12_381, AgeVerification>(&vk, &config, &proof, &wrong).expect("Verification failed"));
+}
+
+#[test]
+fn test_citizenship_proof_on_bls12_381() {
+    let depth = 4;
+    let leaf = BlsFr::from(98765u64);
+    let path = MerklePath {
+        siblings: (1..=depth as u64).map(BlsFr::from).collect(),
+        directions: vec![true, false, false, true],
+    };
+    let public = CitizenshipPublicInputs { nonce: BlsFr::from(424242u64), merkle_root: path.compute_root(leaf) };
+
+    let (pk, vk) = setup::<Bls12_381, CitizenshipVerification>(&depth).expect("Setup failed");
+    let proof = prove::<Bls12_381, CitizenshipVerification>(&pk, &depth, &public, &CitizenshipPrivateInputs { leaf, path })
+        .expect("Proof generation failed");
+    assert!(verify::<Bls12_381, CitizenshipVerification>(&vk, &depth, &proof, &public).expect("Verification failed"));
+}
+
+#[test]
+fn test_keys_from_one_curve_are_rejected_for_the_other() {
+    // The fingerprint covers the scalar field, so a circuit has a different one on each curve
+    let config = AgeConfig::default();
+    let (bn254_pk, _) = insecure_seeded_setup::<Bn254, AgeVerification>(&config, 1).expect("Setup failed");
+    let (bls_pk, _) = insecure_seeded_setup::<Bls12_381, AgeVerification>(&config, 1).expect("Setup failed");
+    assert_ne!(bn254_pk.fingerprint, bls_pk.fingerprint);
+}
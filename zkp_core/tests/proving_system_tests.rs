@@ -0,0 +1,49 @@
+use ark_bls12_381::Fr as BlsFr;
+use ark_bn254::Fr;
+use rand::thread_rng;
+use zkp_core::attribute::AttributeCircuit;
+use zkp_core::circuits::age_verification::witness_calculator::{
+    AgeConfig, AgePrivateInputs, AgePublicInputs, AgeVerification,
+};
+use zkp_core::proving_system::{Groth16Bls12_381, Groth16Bn254, ProvingSystem};
+#[cfg(feature = "experimental-marlin")]
+use zkp_core::proving_system::MarlinBn254;
+
+// ----------------------------
+// Proving System Tests
+// ----------------------------
+
+// Written once against the abstraction, runnable under any proving system
+fn prove_and_verify<S: ProvingSystem, C: AttributeCircuit<S::Field>>(
+    config: &C::Config,
+    public: &C::PublicInputs,
+    private: &C::PrivateInputs,
+) -> bool {
+    let mut rng = thread_rng();
+    let (pk, vk) = S::setup::<C, _>(config, &mut rng).expect("Setup failed");
+    let proof = S::prove::<C, _>(&pk, config, public, private, &mut rng).expect("Proof generation failed");
+    S::verify::<C>(&vk, config, &proof, public).expect("Verification failed")
+}
+
+#[test]
+fn test_age_proof_under_groth16() {
+    let public = AgePublicInputs { nonce: Fr::from(424242u64), min_age: 18, max_age: None };
+    let private = AgePrivateInputs { user_age: 30 };
+    assert!(prove_and_verify::<Groth16Bn254, AgeVerification>(&AgeConfig::default(), &public, &private));
+}
+
+// The same circuit and inputs as under Groth16
+#[cfg(feature = "experimental-marlin")]
+#[test]
+fn test_age_proof_under_marlin() {
+    let public = AgePublicInputs { nonce: Fr::from(424242u64), min_age: 18, max_age: None };
+    let private = AgePrivateInputs { user_age: 30 };
+    assert!(prove_and_verify::<MarlinBn254, AgeVerification>(&AgeConfig::default(), &public, &private));
+}
+
+#[test]
+fn test_age_proof_under_groth16_on_bls12_381() {
+    let public = AgePublicInputs { nonce: BlsFr::from(424242u64), min_age: 18, max_age: None };
+    let private = AgePrivateInputs { user_age: 30 };
+    assert!(prove_and_verify::<Groth16Bls12_381, AgeVerification>(&AgeConfig::default(), &public, &private));
+}
@@ -0,0 +1,108 @@
+use ark_bn254::Fr;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::ns;
+use ark_relations::r1cs::{
+    ConstraintLayer, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, TracingMode,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::{Subscriber, span};
+use tracing_subscriber::Registry;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use zkp_core::circuits::age_verification::witness_calculator::{
+    AgeConfig, AgePrivateInputs, AgePublicInputs, AgeVerification,
+};
+use zkp_core::circuits::citizenship_verification::witness_calculator::{
+    MerklePath, calculate_citizenship_verification_witness,
+};
+use zkp_core::error::ZkpError;
+use zkp_core::satisfiability::{check_satisfied, first_unsatisfied};
+
+// ----------------------------
+// Satisfiability Tests
+// ----------------------------
+
+#[test]
+fn test_check_satisfied_names_the_failing_constraint() {
+    let config = AgeConfig { num_bits: 8, upper_bounded: true };
+    let public = AgePublicInputs { nonce: Fr::from(424242u64), min_age: 18, max_age: Some(25) };
+    let check = |user_age| check_satisfied::<Fr, AgeVerification>(&config, &public, &AgePrivateInputs { user_age });
+
+    assert!(check(21).is_ok());
+    for (user_age, namespace) in [(17, "user_age_at_least_min_age"), (26, "user_age_at_most_max_age")] {
+        match check(user_age) {
+            Err(ZkpError::UnsatisfiedConstraint { constraint: Some(name) }) => assert!(name.starts_with(namespace)),
+            other => panic!("expected an unsatisfied constraint, got {:?}", other),
+        }
+    }
+}
+
+// x * x == y, once inside a namespace and once outside any; squaring adds constraint #0, the equality is #1
+struct Square {
+    x: Fr,
+    y: Fr,
+    namespaced: bool,
+}
+
+impl ConstraintSynthesizer<Fr> for Square {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let x = FpVar::new_witness(cs.clone(), || Ok(self.x))?;
+        let y = FpVar::new_input(cs.clone(), || Ok(self.y))?;
+        if self.namespaced {
+            let _ns = ns!(cs, "outer");
+            let _inner = ns!(cs, "square");
+            return x.square()?.enforce_equal(&y);
+        }
+        x.square()?.enforce_equal(&y)
+    }
+}
+
+#[test]
+fn test_first_unsatisfied_reports_namespace_path_or_index() {
+    let square = |y: u64, namespaced| Square { x: Fr::from(3u64), y: Fr::from(y), namespaced };
+
+    assert_eq!(first_unsatisfied(square(9, true)).unwrap(), None);
+    assert_eq!(first_unsatisfied(square(10, true)).unwrap(), Some("outer/square".to_string()));
+    assert_eq!(first_unsatisfied(square(10, false)).unwrap(), Some("#1".to_string()));
+}
+
+// Counts the spans opened through the subscriber it is part of
+struct SpanCounter(Arc<AtomicUsize>);
+
+impl<S: Subscriber> Layer<S> for SpanCounter {
+    fn new_span(&self, _: &span::Attributes<'_>, _: &span::Id, _: Context<'_, S>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_first_unsatisfied_keeps_the_application_subscriber() {
+    let spans = Arc::new(AtomicUsize::new(0));
+    let subscriber = Registry::default()
+        .with(ConstraintLayer::new(TracingMode::OnlyConstraints))
+        .with(SpanCounter(spans.clone()));
+
+    // An application subscriber with a constraint layer is used as is, so it sees the namespaces synthesis opens
+    tracing::subscriber::with_default(subscriber, || {
+        let square = Square { x: Fr::from(3u64), y: Fr::from(10u64), namespaced: true };
+        assert_eq!(first_unsatisfied(square).unwrap(), Some("outer/square".to_string()));
+    });
+    assert!(spans.load(Ordering::SeqCst) >= 2);
+}
+
+#[test]
+fn test_merkle_levels_are_named_by_index() {
+    let path = MerklePath { siblings: vec![Fr::from(1u64), Fr::from(2u64)], directions: vec![false, true] };
+    let leaf = Fr::from(6u64);
+    let circuit = calculate_citizenship_verification_witness(2, Fr::from(0u64), path.compute_root(leaf), leaf, path);
+
+    let subscriber = Registry::default().with(ConstraintLayer::new(TracingMode::OnlyConstraints));
+    let names = tracing::subscriber::with_default(subscriber, || {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.constraint_names().unwrap()
+    });
+    for level in ["merkle_level_0/", "merkle_level_1/"] {
+        assert!(names.iter().any(|name| name.contains(level)), "no constraint in {}", level);
+    }
+}
@@ -0,0 +1,98 @@
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use serde_json::json;
+use zkp_core::error::ZkpError;
+use zkp_core::proof_system::{prove_age, setup_age_verification_circuit, verify_age};
+use zkp_core::snarkjs::{
+    SnarkjsProof, SnarkjsVerifyingKey, export_proof, export_public_inputs, export_verifying_key, import_proof,
+    import_public_inputs, import_verifying_key,
+};
+
+// ----------------------------
+// snarkjs Interop Tests
+// ----------------------------
+
+// A proof.json as snarkjs writes it, with every point set to the curve generators
+fn generator_proof_json() -> serde_json::Value {
+    json!({
+        "pi_a": ["1", "2", "1"],
+        "pi_b": [
+            [
+                "10857046999023057135944570762232829481370756359578518086990519993285655852781",
+                "11559732032986387107991004021392285783925812861821192530917403151452391805634"
+            ],
+            [
+                "8495653923123431417604973247489272438418190587263600148770280649306958101930",
+                "4082367875863433681332203403145435568316851327593401208105741076214120093531"
+            ],
+            ["1", "0"]
+        ],
+        "pi_c": ["0", "1", "0"],
+        "protocol": "groth16",
+        "curve": "bn128"
+    })
+}
+
+#[test]
+fn test_snarkjs_proof_layout() {
+    let json: SnarkjsProof = serde_json::from_value(generator_proof_json()).unwrap();
+    let proof = import_proof(&json).unwrap();
+
+    // G2 coordinates are real part first; the point at infinity is (0, 1, 0)
+    assert_eq!(proof.a, G1Affine::generator());
+    assert_eq!(proof.b, G2Affine::generator());
+    assert!(proof.c.is_zero());
+    assert_eq!(serde_json::to_value(export_proof(&proof)).unwrap(), generator_proof_json());
+}
+
+#[test]
+fn test_snarkjs_round_trip_verifies() {
+    let (pk, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let nonce = Fr::from(424242u64);
+    let proof = prove_age(&pk, nonce, 8, 25, 18, None).unwrap();
+
+    let vk_json = serde_json::to_string(&export_verifying_key(&vk.key)).unwrap();
+    let proof_json = serde_json::to_string(&export_proof(&proof)).unwrap();
+    let public_json = serde_json::to_string(&export_public_inputs(&[nonce, Fr::from(18u64)])).unwrap();
+    assert!(vk_json.contains("\"nPublic\":2") && vk_json.contains("\"IC\":"));
+    assert_eq!(public_json, "[\"424242\",\"18\"]");
+
+    let imported_vk = import_verifying_key(&serde_json::from_str(&vk_json).unwrap()).unwrap();
+    let imported_proof = import_proof(&serde_json::from_str(&proof_json).unwrap()).unwrap();
+    let public_inputs = import_public_inputs(&serde_json::from_str::<Vec<String>>(&public_json).unwrap()).unwrap();
+    assert_eq!(imported_vk, vk.key);
+    assert_eq!(imported_proof, proof);
+    assert_eq!(public_inputs, [nonce, Fr::from(18u64)]);
+    assert!(verify_age(&vk, &imported_proof, nonce, 8, 18, None).unwrap());
+}
+
+#[test]
+fn test_snarkjs_import_rejects_malformed_objects() {
+    let invalid = |edit: fn(&mut serde_json::Value)| {
+        let mut value = generator_proof_json();
+        edit(&mut value);
+        import_proof(&serde_json::from_value(value).unwrap())
+    };
+
+    assert!(matches!(invalid(|v| v["curve"] = json!("bls12381")), Err(ZkpError::InvalidFile(_))));
+    // Off the curve
+    assert!(matches!(invalid(|v| v["pi_a"][1] = json!("3")), Err(ZkpError::InvalidFile(_))));
+    // Past the modulus, even though it reduces to the generator's y
+    let unreduced = |v: &mut serde_json::Value| {
+        v["pi_a"][1] = json!("21888242871839275222246405745257275088696311157297823662689037894645226208585")
+    };
+    assert!(matches!(invalid(unreduced), Err(ZkpError::InvalidFile(_))));
+    // Not affine
+    assert!(matches!(invalid(|v| v["pi_a"][2] = json!("2")), Err(ZkpError::InvalidFile(_))));
+    // The point at infinity only as (0, 1, 0), not any point with z = 0
+    assert!(matches!(invalid(|v| v["pi_c"] = json!(["5", "7", "0"])), Err(ZkpError::InvalidFile(_))));
+    assert!(matches!(invalid(|v| v["pi_a"] = json!(["1", "2", "0"])), Err(ZkpError::InvalidFile(_))));
+    let g2_infinity = |v: &mut serde_json::Value| v["pi_b"] = json!([["1", "0"], ["1", "0"], ["0", "0"]]);
+    assert!(matches!(invalid(g2_infinity), Err(ZkpError::InvalidFile(_))));
+
+    let (_, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let mut vk_json: SnarkjsVerifyingKey = export_verifying_key(&vk.key);
+    vk_json.n_public = 3;
+    assert!(matches!(import_verifying_key(&vk_json), Err(ZkpError::InvalidFile(_))));
+    assert!(matches!(import_public_inputs(&["-1".to_string()]), Err(ZkpError::InvalidInput(_))));
+}
@@ -0,0 +1,64 @@
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup, pairing::Pairing};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_groth16::VerifyingKey;
+use sha3::{Digest, Keccak256};
+use zkp_core::error::ZkpError;
+use zkp_core::proof_system::{prove_age, setup_age_verification_circuit};
+use zkp_core::solidity::{calldata, verifier_contract, verify_proof_signature};
+
+// ----------------------------
+// Solidity Verifier Tests
+// ----------------------------
+
+// The generated contract's check, e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1, run on calldata
+// words the way the contract reads them
+fn contract_accepts(vk: &VerifyingKey<Bn254>, data: &[u8]) -> bool {
+    let words: Vec<Fq> = data[4..].chunks(32).map(Fq::from_be_bytes_mod_order).collect();
+    let a = G1Affine::new(words[0], words[1]);
+    let b = G2Affine::new(Fq2::new(words[3], words[2]), Fq2::new(words[5], words[4]));
+    let c = G1Affine::new(words[6], words[7]);
+    let vk_x = words[8..].iter().zip(&vk.gamma_abc_g1[1..]).fold(vk.gamma_abc_g1[0].into_group(), |acc, (x, ic)| {
+        acc + *ic * Fr::from_le_bytes_mod_order(&x.into_bigint().to_bytes_le())
+    });
+
+    Bn254::multi_pairing(
+        [-a, vk.alpha_g1, vk_x.into_affine(), c],
+        [b, vk.beta_g2, vk.gamma_g2, vk.delta_g2],
+    )
+    .is_zero()
+}
+
+#[test]
+fn test_calldata_matches_contract_check() {
+    let (pk, vk) = setup_age_verification_circuit(8, false).unwrap();
+    let nonce = Fr::from(424242u64);
+    let proof = prove_age(&pk, nonce, 8, 25, 18, None).unwrap();
+
+    let data = calldata(&proof, &[nonce, Fr::from(18u64)]);
+    assert_eq!(data.len(), 4 + 32 * 10);
+    assert_eq!(data[..4], Keccak256::digest(verify_proof_signature(2).as_bytes())[..4]);
+    assert!(contract_accepts(&vk.key, &data));
+
+    // Other public inputs do not verify
+    assert!(!contract_accepts(&vk.key, &calldata(&proof, &[nonce, Fr::from(21u64)])));
+}
+
+#[test]
+fn test_verifier_contract_embeds_the_key() {
+    let (_, vk) = setup_age_verification_circuit(8, true).unwrap();
+    let contract = verifier_contract("AgeVerifier", &vk.key).unwrap();
+
+    assert!(contract.contains("contract AgeVerifier {"));
+    assert!(contract.contains("uint256[3] calldata input"));
+    let alpha_x = vk.key.alpha_g1.x().unwrap().into_bigint();
+    assert!(contract.contains(&format!("uint256 constant ALPHA_X = {};", alpha_x)));
+    for i in 0..4 {
+        assert!(contract.contains(&format!("IC{}_X", i)));
+    }
+    assert!(!contract.contains("IC4_X"));
+
+    for name in ["", "1Verifier", "Age Verifier", "Age-Verifier"] {
+        assert!(matches!(verifier_contract(name, &vk.key), Err(ZkpError::InvalidInput(_))));
+    }
+}
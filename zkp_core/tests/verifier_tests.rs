@@ -0,0 +1,60 @@
+use ark_bn254::Fr;
+use zkp_core::circuits::age_verification::witness_calculator::{AgeConfig, AgePublicInputs, AgeVerification};
+use zkp_core::circuits::citizenship_verification::witness_calculator::{CitizenshipPublicInputs, CitizenshipVerification};
+use zkp_core::error::ZkpError;
+use zkp_core::proof_system::{prove_age, setup_age_verification_circuit};
+use zkp_core::verifier::{PreparedKey, Verifier};
+
+// ----------------------------
+// Prepared Verifier Tests
+// ----------------------------
+
+#[test]
+fn test_registered_key_verifies_with_cached_inputs() {
+    let config = AgeConfig::default();
+    let (pk, vk) = setup_age_verification_circuit(config.num_bits, false).unwrap();
+    let verifier = Verifier::new();
+    let key = verifier.register::<AgeVerification>(&vk, &config).unwrap();
+
+    // Common thresholds are cached, others are computed on the fly; both must verify the same way
+    key.precompute_input(1, &[Fr::from(16u64), Fr::from(18u64), Fr::from(21u64)]).unwrap();
+    for min_age in [18, 20] {
+        let nonce = Fr::from(424242u64 + min_age);
+        let proof = prove_age(&pk, nonce, config.num_bits, 25, min_age, None).unwrap();
+        let public = AgePublicInputs { nonce, min_age, max_age: None };
+        assert!(verifier.verify::<AgeVerification>(&config, &proof, &public).unwrap());
+
+        let wrong = AgePublicInputs { min_age: 21, ..public };
+        assert!(!verifier.verify::<AgeVerification>(&config, &proof, &wrong).unwrap());
+    }
+
+    // The age circuit has no third public input when unbounded
+    assert!(matches!(key.precompute_input(2, &[Fr::from(30u64)]), Err(ZkpError::InvalidInput(_))));
+}
+
+#[test]
+fn test_prepared_key_is_bound_to_its_circuit() {
+    let config = AgeConfig::default();
+    let (pk, vk) = setup_age_verification_circuit(config.num_bits, false).unwrap();
+
+    // A key cannot be registered for a circuit it was not generated for
+    assert!(matches!(PreparedKey::new::<CitizenshipVerification>(&vk, &4), Err(ZkpError::KeyCircuitMismatch(_))));
+
+    // Nor used to verify one, even though both take two public inputs
+    let key = PreparedKey::new::<AgeVerification>(&vk, &config).unwrap();
+    let nonce = Fr::from(1u64);
+    let proof = prove_age(&pk, nonce, config.num_bits, 25, 18, None).unwrap();
+    let public = CitizenshipPublicInputs { nonce, merkle_root: Fr::from(18u64) };
+    assert!(matches!(key.verify::<CitizenshipVerification>(&4, &proof, &public), Err(ZkpError::KeyCircuitMismatch(_))));
+
+    // Nor a different shape of the circuit it was prepared for
+    let public = AgePublicInputs { nonce, min_age: 18, max_age: None };
+    let wider = AgeConfig { num_bits: config.num_bits + 1, ..config };
+    assert!(matches!(key.verify::<AgeVerification>(&wider, &proof, &public), Err(ZkpError::KeyCircuitMismatch(_))));
+    assert!(key.verify::<AgeVerification>(&config, &proof, &public).unwrap());
+
+    // Unknown fingerprints are reported rather than treated as a failed proof
+    let verifier = Verifier::new();
+    let result = verifier.verify::<AgeVerification>(&config, &proof, &public);
+    assert!(matches!(result, Err(ZkpError::KeyCircuitMismatch(_))));
+}